@@ -0,0 +1,160 @@
+//! Schnorr adaptor signatures over xsk233.
+//!
+//! A pre-signature on `msg` under an adaptor point `T = t*G` is
+//! `(R', s')` with `R' = k*G` and `s' = k + c*x`, where the challenge `c`
+//! is the [`schnorr`](crate::schnorr) challenge of `R = R' + T`. Anyone can
+//! check it against `T` without knowing `t`, and:
+//!
+//! * whoever knows `t` turns it into the [`Signature`] `(R, s' + t)` with
+//!   [`PreSignature::adapt`];
+//! * whoever holds the pre-signature learns `t = s - s'` from that
+//!   signature with [`PreSignature::extract`].
+//!
+//! This is what atomic swaps rely on: publishing the adapted signature to
+//! claim one side reveals the secret that unlocks the other.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::group::Xsk233Projective;
+use crate::keys::SecretKey;
+use crate::schnorr::{self, SIGNATURE_SIZE, Signature};
+use crate::xsk233::Fr;
+use crate::{scalar_from_bytes, scalar_to_bytes};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::rand::Rng;
+
+/// Size of an encoded pre-signature: `R'` and `s'`.
+pub const PRE_SIGNATURE_SIZE: usize = SIGNATURE_SIZE;
+
+/// Pre-signature `(R', s')` under an adaptor point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreSignature {
+    r: Xsk233Affine,
+    s: Fr,
+}
+
+impl PreSignature {
+    /// Pre-signs `msg` under `adaptor`, which must not be the neutral
+    /// element.
+    pub fn sign<R: Rng + ?Sized>(
+        rng: &mut R,
+        secret: &SecretKey,
+        adaptor: &Xsk233Affine,
+        msg: &[u8],
+    ) -> Self {
+        let k = schnorr::nonce(rng, secret.scalar());
+        let r = (Xsk233Affine::generator() * *k).into_affine();
+        let c = schnorr::challenge(&(r + adaptor).into_affine(), &secret.public_key(), msg);
+        Self {
+            r,
+            s: *k + c * secret.scalar(),
+        }
+    }
+
+    /// Checks that the pre-signature adapts with `log_G(adaptor)` into a
+    /// signature on `msg` under `public`: `s'*G - c*Y = R'`.
+    pub fn verify(&self, public: &Xsk233Affine, adaptor: &Xsk233Affine, msg: &[u8]) -> bool {
+        if public.is_zero() || adaptor.is_zero() {
+            return false;
+        }
+        let c = schnorr::challenge(&self.nonce(adaptor), public, msg);
+        Xsk233Projective::mul_add_generator(&self.s, &-c, public) == self.r
+    }
+
+    /// Completes the pre-signature with the adaptor secret `t`. The result
+    /// is only valid if `t` matches the adaptor point of the
+    /// pre-signature.
+    pub fn adapt(&self, secret: &Fr) -> Signature {
+        let adaptor = (Xsk233Affine::generator() * secret).into_affine();
+        Signature::new(self.nonce(&adaptor), self.s + secret)
+    }
+
+    /// Recovers the adaptor secret from the completed `signature`, or
+    /// returns `None` if it was not adapted from this pre-signature with
+    /// the secret of `adaptor`.
+    pub fn extract(&self, signature: &Signature, adaptor: &Xsk233Affine) -> Option<Fr> {
+        let t = *signature.z() - self.s;
+        (*signature.r() == self.nonce(adaptor) && Xsk233Affine::generator() * t == *adaptor)
+            .then_some(t)
+    }
+
+    /// The nonce `R = R' + T` of the completed signature.
+    fn nonce(&self, adaptor: &Xsk233Affine) -> Xsk233Affine {
+        (self.r + adaptor).into_affine()
+    }
+
+    pub fn to_bytes(&self) -> [u8; PRE_SIGNATURE_SIZE] {
+        let mut bytes = [0u8; PRE_SIGNATURE_SIZE];
+        bytes[..COMPRESSED_POINT_SIZE].copy_from_slice(&self.r.to_bytes());
+        bytes[COMPRESSED_POINT_SIZE..].copy_from_slice(&scalar_to_bytes(&self.s));
+        bytes
+    }
+
+    /// Decodes a pre-signature; returns `None` if `R'` is invalid or `s'`
+    /// is not reduced.
+    pub fn from_bytes(bytes: &[u8; PRE_SIGNATURE_SIZE]) -> Option<Self> {
+        let (r, s) = bytes.split_at(COMPRESSED_POINT_SIZE);
+        Some(Self {
+            r: Xsk233Affine::from_bytes(r.try_into().unwrap())?,
+            s: scalar_from_bytes(s.try_into().unwrap())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::{UniformRand, test_rng};
+
+    #[test]
+    fn test_atomic_swap() {
+        let mut rng = test_rng();
+        let alice = SecretKey::random(&mut rng);
+        let bob = SecretKey::random(&mut rng);
+        let tx_to_alice = b"bob pays 1 coin to alice";
+        let tx_to_bob = b"alice pays 5 tokens to bob";
+
+        // Alice picks the swap secret and shares the adaptor point only
+        let t = Fr::rand(&mut rng);
+        let adaptor = (Xsk233Affine::generator() * t).into_affine();
+
+        // both sides pre-sign their payment and check the other one
+        let pre_bob = PreSignature::sign(&mut rng, &bob, &adaptor, tx_to_alice);
+        let pre_alice = PreSignature::sign(&mut rng, &alice, &adaptor, tx_to_bob);
+        let pre_bob = PreSignature::from_bytes(&pre_bob.to_bytes()).unwrap();
+        assert!(pre_bob.verify(&bob.public_key(), &adaptor, tx_to_alice));
+        assert!(pre_alice.verify(&alice.public_key(), &adaptor, tx_to_bob));
+        // a pre-signature alone is not a signature
+        let unadapted = Signature::new(pre_bob.r, pre_bob.s);
+        assert!(!unadapted.verify(&bob.public_key(), tx_to_alice));
+
+        // Alice claims her coin, which publishes the signature of Bob
+        let sig_bob = pre_bob.adapt(&t);
+        assert!(sig_bob.verify(&bob.public_key(), tx_to_alice));
+
+        // Bob learns t from it and claims his tokens
+        let extracted = pre_bob.extract(&sig_bob, &adaptor).unwrap();
+        assert_eq!(extracted, t);
+        let sig_alice = pre_alice.adapt(&extracted);
+        assert!(sig_alice.verify(&alice.public_key(), tx_to_bob));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let public = secret.public_key();
+        let t = Fr::rand(&mut rng);
+        let adaptor = (Xsk233Affine::generator() * t).into_affine();
+        let other = (Xsk233Affine::generator() * Fr::rand(&mut rng)).into_affine();
+
+        let pre = PreSignature::sign(&mut rng, &secret, &adaptor, b"msg");
+        assert!(!pre.verify(&public, &other, b"msg"));
+        assert!(!pre.verify(&public, &adaptor, b"other"));
+        assert!(!pre.verify(&public, &Xsk233Affine::zero(), b"msg"));
+
+        // a signature from another pre-signature reveals nothing
+        let unrelated = PreSignature::sign(&mut rng, &secret, &adaptor, b"msg").adapt(&t);
+        assert_eq!(pre.extract(&unrelated, &adaptor), None);
+        assert_eq!(pre.extract(&pre.adapt(&t), &other), None);
+    }
+}
@@ -0,0 +1,231 @@
+//! Blind Schnorr signatures over xsk233.
+//!
+//! An issuer signs a message chosen by a user without seeing it, and
+//! without being able to link the resulting [`Signature`], which verifies
+//! as any other one under the issuer key `Y`, to the session that produced
+//! it. The three moves of the protocol are:
+//!
+//! 1. the issuer opens an [`IssuerSession`] and sends `R = k*G`;
+//! 2. the user opens a [`UserSession`] with blinding factors `alpha` and
+//!    `beta`, computes `R' = R + alpha*G + beta*Y` and
+//!    `c' = H(R' || Y || msg)`, and sends `c = c' + beta`;
+//! 3. the issuer answers `s = k + c*x`, and the user checks it and outputs
+//!    `(R', s + alpha)`.
+//!
+//! Plain blind Schnorr is only secure for sessions run one after the
+//! other: with many concurrent sessions, the ROS attack of Benhamouda et
+//! al. forges one signature more than the issuer completed. The clause
+//! variant of Fuchsbauer, Plouviez and Seurin (Eurocrypt 2020),
+//! [`ClauseIssuerSession`] and [`ClauseUserSession`], resists it: every
+//! session runs two instances of the protocol, and the issuer completes
+//! only one of them, at random.
+
+use crate::affine::Xsk233Affine;
+use crate::group::Xsk233Projective;
+use crate::keys::SecretKey;
+use crate::schnorr::{self, Signature};
+use crate::xsk233::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::UniformRand;
+use ark_std::fmt::{Debug, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use zeroize::Zeroize;
+
+/// Issuer side of a blind signing session.
+pub struct IssuerSession {
+    k: Fr,
+}
+
+impl IssuerSession {
+    /// Starts a session and returns the commitment `R` for the user.
+    pub fn new<R: Rng + ?Sized>(rng: &mut R, secret: &SecretKey) -> (Self, Xsk233Affine) {
+        let k = schnorr::nonce(rng, secret.scalar());
+        let r = (Xsk233Affine::generator() * *k).into_affine();
+        (Self { k: *k }, r)
+    }
+
+    /// Answers the blinded challenge of the user, consuming the session:
+    /// answering twice with the same nonce reveals the key.
+    pub fn respond(self, secret: &SecretKey, challenge: &Fr) -> Fr {
+        self.k + *challenge * secret.scalar()
+    }
+}
+
+impl Debug for IssuerSession {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("IssuerSession").finish_non_exhaustive()
+    }
+}
+
+impl Drop for IssuerSession {
+    fn drop(&mut self) {
+        self.k.zeroize();
+    }
+}
+
+/// User side of a blind signing session.
+#[derive(Clone, Debug)]
+pub struct UserSession {
+    issuer_key: Xsk233Affine,
+    r: Xsk233Affine,
+    c: Fr,
+    alpha: Fr,
+    blinded_r: Xsk233Affine,
+}
+
+impl UserSession {
+    /// Blinds the commitment `r` of the issuer for `msg`, and returns the
+    /// challenge to send to the issuer.
+    pub fn new<R: Rng + ?Sized>(
+        rng: &mut R,
+        issuer_key: &Xsk233Affine,
+        r: &Xsk233Affine,
+        msg: &[u8],
+    ) -> (Self, Fr) {
+        let alpha = Fr::rand(rng);
+        let beta = Fr::rand(rng);
+        // R' = R + alpha*G + beta*Y, in constant time as alpha and beta
+        // unlink the signature from the session
+        let blinded_r = (Xsk233Affine::generator() * alpha + *issuer_key * beta + r).into_affine();
+        let c = schnorr::challenge(&blinded_r, issuer_key, msg) + beta;
+        let session = Self {
+            issuer_key: *issuer_key,
+            r: *r,
+            c,
+            alpha,
+            blinded_r,
+        };
+        (session, c)
+    }
+
+    /// Checks the response of the issuer, `s*G = R + c*Y`, and unblinds
+    /// it into a signature on the message.
+    pub fn finish(self, response: &Fr) -> Option<Signature> {
+        if self.issuer_key.is_zero()
+            || Xsk233Projective::mul_add_generator(response, &-self.c, &self.issuer_key) != self.r
+        {
+            return None;
+        }
+        Some(Signature::new(self.blinded_r, *response + self.alpha))
+    }
+}
+
+/// Answer of the issuer in the clause variant: which of the two instances
+/// it completed, and its response for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClauseResponse {
+    pub clause: usize,
+    pub s: Fr,
+}
+
+/// Issuer side of a clause blind signing session.
+#[derive(Debug)]
+pub struct ClauseIssuerSession([IssuerSession; 2]);
+
+impl ClauseIssuerSession {
+    /// Starts both instances and returns their commitments.
+    pub fn new<R: Rng + ?Sized>(rng: &mut R, secret: &SecretKey) -> (Self, [Xsk233Affine; 2]) {
+        let (s0, r0) = IssuerSession::new(rng, secret);
+        let (s1, r1) = IssuerSession::new(rng, secret);
+        (Self([s0, s1]), [r0, r1])
+    }
+
+    /// Completes one of the two instances, chosen at random.
+    pub fn respond<R: Rng + ?Sized>(
+        self,
+        rng: &mut R,
+        secret: &SecretKey,
+        challenges: &[Fr; 2],
+    ) -> ClauseResponse {
+        let clause = rng.gen_range(0..2);
+        let [s0, s1] = self.0;
+        let session = if clause == 0 { s0 } else { s1 };
+        ClauseResponse {
+            clause,
+            s: session.respond(secret, &challenges[clause]),
+        }
+    }
+}
+
+/// User side of a clause blind signing session.
+#[derive(Clone, Debug)]
+pub struct ClauseUserSession([UserSession; 2]);
+
+impl ClauseUserSession {
+    /// Blinds both commitments of the issuer for `msg`, and returns the
+    /// two challenges to send back.
+    pub fn new<R: Rng + ?Sized>(
+        rng: &mut R,
+        issuer_key: &Xsk233Affine,
+        commitments: &[Xsk233Affine; 2],
+        msg: &[u8],
+    ) -> (Self, [Fr; 2]) {
+        let (u0, c0) = UserSession::new(rng, issuer_key, &commitments[0], msg);
+        let (u1, c1) = UserSession::new(rng, issuer_key, &commitments[1], msg);
+        (Self([u0, u1]), [c0, c1])
+    }
+
+    /// Unblinds the instance completed by the issuer.
+    pub fn finish(self, response: &ClauseResponse) -> Option<Signature> {
+        let [u0, u1] = self.0;
+        match response.clause {
+            0 => u0.finish(&response.s),
+            1 => u1.finish(&response.s),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_blind_signature() {
+        let mut rng = test_rng();
+        let issuer = SecretKey::random(&mut rng);
+        let issuer_key = issuer.public_key();
+        let token = b"token serial 0042";
+
+        let (issuer_session, r) = IssuerSession::new(&mut rng, &issuer);
+        let (user_session, c) = UserSession::new(&mut rng, &issuer_key, &r, token);
+        let s = issuer_session.respond(&issuer, &c);
+
+        assert_eq!(user_session.clone().finish(&(s + Fr::from(1u64))), None);
+        let signature = user_session.finish(&s).unwrap();
+        assert!(signature.verify(&issuer_key, token));
+        assert!(!signature.verify(&issuer_key, b"token serial 0043"));
+
+        // the issuer saw neither the nonce nor the challenge of the signature
+        assert_ne!(*signature.r(), r);
+        assert_ne!(schnorr::challenge(signature.r(), &issuer_key, token), c);
+        assert_ne!(*signature.z(), s);
+    }
+
+    #[test]
+    fn test_clause_blind_signature() {
+        let mut rng = test_rng();
+        let issuer = SecretKey::random(&mut rng);
+        let issuer_key = issuer.public_key();
+
+        let mut clauses = [0; 2];
+        for i in 0..8u8 {
+            let msg = [b'm', i];
+            let (issuer_session, commitments) = ClauseIssuerSession::new(&mut rng, &issuer);
+            let (user_session, challenges) =
+                ClauseUserSession::new(&mut rng, &issuer_key, &commitments, &msg);
+            let response = issuer_session.respond(&mut rng, &issuer, &challenges);
+            clauses[response.clause] += 1;
+
+            let wrong = ClauseResponse {
+                clause: 1 - response.clause,
+                ..response
+            };
+            assert_eq!(user_session.clone().finish(&wrong), None);
+            let signature = user_session.finish(&response).unwrap();
+            assert!(signature.verify(&issuer_key, &msg));
+        }
+        assert!(clauses.iter().all(|n| *n > 0));
+    }
+}
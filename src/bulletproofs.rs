@@ -0,0 +1,576 @@
+//! Bulletproofs range proofs over xsk233.
+//!
+//! A [`RangeProof`] shows that Pedersen commitments `V_j = v_j*B + g_j*B~`
+//! open to values `v_j` in `[0, 2^n)`, for `n` one of 8, 16, 32 or 64,
+//! without revealing them. Proofs for `m` values, a power of two, are
+//! aggregated: they only grow by `2*log2(m)` points over a single proof.
+//!
+//! The protocol is the one of Bünz et al. as laid out by the dalek
+//! implementation, with the challenges drawn from a [`Transcript`] and the
+//! [`inner_product`] argument in the last step. Verification takes a single
+//! multi-scalar multiplication, over the [`BulletproofGens`] and the
+//! commitments.
+//!
+//! All generators are [`hash_to_curve_vartime`] of fixed strings, so
+//! nobody knows discrete logarithms between them, except `B` which is the
+//! standard generator.
+
+pub mod inner_product;
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::group::Xsk233Projective;
+use crate::hash::hash_to_curve_vartime;
+use crate::transcript::Transcript;
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, scalar_from_bytes, scalar_to_bytes};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, Zero};
+use ark_std::UniformRand;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use inner_product::{InnerProductProof, inner_product};
+use std::error::Error;
+use std::iter;
+
+const GENERATORS_DST: &[u8] = b"xsk233-bulletproofs-v1-generators";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// The bit size is not 8, 16, 32 or 64.
+    InvalidBitsize,
+    /// The number of values is not a power of two.
+    InvalidAggregation,
+    /// There are not enough generators for the proof.
+    InvalidGeneratorsLength,
+    /// There are not as many blinding factors as values.
+    WrongNumBlindingFactors,
+    /// A value does not fit in the bit size.
+    ValueOutOfRange,
+    /// The proof is invalid.
+    VerificationError,
+    /// The encoding of the proof is invalid.
+    FormatError,
+}
+
+impl Display for ProofError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ProofError::InvalidBitsize => write!(f, "invalid bit size"),
+            ProofError::InvalidAggregation => write!(f, "invalid number of aggregated values"),
+            ProofError::InvalidGeneratorsLength => write!(f, "not enough generators"),
+            ProofError::WrongNumBlindingFactors => write!(f, "wrong number of blinding factors"),
+            ProofError::ValueOutOfRange => write!(f, "value out of range"),
+            ProofError::VerificationError => write!(f, "invalid proof"),
+            ProofError::FormatError => write!(f, "invalid proof encoding"),
+        }
+    }
+}
+
+impl Error for ProofError {}
+
+/// Generators `B` and `B~` of the Pedersen commitments to the values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PedersenGens {
+    pub b: Xsk233Affine,
+    pub b_blinding: Xsk233Affine,
+}
+
+impl PedersenGens {
+    /// `value*B + blinding*B~`, in constant time since both scalars are
+    /// secret.
+    pub fn commit(&self, value: &Fr, blinding: &Fr) -> Xsk233Projective {
+        self.b * value + self.b_blinding * blinding
+    }
+}
+
+impl Default for PedersenGens {
+    fn default() -> Self {
+        Self {
+            b: Xsk233Affine::generator(),
+            b_blinding: hash_to_curve_vartime(GENERATORS_DST, b"B_blinding"),
+        }
+    }
+}
+
+/// Vectors of generators `G` and `H` for the bits of all the values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BulletproofGens {
+    g_vec: Vec<Xsk233Affine>,
+    h_vec: Vec<Xsk233Affine>,
+}
+
+impl BulletproofGens {
+    /// Derives `capacity` generators of each vector, enough for proofs of
+    /// `m` values of `n` bits with `n*m <= capacity`.
+    pub fn new(capacity: usize) -> Self {
+        let derive = |label: u8, i: usize| {
+            let input = [&[label][..], &(i as u64).to_le_bytes()].concat();
+            hash_to_curve_vartime(GENERATORS_DST, &input)
+        };
+        Self {
+            g_vec: (0..capacity).map(|i| derive(b'G', i)).collect(),
+            h_vec: (0..capacity).map(|i| derive(b'H', i)).collect(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.g_vec.len()
+    }
+
+    pub fn g_vec(&self) -> &[Xsk233Affine] {
+        &self.g_vec
+    }
+
+    pub fn h_vec(&self) -> &[Xsk233Affine] {
+        &self.h_vec
+    }
+}
+
+/// Aggregated range proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeProof {
+    a: Xsk233Affine,
+    s: Xsk233Affine,
+    t1: Xsk233Affine,
+    t2: Xsk233Affine,
+    t_x: Fr,
+    t_x_blinding: Fr,
+    e_blinding: Fr,
+    ipp_proof: InnerProductProof,
+}
+
+impl RangeProof {
+    /// Proves that `value` is in `[0, 2^n)`, and returns the proof with
+    /// the commitment `value*B + blinding*B~`.
+    pub fn prove_single<R: Rng + ?Sized>(
+        rng: &mut R,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value: u64,
+        blinding: &Fr,
+        n: usize,
+    ) -> Result<(Self, Xsk233Affine), ProofError> {
+        let (proof, commitments) =
+            Self::prove_multiple(rng, bp_gens, pc_gens, transcript, &[value], &[*blinding], n)?;
+        Ok((proof, commitments[0]))
+    }
+
+    /// Proves that all `values` are in `[0, 2^n)`, and returns the proof
+    /// with the commitments to the values. The prover is not constant
+    /// time.
+    pub fn prove_multiple<R: Rng + ?Sized>(
+        rng: &mut R,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Fr],
+        n: usize,
+    ) -> Result<(Self, Vec<Xsk233Affine>), ProofError> {
+        let m = values.len();
+        check_sizes(bp_gens, n, m)?;
+        if blindings.len() != m {
+            return Err(ProofError::WrongNumBlindingFactors);
+        }
+        if n < 64 && values.iter().any(|v| v >> n != 0) {
+            return Err(ProofError::ValueOutOfRange);
+        }
+        let nm = n * m;
+        let (g, h) = (&bp_gens.g_vec[..nm], &bp_gens.h_vec[..nm]);
+
+        let commitments = Xsk233Projective::normalize_batch(
+            &values
+                .iter()
+                .zip(blindings)
+                .map(|(v, blinding)| pc_gens.commit(&Fr::from(*v), blinding))
+                .collect::<Vec<_>>(),
+        );
+        transcript_header(transcript, n, m, &commitments);
+
+        // A commits to the bits a_L and to a_R = a_L - 1, S to the blinding
+        // vectors s_L and s_R
+        let a_l: Vec<_> = values
+            .iter()
+            .flat_map(|v| (0..n).map(move |i| Fr::from((v >> i) & 1)))
+            .collect();
+        let a_r: Vec<_> = a_l.iter().map(|bit| *bit - Fr::ONE).collect();
+        let alpha = Fr::rand(rng);
+        let a = commit_vectors(pc_gens, g, h, &alpha, &a_l, &a_r);
+        let s_l: Vec<_> = (0..nm).map(|_| Fr::rand(rng)).collect();
+        let s_r: Vec<_> = (0..nm).map(|_| Fr::rand(rng)).collect();
+        let rho = Fr::rand(rng);
+        let s = commit_vectors(pc_gens, g, h, &rho, &s_l, &s_r);
+        transcript.append_point(b"A", &a);
+        transcript.append_point(b"S", &s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        // l(X) = a_L - z + s_L*X
+        // r(X) = y^i*(a_R + z + s_R*X) + z^(2+j)*2^k, for bit k of value j
+        let z_and_2 = concat_z_and_2(&z, n, m);
+        let l0: Vec<_> = a_l.iter().map(|a| *a - z).collect();
+        let r0: Vec<_> = powers(&y)
+            .zip(&a_r)
+            .zip(&z_and_2)
+            .map(|((y_i, a), z2)| y_i * (*a + z) + z2)
+            .collect();
+        let r1: Vec<_> = powers(&y).zip(&s_r).map(|(y_i, s)| y_i * s).collect();
+        let t1 = inner_product(&l0, &r1) + inner_product(&s_l, &r0);
+        let t2 = inner_product(&s_l, &r1);
+        let tau1 = Fr::rand(rng);
+        let tau2 = Fr::rand(rng);
+        let t1_commitment = pc_gens.commit(&t1, &tau1).into_affine();
+        let t2_commitment = pc_gens.commit(&t2, &tau2).into_affine();
+        transcript.append_point(b"T1", &t1_commitment);
+        transcript.append_point(b"T2", &t2_commitment);
+        let x = transcript.challenge_scalar(b"x");
+
+        let l: Vec<_> = l0.iter().zip(&s_l).map(|(l0, l1)| *l0 + x * l1).collect();
+        let r: Vec<_> = r0.iter().zip(&r1).map(|(r0, r1)| *r0 + x * r1).collect();
+        let t_x = inner_product(&l, &r);
+        let t_x_blinding = tau2 * x.square()
+            + tau1 * x
+            + powers(&z)
+                .skip(2)
+                .zip(blindings)
+                .map(|(z_j, blinding)| z_j * blinding)
+                .sum::<Fr>();
+        let e_blinding = alpha + rho * x;
+        transcript.append_scalar(b"t_x", &t_x);
+        transcript.append_scalar(b"t_x_blinding", &t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &e_blinding);
+
+        // the inner-product argument for l and r, over H'_i = y^-i*H_i
+        let w = transcript.challenge_scalar(b"w");
+        let q = (pc_gens.b * w).into_affine();
+        let y_inv = y.inverse().expect("challenge is non-zero");
+        let h_prime: Vec<_> = h.iter().zip(powers(&y_inv)).map(|(h, y)| *h * y).collect();
+        let ipp_proof = InnerProductProof::prove(
+            transcript,
+            &q,
+            g.to_vec(),
+            Xsk233Projective::normalize_batch(&h_prime),
+            l,
+            r,
+        );
+
+        let proof = Self {
+            a,
+            s,
+            t1: t1_commitment,
+            t2: t2_commitment,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp_proof,
+        };
+        Ok((proof, commitments))
+    }
+
+    /// Verifies a proof for a single commitment.
+    pub fn verify_single(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        commitment: &Xsk233Affine,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple(bp_gens, pc_gens, transcript, &[*commitment], n)
+    }
+
+    /// Verifies that all `commitments` open to values in `[0, 2^n)`.
+    pub fn verify_multiple(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        commitments: &[Xsk233Affine],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        let m = commitments.len();
+        check_sizes(bp_gens, n, m)?;
+        let nm = n * m;
+
+        transcript_header(transcript, n, m, commitments);
+        transcript.append_point(b"A", &self.a);
+        transcript.append_point(b"S", &self.s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+        transcript.append_point(b"T1", &self.t1);
+        transcript.append_point(b"T2", &self.t2);
+        let x = transcript.challenge_scalar(b"x");
+        transcript.append_scalar(b"t_x", &self.t_x);
+        transcript.append_scalar(b"t_x_blinding", &self.t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &self.e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+        let (u_sq, u_inv_sq, s) = self.ipp_proof.verification_scalars(nm, transcript)?;
+        // weight of the check on t_x against the inner-product check
+        let c = transcript.challenge_scalar(b"c");
+
+        let (a, b) = (self.ipp_proof.a(), self.ipp_proof.b());
+        let y_inv = y.inverse().ok_or(ProofError::VerificationError)?;
+        let z_and_2 = concat_z_and_2(&z, n, m);
+        let g_scalars = s.iter().map(|s| -z - *a * s);
+        let h_scalars = s
+            .iter()
+            .rev()
+            .zip(powers(&y_inv))
+            .zip(&z_and_2)
+            .map(|((s_inv, y_inv), z2)| z + y_inv * (*z2 - *b * s_inv));
+        let value_scalars = powers(&z).skip(2).take(m).map(|z_j| c * z_j);
+        let b_scalar = w * (self.t_x - *a * b) + c * (delta(&y, &z, n, m) - self.t_x);
+        let b_blinding_scalar = -self.e_blinding - c * self.t_x_blinding;
+
+        let scalars: Vec<_> = [Fr::ONE, x, c * x, c * x * x]
+            .into_iter()
+            .chain(u_sq)
+            .chain(u_inv_sq)
+            .chain([b_blinding_scalar, b_scalar])
+            .chain(g_scalars)
+            .chain(h_scalars)
+            .chain(value_scalars)
+            .collect();
+        let bases = [
+            &[self.a, self.s, self.t1, self.t2][..],
+            self.ipp_proof.l_vec(),
+            self.ipp_proof.r_vec(),
+            &[pc_gens.b_blinding, pc_gens.b],
+            &bp_gens.g_vec[..nm],
+            &bp_gens.h_vec[..nm],
+            commitments,
+        ]
+        .concat();
+        if Xsk233Projective::msm_unchecked(&bases, &scalars).is_zero() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Size of the encoding, which grows with the number of bits of all
+    /// the values.
+    pub fn serialized_size(&self) -> usize {
+        4 * COMPRESSED_POINT_SIZE + 3 * SCALAR_SIZE + self.ipp_proof.serialized_size()
+    }
+
+    /// Encodes the proof as `A || S || T1 || T2 || t_x || t_x_blinding ||
+    /// e_blinding` followed by the inner-product proof.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        for p in [&self.a, &self.s, &self.t1, &self.t2] {
+            bytes.extend_from_slice(&p.to_bytes());
+        }
+        for scalar in [&self.t_x, &self.t_x_blinding, &self.e_blinding] {
+            bytes.extend_from_slice(&scalar_to_bytes(scalar));
+        }
+        bytes.extend_from_slice(&self.ipp_proof.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        const POINTS_SIZE: usize = 4 * COMPRESSED_POINT_SIZE;
+        const HEADER_SIZE: usize = POINTS_SIZE + 3 * SCALAR_SIZE;
+        if bytes.len() < HEADER_SIZE {
+            return Err(ProofError::FormatError);
+        }
+        let point = |i: usize| {
+            let chunk = &bytes[i * COMPRESSED_POINT_SIZE..(i + 1) * COMPRESSED_POINT_SIZE];
+            Xsk233Affine::from_bytes(chunk.try_into().unwrap()).ok_or(ProofError::FormatError)
+        };
+        let scalar = |i: usize| {
+            let start = POINTS_SIZE + i * SCALAR_SIZE;
+            scalar_from_bytes(bytes[start..start + SCALAR_SIZE].try_into().unwrap())
+                .ok_or(ProofError::FormatError)
+        };
+        Ok(Self {
+            a: point(0)?,
+            s: point(1)?,
+            t1: point(2)?,
+            t2: point(3)?,
+            t_x: scalar(0)?,
+            t_x_blinding: scalar(1)?,
+            e_blinding: scalar(2)?,
+            ipp_proof: InnerProductProof::from_bytes(&bytes[HEADER_SIZE..])?,
+        })
+    }
+}
+
+fn check_sizes(bp_gens: &BulletproofGens, n: usize, m: usize) -> Result<(), ProofError> {
+    if ![8, 16, 32, 64].contains(&n) {
+        return Err(ProofError::InvalidBitsize);
+    }
+    if !m.is_power_of_two() {
+        return Err(ProofError::InvalidAggregation);
+    }
+    if bp_gens.capacity() < n * m {
+        return Err(ProofError::InvalidGeneratorsLength);
+    }
+    Ok(())
+}
+
+fn transcript_header(
+    transcript: &mut Transcript,
+    n: usize,
+    m: usize,
+    commitments: &[Xsk233Affine],
+) {
+    transcript.append_message(b"dom-sep", b"rangeproof v1");
+    transcript.append_u64(b"n", n as u64);
+    transcript.append_u64(b"m", m as u64);
+    for v in commitments {
+        transcript.append_point(b"V", v);
+    }
+}
+
+/// `blinding*B~ + <a, G> + <b, H>`.
+fn commit_vectors(
+    pc_gens: &PedersenGens,
+    g: &[Xsk233Affine],
+    h: &[Xsk233Affine],
+    blinding: &Fr,
+    a: &[Fr],
+    b: &[Fr],
+) -> Xsk233Affine {
+    let bases = [g, h, &[pc_gens.b_blinding]].concat();
+    let scalars = [a, b, &[*blinding]].concat();
+    Xsk233Projective::msm_unchecked(&bases, &scalars).into_affine()
+}
+
+/// `1, x, x^2, ...`.
+fn powers(x: &Fr) -> impl Iterator<Item = Fr> + '_ {
+    iter::successors(Some(Fr::ONE), move |p| Some(*p * x))
+}
+
+/// `z^(2+j)*2^k` for bit `k` of value `j`.
+fn concat_z_and_2(z: &Fr, n: usize, m: usize) -> Vec<Fr> {
+    let two = Fr::from(2u64);
+    powers(z)
+        .skip(2)
+        .take(m)
+        .flat_map(|z_j| powers(&two).take(n).map(move |p| z_j * p))
+        .collect()
+}
+
+/// `delta(y, z) = (z - z^2)*<1, y^(nm)> - sum(z^(3+j))*<1, 2^n>`.
+fn delta(y: &Fr, z: &Fr, n: usize, m: usize) -> Fr {
+    let sum_y: Fr = powers(y).take(n * m).sum();
+    let sum_2: Fr = powers(&Fr::from(2u64)).take(n).sum();
+    let sum_z: Fr = powers(z).skip(3).take(m).sum();
+    (*z - z.square()) * sum_y - sum_z * sum_2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_single_range_proof() {
+        let mut rng = test_rng();
+        let bp_gens = BulletproofGens::new(64);
+        let pc_gens = PedersenGens::default();
+        let blinding = Fr::rand(&mut rng);
+
+        for (value, n) in [(0, 8), (255, 8), (1 << 40, 64), (u64::MAX, 64)] {
+            let (proof, commitment) = RangeProof::prove_single(
+                &mut rng,
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"test"),
+                value,
+                &blinding,
+                n,
+            )
+            .unwrap();
+            assert_eq!(commitment, pc_gens.commit(&Fr::from(value), &blinding));
+            let proof = RangeProof::from_bytes(&proof.to_bytes()).unwrap();
+            let verify = |transcript: &mut Transcript, commitment: &Xsk233Affine, n| {
+                proof.verify_single(&bp_gens, &pc_gens, transcript, commitment, n)
+            };
+            assert_eq!(
+                verify(&mut Transcript::new(b"test"), &commitment, n),
+                Ok(())
+            );
+            assert!(verify(&mut Transcript::new(b"other"), &commitment, n).is_err());
+            let shifted = (commitment + pc_gens.b).into_affine();
+            assert!(verify(&mut Transcript::new(b"test"), &shifted, n).is_err());
+        }
+
+        assert_eq!(
+            RangeProof::prove_single(
+                &mut rng,
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"test"),
+                256,
+                &blinding,
+                8
+            )
+            .err(),
+            Some(ProofError::ValueOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_aggregated_range_proof() {
+        let mut rng = test_rng();
+        let bp_gens = BulletproofGens::new(64 * 4);
+        let pc_gens = PedersenGens::default();
+        let values = [7, u64::MAX, 0, 1 << 63];
+        let blindings: Vec<_> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+
+        let (proof, commitments) = RangeProof::prove_multiple(
+            &mut rng,
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"aggregated"),
+            &values,
+            &blindings,
+            64,
+        )
+        .unwrap();
+        // 4 points and 3 scalars, 8 pairs of points and 2 scalars
+        assert_eq!(
+            proof.to_bytes().len(),
+            20 * COMPRESSED_POINT_SIZE + 5 * SCALAR_SIZE
+        );
+        let verify = |commitments: &[Xsk233Affine]| {
+            proof.verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"aggregated"),
+                commitments,
+                64,
+            )
+        };
+        assert_eq!(verify(&commitments), Ok(()));
+
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert_eq!(verify(&swapped), Err(ProofError::VerificationError));
+        assert_eq!(
+            verify(&commitments[..2]),
+            Err(ProofError::VerificationError)
+        );
+        assert_eq!(
+            verify(&commitments[..3]),
+            Err(ProofError::InvalidAggregation)
+        );
+
+        assert_eq!(
+            RangeProof::prove_multiple(
+                &mut rng,
+                &BulletproofGens::new(64),
+                &pc_gens,
+                &mut Transcript::new(b"aggregated"),
+                &values,
+                &blindings,
+                64
+            )
+            .err(),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
+    }
+}
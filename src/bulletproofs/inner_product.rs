@@ -0,0 +1,292 @@
+//! Inner-product argument of Bulletproofs.
+//!
+//! For generators `G`, `H` of length `n`, a power of two, and a point `Q`,
+//! an [`InnerProductProof`] shows knowledge of vectors `a`, `b` with
+//!
+//! ```text
+//! P = <a, G> + <b, H> + <a, b>*Q
+//! ```
+//!
+//! in `log2(n)` rounds, each halving the vectors and sending two points
+//! `L`, `R`. The verifier folds all the rounds into a single multi-scalar
+//! multiplication.
+
+use super::ProofError;
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::group::Xsk233Projective;
+use crate::transcript::Transcript;
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, scalar_from_bytes, scalar_to_bytes};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, Zero, batch_inversion};
+
+/// Squares of the challenges and of their inverses, and the scalars of
+/// the folded generators.
+type VerificationScalars = (Vec<Fr>, Vec<Fr>, Vec<Fr>);
+
+/// Proof of `log2(n)` pairs `(L, R)` and the final scalars `a`, `b`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InnerProductProof {
+    l_vec: Vec<Xsk233Affine>,
+    r_vec: Vec<Xsk233Affine>,
+    a: Fr,
+    b: Fr,
+}
+
+impl InnerProductProof {
+    /// Proves knowledge of `a` and `b` for `P = <a, G> + <b, H> + <a, b>*Q`.
+    /// The prover is not constant time.
+    ///
+    /// # Panics
+    ///
+    /// If the vectors do not all have the same length, a power of two.
+    pub fn prove(
+        transcript: &mut Transcript,
+        q: &Xsk233Affine,
+        mut g: Vec<Xsk233Affine>,
+        mut h: Vec<Xsk233Affine>,
+        mut a: Vec<Fr>,
+        mut b: Vec<Fr>,
+    ) -> Self {
+        let mut n = g.len();
+        assert!(n.is_power_of_two() && h.len() == n && a.len() == n && b.len() == n);
+        transcript.append_u64(b"ipp-n", n as u64);
+
+        let lg_n = n.trailing_zeros() as usize;
+        let mut l_vec = Vec::with_capacity(lg_n);
+        let mut r_vec = Vec::with_capacity(lg_n);
+        while n > 1 {
+            n /= 2;
+            let (a_lo, a_hi) = a.split_at(n);
+            let (b_lo, b_hi) = b.split_at(n);
+            let (g_lo, g_hi) = g.split_at(n);
+            let (h_lo, h_hi) = h.split_at(n);
+
+            let c_l = inner_product(a_lo, b_hi);
+            let c_r = inner_product(a_hi, b_lo);
+            let l = Xsk233Projective::msm_unchecked(
+                &[g_hi, h_lo, &[*q]].concat(),
+                &[a_lo, b_hi, &[c_l]].concat(),
+            )
+            .into_affine();
+            let r = Xsk233Projective::msm_unchecked(
+                &[g_lo, h_hi, &[*q]].concat(),
+                &[a_hi, b_lo, &[c_r]].concat(),
+            )
+            .into_affine();
+            transcript.append_point(b"L", &l);
+            transcript.append_point(b"R", &r);
+            l_vec.push(l);
+            r_vec.push(r);
+
+            let u = transcript.challenge_scalar(b"u");
+            let u_inv = u.inverse().expect("challenge is non-zero");
+            a = fold(a_lo, a_hi, &u, &u_inv);
+            b = fold(b_lo, b_hi, &u_inv, &u);
+            g = fold_points(g_lo, g_hi, &u_inv, &u);
+            h = fold_points(h_lo, h_hi, &u, &u_inv);
+        }
+        Self {
+            l_vec,
+            r_vec,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    /// Verifies the proof for `P` on its own.
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        q: &Xsk233Affine,
+        p: &Xsk233Affine,
+        g: &[Xsk233Affine],
+        h: &[Xsk233Affine],
+    ) -> Result<(), ProofError> {
+        if g.len() != h.len() {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        let (u_sq, u_inv_sq, s) = self.verification_scalars(g.len(), transcript)?;
+
+        // a*<s, G> + b*<1/s, H> + a*b*Q - P - sum(u^2*L + u^-2*R) = 0
+        let scalars: Vec<_> = s
+            .iter()
+            .map(|s| self.a * s)
+            .chain(s.iter().rev().map(|s_inv| self.b * s_inv))
+            .chain([self.a * self.b, -Fr::ONE])
+            .chain(u_sq.iter().chain(&u_inv_sq).map(|u| -*u))
+            .collect();
+        let bases = [g, h, &[*q, *p], &self.l_vec, &self.r_vec].concat();
+        if Xsk233Projective::msm_unchecked(&bases, &scalars).is_zero() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Replays the transcript of the proof for `n` generators, and returns
+    /// the squares of the challenges, of their inverses, and the scalars
+    /// `s_i` of the folded generator `<s, G>`. The folded `H` is
+    /// `<1/s, H>`, and `1/s` is `s` reversed.
+    pub(crate) fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+    ) -> Result<VerificationScalars, ProofError> {
+        let lg_n = self.l_vec.len();
+        if lg_n >= 32 || n != 1 << lg_n {
+            return Err(ProofError::VerificationError);
+        }
+        transcript.append_u64(b"ipp-n", n as u64);
+
+        let mut challenges: Vec<_> = self
+            .l_vec
+            .iter()
+            .zip(&self.r_vec)
+            .map(|(l, r)| {
+                transcript.append_point(b"L", l);
+                transcript.append_point(b"R", r);
+                transcript.challenge_scalar(b"u")
+            })
+            .collect();
+        if challenges.iter().any(Zero::is_zero) {
+            return Err(ProofError::VerificationError);
+        }
+        let u_sq: Vec<_> = challenges.iter().map(Field::square).collect();
+        batch_inversion(&mut challenges);
+        let u_inv_sq: Vec<_> = challenges.iter().map(Field::square).collect();
+
+        // s_0 is the product of the inverses; each bit set in i swaps one
+        // inverse for the challenge, i.e. multiplies by its square
+        let mut s = Vec::with_capacity(n);
+        s.push(challenges.iter().product::<Fr>());
+        for i in 1..n {
+            let lg_i = i.ilog2() as usize;
+            let k = 1 << lg_i;
+            s.push(s[i - k] * u_sq[lg_n - 1 - lg_i]);
+        }
+        Ok((u_sq, u_inv_sq, s))
+    }
+
+    pub fn l_vec(&self) -> &[Xsk233Affine] {
+        &self.l_vec
+    }
+
+    pub fn r_vec(&self) -> &[Xsk233Affine] {
+        &self.r_vec
+    }
+
+    pub fn a(&self) -> &Fr {
+        &self.a
+    }
+
+    pub fn b(&self) -> &Fr {
+        &self.b
+    }
+
+    /// Size of the encoding, `L_0 || R_0 || ... || a || b`.
+    pub fn serialized_size(&self) -> usize {
+        self.l_vec.len() * 2 * COMPRESSED_POINT_SIZE + 2 * SCALAR_SIZE
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            bytes.extend_from_slice(&l.to_bytes());
+            bytes.extend_from_slice(&r.to_bytes());
+        }
+        bytes.extend_from_slice(&scalar_to_bytes(&self.a));
+        bytes.extend_from_slice(&scalar_to_bytes(&self.b));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let points_len = bytes
+            .len()
+            .checked_sub(2 * SCALAR_SIZE)
+            .filter(|len| len % (2 * COMPRESSED_POINT_SIZE) == 0)
+            .ok_or(ProofError::FormatError)?;
+        let (points, scalars) = bytes.split_at(points_len);
+        let points = points
+            .chunks_exact(COMPRESSED_POINT_SIZE)
+            .map(|chunk| Xsk233Affine::from_bytes(chunk.try_into().unwrap()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ProofError::FormatError)?;
+        let (a, b) = scalars.split_at(SCALAR_SIZE);
+        Ok(Self {
+            l_vec: points.iter().step_by(2).copied().collect(),
+            r_vec: points.iter().skip(1).step_by(2).copied().collect(),
+            a: scalar_from_bytes(a.try_into().unwrap()).ok_or(ProofError::FormatError)?,
+            b: scalar_from_bytes(b.try_into().unwrap()).ok_or(ProofError::FormatError)?,
+        })
+    }
+}
+
+pub(crate) fn inner_product(a: &[Fr], b: &[Fr]) -> Fr {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+/// `lo*x + hi*y`, element-wise.
+fn fold(lo: &[Fr], hi: &[Fr], x: &Fr, y: &Fr) -> Vec<Fr> {
+    lo.iter()
+        .zip(hi)
+        .map(|(lo, hi)| *lo * x + *hi * y)
+        .collect()
+}
+
+fn fold_points(lo: &[Xsk233Affine], hi: &[Xsk233Affine], x: &Fr, y: &Fr) -> Vec<Xsk233Affine> {
+    let folded: Vec<_> = lo
+        .iter()
+        .zip(hi)
+        .map(|(lo, hi)| Xsk233Projective::double_mul(x, lo, y, hi))
+        .collect();
+    Xsk233Projective::normalize_batch(&folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulletproofs::BulletproofGens;
+    use ark_ec::AffineRepr;
+    use ark_std::{UniformRand, test_rng};
+
+    #[test]
+    fn test_inner_product_proof() {
+        let mut rng = test_rng();
+        let n = 16;
+        let gens = BulletproofGens::new(n);
+        let (g, h) = (&gens.g_vec()[..n], &gens.h_vec()[..n]);
+        let q = Xsk233Affine::rand(&mut rng);
+        let a: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let p = (Xsk233Projective::msm_unchecked(&[g, h].concat(), &[&a[..], &b].concat())
+            + q * inner_product(&a, &b))
+        .into_affine();
+
+        let proof = InnerProductProof::prove(
+            &mut Transcript::new(b"ipp test"),
+            &q,
+            g.to_vec(),
+            h.to_vec(),
+            a,
+            b,
+        );
+        assert_eq!(proof.l_vec().len(), 4);
+        let proof = InnerProductProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(proof.to_bytes().len(), proof.serialized_size());
+        assert_eq!(
+            proof.verify(&mut Transcript::new(b"ipp test"), &q, &p, g, h),
+            Ok(())
+        );
+
+        let other = (p + Xsk233Affine::generator()).into_affine();
+        assert_eq!(
+            proof.verify(&mut Transcript::new(b"ipp test"), &q, &other, g, h),
+            Err(ProofError::VerificationError)
+        );
+        assert_eq!(
+            proof.verify(&mut Transcript::new(b"ipp test"), &q, &p, &g[..8], &h[..8]),
+            Err(ProofError::VerificationError)
+        );
+    }
+}
@@ -0,0 +1,703 @@
+//! Boolean circuits for xsk233 arithmetic in Bristol Fashion format.
+//!
+//! GF(2^233) additions and squarings are linear over GF(2), so they only
+//! need XOR gates, which are free in garbling schemes with free-XOR and
+//! cheap in BitVM-style verification. The generators below build circuits
+//! for field multiplication (Karatsuba), squaring, inversion (Itoh-Tsujii),
+//! the complete xsk233 point addition and doubling formulas, and
+//! double-and-add scalar multiplication.
+//!
+//! | circuit                | AND gates | XOR gates |
+//! |------------------------|-----------|-----------|
+//! | field multiplication   |     6 323 |    35 236 |
+//! | field squaring         |         0 |       234 |
+//! | field inversion        |    63 230 |   387 856 |
+//! | point doubling         |    18 969 |   107 566 |
+//! | point addition         |    44 261 |   248 123 |
+//! | scalar mult., per bit  |    63 929 |   357 006 |
+//!
+//! The per-bit cost of scalar multiplication is the difference between
+//! circuits for consecutive scalar sizes: a doubling, an addition and the
+//! multiplexer, after constant folding. [`Circuit::gate_counts`] reports
+//! the exact numbers of a circuit.
+//!
+//! Field elements are 233 wires in polynomial basis, lowest degree first
+//! (the bit order of [`Gf233::to_bits`]). Points are the extended
+//! `(X, S, Z, T)` coordinates used by xs233, as four consecutive field
+//! elements; see [`point_to_bits`] and [`point_from_bits`].
+//!
+//! The format is described at
+//! <https://nigelsmart.github.io/MPC-Circuits/>: a header with the gate and
+//! wire counts and the input/output value sizes, then one gate per line.
+//! Input values occupy the first wires and output values the last ones.
+
+use crate::affine::Xsk233Affine;
+use crate::gf233::{GF233_BITS, Gf233};
+use crate::inner::InnerPoint;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use std::error::Error;
+use std::fmt::Write as _;
+
+mod field;
+mod point;
+
+use field::FieldWires;
+use point::PointWires;
+
+/// Number of wires of a point in `(X, S, Z, T)` coordinates.
+pub const POINT_BITS: usize = 4 * GF233_BITS;
+
+/// Index of a wire. `u32` keeps the gate list of a full scalar
+/// multiplication circuit (around 10^8 gates) within a few gigabytes.
+pub type Wire = u32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gate {
+    Xor { a: Wire, b: Wire, out: Wire },
+    And { a: Wire, b: Wire, out: Wire },
+    Inv { a: Wire, out: Wire },
+}
+
+/// Number of gates of each kind in a circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GateCounts {
+    pub and: usize,
+    pub xor: usize,
+    pub inv: usize,
+}
+
+impl GateCounts {
+    pub fn total(&self) -> usize {
+        self.and + self.xor + self.inv
+    }
+}
+
+impl Display for GateCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} AND, {} XOR, {} INV", self.and, self.xor, self.inv)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Circuit {
+    num_wires: usize,
+    input_sizes: Vec<usize>,
+    output_sizes: Vec<usize>,
+    gates: Vec<Gate>,
+}
+
+impl Circuit {
+    /// `a * b`, with inputs `a` and `b` and one output.
+    pub fn field_mul() -> Self {
+        let mut builder = Builder::default();
+        let a = FieldWires::input(&mut builder);
+        let b = FieldWires::input(&mut builder);
+        let c = a.mul(&b, &mut builder);
+        builder.finish(vec![c.into_bits()])
+    }
+
+    /// `a^2`, which only needs XOR gates.
+    pub fn field_square() -> Self {
+        let mut builder = Builder::default();
+        let a = FieldWires::input(&mut builder);
+        let c = a.square(&mut builder);
+        builder.finish(vec![c.into_bits()])
+    }
+
+    /// `a^-1`, with the inverse of zero being zero as in xs233.
+    pub fn field_inverse() -> Self {
+        let mut builder = Builder::default();
+        let a = FieldWires::input(&mut builder);
+        let c = a.inverse(&mut builder);
+        builder.finish(vec![c.into_bits()])
+    }
+
+    /// `P + Q` with the complete xsk233 addition formulas (7M + 2S).
+    pub fn point_add() -> Self {
+        let mut builder = Builder::default();
+        let p = PointWires::input(&mut builder);
+        let q = PointWires::input(&mut builder);
+        let r = p.add(&q, &mut builder);
+        builder.finish(vec![r.into_bits()])
+    }
+
+    /// `2P` with the xsk233 doubling formulas (3M + 4S).
+    pub fn point_double() -> Self {
+        let mut builder = Builder::default();
+        let p = PointWires::input(&mut builder);
+        let r = p.double(&mut builder);
+        builder.finish(vec![r.into_bits()])
+    }
+
+    /// `k * P` for a `scalar_bits`-bit little-endian scalar `k` (first
+    /// input) and a point `P` (second input), with a fixed sequence of
+    /// doublings, additions and multiplexers that does not depend on `k`.
+    ///
+    /// Scalars of the full 232 bits yield circuits with about 10^8 gates.
+    pub fn scalar_mul(scalar_bits: usize) -> Self {
+        let mut builder = Builder::default();
+        let k = builder.input(scalar_bits);
+        let p = PointWires::input(&mut builder);
+
+        let mut acc = PointWires::neutral();
+        for bit in k.iter().rev() {
+            acc = acc.double(&mut builder);
+            let sum = acc.add(&p, &mut builder);
+            acc = PointWires::select(*bit, &sum, &acc, &mut builder);
+        }
+        builder.finish(vec![acc.into_bits()])
+    }
+
+    pub fn num_wires(&self) -> usize {
+        self.num_wires
+    }
+
+    pub fn input_sizes(&self) -> &[usize] {
+        &self.input_sizes
+    }
+
+    pub fn output_sizes(&self) -> &[usize] {
+        &self.output_sizes
+    }
+
+    pub fn gates(&self) -> &[Gate] {
+        &self.gates
+    }
+
+    pub fn gate_counts(&self) -> GateCounts {
+        let mut counts = GateCounts::default();
+        for gate in &self.gates {
+            match gate {
+                Gate::Xor { .. } => counts.xor += 1,
+                Gate::And { .. } => counts.and += 1,
+                Gate::Inv { .. } => counts.inv += 1,
+            }
+        }
+        counts
+    }
+
+    /// Evaluates the circuit in the clear.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number or sizes of `inputs` do not match
+    /// [`Self::input_sizes`].
+    pub fn evaluate(&self, inputs: &[Vec<bool>]) -> Vec<Vec<bool>> {
+        assert_eq!(
+            inputs.len(),
+            self.input_sizes.len(),
+            "wrong number of inputs"
+        );
+
+        let mut wires = vec![false; self.num_wires];
+        let mut offset = 0;
+        for (input, size) in inputs.iter().zip(&self.input_sizes) {
+            assert_eq!(input.len(), *size, "wrong input size");
+            wires[offset..offset + size].copy_from_slice(input);
+            offset += size;
+        }
+
+        for gate in &self.gates {
+            match *gate {
+                Gate::Xor { a, b, out } => {
+                    wires[out as usize] = wires[a as usize] ^ wires[b as usize]
+                }
+                Gate::And { a, b, out } => {
+                    wires[out as usize] = wires[a as usize] & wires[b as usize]
+                }
+                Gate::Inv { a, out } => wires[out as usize] = !wires[a as usize],
+            }
+        }
+
+        let mut offset = self.num_wires - self.output_sizes.iter().sum::<usize>();
+        self.output_sizes
+            .iter()
+            .map(|size| {
+                let output = wires[offset..offset + size].to_vec();
+                offset += size;
+                output
+            })
+            .collect()
+    }
+
+    /// Serializes the circuit in Bristol Fashion.
+    pub fn to_bristol(&self) -> String {
+        let mut out = String::new();
+        let sizes = |sizes: &[usize]| {
+            sizes
+                .iter()
+                .map(|size| size.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        writeln!(out, "{} {}", self.gates.len(), self.num_wires).unwrap();
+        writeln!(
+            out,
+            "{} {}",
+            self.input_sizes.len(),
+            sizes(&self.input_sizes)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "{} {}",
+            self.output_sizes.len(),
+            sizes(&self.output_sizes)
+        )
+        .unwrap();
+        writeln!(out).unwrap();
+
+        for gate in &self.gates {
+            match gate {
+                Gate::Xor { a, b, out: o } => writeln!(out, "2 1 {a} {b} {o} XOR"),
+                Gate::And { a, b, out: o } => writeln!(out, "2 1 {a} {b} {o} AND"),
+                Gate::Inv { a, out: o } => writeln!(out, "1 1 {a} {o} INV"),
+            }
+            .unwrap();
+        }
+        out
+    }
+
+    /// Parses a Bristol Fashion circuit made of XOR, AND and INV gates.
+    pub fn from_bristol(s: &str) -> Result<Self, BristolError> {
+        let mut lines = s.lines().filter(|line| !line.trim().is_empty());
+        let mut header = |name: &'static str| -> Result<Vec<usize>, BristolError> {
+            lines
+                .next()
+                .ok_or(BristolError::MissingHeader(name))?
+                .split_whitespace()
+                .map(|v| v.parse().map_err(|_| BristolError::MissingHeader(name)))
+                .collect()
+        };
+
+        let counts = header("gate and wire counts")?;
+        let inputs = header("inputs")?;
+        let outputs = header("outputs")?;
+        let [num_gates, num_wires] = counts[..] else {
+            return Err(BristolError::MissingHeader("gate and wire counts"));
+        };
+        let parse_sizes = |values: Vec<usize>, name| match values.split_first() {
+            Some((n, sizes)) if *n == sizes.len() => Ok(sizes.to_vec()),
+            _ => Err(BristolError::MissingHeader(name)),
+        };
+        let input_sizes = parse_sizes(inputs, "inputs")?;
+        let output_sizes = parse_sizes(outputs, "outputs")?;
+
+        let mut gates = Vec::with_capacity(num_gates);
+        for (i, line) in lines.enumerate() {
+            let gate = parse_gate(line, num_wires).ok_or(BristolError::InvalidGate(i))?;
+            gates.push(gate);
+        }
+        if gates.len() != num_gates {
+            return Err(BristolError::GateCountMismatch {
+                expected: num_gates,
+                found: gates.len(),
+            });
+        }
+        if input_sizes.iter().sum::<usize>() + output_sizes.iter().sum::<usize>() > num_wires {
+            return Err(BristolError::MissingHeader("gate and wire counts"));
+        }
+
+        Ok(Self {
+            num_wires,
+            input_sizes,
+            output_sizes,
+            gates,
+        })
+    }
+}
+
+fn parse_gate(line: &str, num_wires: usize) -> Option<Gate> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    let wire = |s: &str| s.parse::<Wire>().ok().filter(|w| (*w as usize) < num_wires);
+    match fields[..] {
+        ["2", "1", a, b, out, "XOR"] => Some(Gate::Xor {
+            a: wire(a)?,
+            b: wire(b)?,
+            out: wire(out)?,
+        }),
+        ["2", "1", a, b, out, "AND"] => Some(Gate::And {
+            a: wire(a)?,
+            b: wire(b)?,
+            out: wire(out)?,
+        }),
+        ["1", "1", a, out, "INV"] => Some(Gate::Inv {
+            a: wire(a)?,
+            out: wire(out)?,
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BristolError {
+    MissingHeader(&'static str),
+    /// The gate on the given line (counting from the first gate) is
+    /// malformed, refers to a missing wire or is not XOR, AND or INV.
+    InvalidGate(usize),
+    GateCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for BristolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::MissingHeader(name) => write!(f, "missing or malformed header: {name}"),
+            Self::InvalidGate(i) => write!(f, "invalid gate #{i}"),
+            Self::GateCountMismatch { expected, found } => {
+                write!(f, "expected {expected} gates, found {found}")
+            }
+        }
+    }
+}
+
+impl Error for BristolError {}
+
+/// Returns the `(X, S, Z, T)` coordinates of `point` as circuit input bits.
+pub fn point_to_bits(point: &Xsk233Affine) -> Vec<bool> {
+    InnerPoint::from_affine(point)
+        .coordinates()
+        .iter()
+        .flat_map(|c| c.to_bits())
+        .collect()
+}
+
+/// Reads `(X, S, Z, T)` coordinates from circuit output bits. Returns `None`
+/// if the length is not [`POINT_BITS`] or the coordinates do not describe a
+/// group element.
+pub fn point_from_bits(bits: &[bool]) -> Option<Xsk233Affine> {
+    if bits.len() != POINT_BITS {
+        return None;
+    }
+    let mut coordinates = [Gf233::ZERO; 4];
+    for (c, chunk) in coordinates.iter_mut().zip(bits.chunks_exact(GF233_BITS)) {
+        *c = Gf233::from_bits(chunk.try_into().unwrap());
+    }
+    InnerPoint::from_coordinates(coordinates).try_into_affine()
+}
+
+/// A wire during construction, with constants kept symbolic so that they
+/// fold away instead of producing gates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bit {
+    Const(bool),
+    Wire(Wire),
+}
+
+#[derive(Default)]
+struct Builder {
+    gates: Vec<Gate>,
+    num_wires: Wire,
+    input_sizes: Vec<usize>,
+}
+
+impl Builder {
+    /// Allocates the wires of the next input value. All inputs must be
+    /// declared before the first gate.
+    fn input(&mut self, size: usize) -> Vec<Bit> {
+        assert!(self.gates.is_empty(), "inputs must precede gates");
+        self.input_sizes.push(size);
+        (0..size).map(|_| Bit::Wire(self.fresh())).collect()
+    }
+
+    fn fresh(&mut self) -> Wire {
+        let wire = self.num_wires;
+        self.num_wires += 1;
+        wire
+    }
+
+    fn xor(&mut self, a: Bit, b: Bit) -> Bit {
+        match (a, b) {
+            (Bit::Const(x), Bit::Const(y)) => Bit::Const(x ^ y),
+            (Bit::Const(false), w) | (w, Bit::Const(false)) => w,
+            (Bit::Const(true), w) | (w, Bit::Const(true)) => self.inv(w),
+            (Bit::Wire(a), Bit::Wire(b)) if a == b => Bit::Const(false),
+            (Bit::Wire(a), Bit::Wire(b)) => {
+                let out = self.fresh();
+                self.gates.push(Gate::Xor { a, b, out });
+                Bit::Wire(out)
+            }
+        }
+    }
+
+    fn and(&mut self, a: Bit, b: Bit) -> Bit {
+        match (a, b) {
+            (Bit::Const(x), Bit::Const(y)) => Bit::Const(x & y),
+            (Bit::Const(false), _) | (_, Bit::Const(false)) => Bit::Const(false),
+            (Bit::Const(true), w) | (w, Bit::Const(true)) => w,
+            (Bit::Wire(a), Bit::Wire(b)) if a == b => Bit::Wire(a),
+            (Bit::Wire(a), Bit::Wire(b)) => {
+                let out = self.fresh();
+                self.gates.push(Gate::And { a, b, out });
+                Bit::Wire(out)
+            }
+        }
+    }
+
+    fn inv(&mut self, a: Bit) -> Bit {
+        match a {
+            Bit::Const(x) => Bit::Const(!x),
+            Bit::Wire(a) => {
+                let out = self.fresh();
+                self.gates.push(Gate::Inv { a, out });
+                Bit::Wire(out)
+            }
+        }
+    }
+
+    /// Removes gates that do not contribute to `outputs` and renumbers the
+    /// wires so that the outputs come last, as Bristol Fashion requires.
+    fn finish(mut self, outputs: Vec<Vec<Bit>>) -> Circuit {
+        let num_inputs = self.input_sizes.iter().sum::<usize>() as Wire;
+        assert!(num_inputs > 0, "circuits need at least one input");
+
+        // Outputs must be distinct gate outputs, so constants, inputs and
+        // repeated wires are copied through an extra gate.
+        let mut zero = None;
+        let mut is_output = vec![false; self.num_wires as usize];
+        let mut output_wires = Vec::new();
+        for bit in outputs.iter().flatten() {
+            let wire = match *bit {
+                Bit::Wire(w) if w >= num_inputs && !is_output[w as usize] => w,
+                bit => {
+                    let zero = *zero.get_or_insert_with(|| {
+                        let out = self.fresh();
+                        self.gates.push(Gate::Xor { a: 0, b: 0, out });
+                        out
+                    });
+                    let out = self.fresh();
+                    self.gates.push(match bit {
+                        Bit::Const(false) => Gate::Xor { a: 0, b: 0, out },
+                        Bit::Const(true) => Gate::Inv { a: zero, out },
+                        Bit::Wire(w) => Gate::Xor { a: w, b: zero, out },
+                    });
+                    out
+                }
+            };
+            if is_output.len() <= wire as usize {
+                is_output.resize(wire as usize + 1, false);
+            }
+            is_output[wire as usize] = true;
+            output_wires.push(wire);
+        }
+
+        // dead gate elimination
+        let mut live = vec![false; self.num_wires as usize];
+        for wire in &output_wires {
+            live[*wire as usize] = true;
+        }
+        let mut gates = Vec::new();
+        for gate in self.gates.iter().rev() {
+            match *gate {
+                Gate::Xor { a, b, out } | Gate::And { a, b, out } if live[out as usize] => {
+                    live[a as usize] = true;
+                    live[b as usize] = true;
+                    gates.push(*gate);
+                }
+                Gate::Inv { a, out } if live[out as usize] => {
+                    live[a as usize] = true;
+                    gates.push(*gate);
+                }
+                _ => {}
+            }
+        }
+        gates.reverse();
+
+        // inputs keep their wires, outputs take the last ones
+        let num_outputs = output_wires.len() as Wire;
+        let num_wires = num_inputs + gates.len() as Wire;
+        let mut rename = vec![Wire::MAX; self.num_wires as usize];
+        for wire in 0..num_inputs {
+            rename[wire as usize] = wire;
+        }
+        for (i, wire) in output_wires.iter().enumerate() {
+            rename[*wire as usize] = num_wires - num_outputs + i as Wire;
+        }
+        let mut next = num_inputs;
+        for gate in &gates {
+            let out = match gate {
+                Gate::Xor { out, .. } | Gate::And { out, .. } | Gate::Inv { out, .. } => *out,
+            };
+            if rename[out as usize] == Wire::MAX {
+                rename[out as usize] = next;
+                next += 1;
+            }
+        }
+
+        let r = |w: Wire| rename[w as usize];
+        let gates = gates
+            .into_iter()
+            .map(|gate| match gate {
+                Gate::Xor { a, b, out } => Gate::Xor {
+                    a: r(a),
+                    b: r(b),
+                    out: r(out),
+                },
+                Gate::And { a, b, out } => Gate::And {
+                    a: r(a),
+                    b: r(b),
+                    out: r(out),
+                },
+                Gate::Inv { a, out } => Gate::Inv {
+                    a: r(a),
+                    out: r(out),
+                },
+            })
+            .collect();
+
+        Circuit {
+            num_wires: num_wires as usize,
+            input_sizes: self.input_sizes,
+            output_sizes: outputs.iter().map(Vec::len).collect(),
+            gates,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::Xsk233Projective;
+    use crate::xsk233::Fr;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::AdditiveGroup;
+    use ark_std::UniformRand;
+    use rand::{Rng, thread_rng};
+
+    fn rand_element<R: Rng>(rng: &mut R) -> Gf233 {
+        let mut bits = [false; GF233_BITS];
+        bits.iter_mut().for_each(|b| *b = rng.r#gen());
+        Gf233::from_bits(&bits)
+    }
+
+    fn eval_field(circuit: &Circuit, inputs: &[Gf233]) -> Gf233 {
+        let inputs = inputs
+            .iter()
+            .map(|a| a.to_bits().to_vec())
+            .collect::<Vec<_>>();
+        let output = circuit.evaluate(&inputs).remove(0);
+        Gf233::from_bits(output.as_slice().try_into().unwrap())
+    }
+
+    #[test]
+    fn test_field_circuits() {
+        let mut rng = thread_rng();
+        let mul = Circuit::field_mul();
+        let square = Circuit::field_square();
+        let inverse = Circuit::field_inverse();
+
+        for _ in 0..5 {
+            let a = rand_element(&mut rng);
+            let b = rand_element(&mut rng);
+            assert_eq!(eval_field(&mul, &[a, b]), a * b);
+            assert_eq!(eval_field(&square, &[a]), a.square());
+            assert_eq!(eval_field(&inverse, &[a]), a.inverse());
+        }
+        assert_eq!(eval_field(&inverse, &[Gf233::ZERO]), Gf233::ZERO);
+
+        // squaring is linear; the inversion chain has 10 multiplications
+        let mul_ands = mul.gate_counts().and;
+        assert_eq!(square.gate_counts().and, 0);
+        assert_eq!(inverse.gate_counts().and, 10 * mul_ands);
+        assert_eq!(mul_ands, 6323);
+    }
+
+    #[test]
+    fn test_point_circuits() {
+        let mut rng = thread_rng();
+        let p = Xsk233Projective::rand(&mut rng).into_affine();
+        let q = Xsk233Projective::rand(&mut rng).into_affine();
+        let zero = Xsk233Affine::zero();
+
+        let add = Circuit::point_add();
+        for (a, b) in [(p, q), (p, p), (p, zero), (p, -p)] {
+            let out = add.evaluate(&[point_to_bits(&a), point_to_bits(&b)]);
+            assert_eq!(point_from_bits(&out[0]), Some((a + b).into_affine()));
+        }
+
+        let double = Circuit::point_double();
+        let out = double.evaluate(&[point_to_bits(&p)]);
+        assert_eq!(
+            point_from_bits(&out[0]),
+            Some(Xsk233Projective::from(p).double().into_affine())
+        );
+
+        let mul_ands = Circuit::field_mul().gate_counts().and;
+        assert_eq!(add.gate_counts().and, 7 * mul_ands);
+        assert_eq!(double.gate_counts().and, 3 * mul_ands);
+    }
+
+    #[test]
+    fn test_scalar_mul_circuit() {
+        let mut rng = thread_rng();
+        let p = Xsk233Projective::rand(&mut rng).into_affine();
+        let circuit = Circuit::scalar_mul(5);
+        assert_eq!(circuit.input_sizes(), &[5, POINT_BITS]);
+        assert_eq!(circuit.output_sizes(), &[POINT_BITS]);
+
+        for k in [0u64, 1, 6, 31] {
+            let bits = (0..5).map(|i| (k >> i) & 1 == 1).collect::<Vec<_>>();
+            let out = circuit.evaluate(&[bits, point_to_bits(&p)]);
+            assert_eq!(
+                point_from_bits(&out[0]),
+                Some((p * Fr::from(k)).into_affine())
+            );
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_gate_counts() {
+        let short = Circuit::scalar_mul(2).gate_counts();
+        let long = Circuit::scalar_mul(3).gate_counts();
+        let per_bit = (long.and - short.and, long.xor - short.xor);
+        assert_eq!(per_bit, (63_929, 357_006));
+
+        let double = Circuit::point_double().gate_counts();
+        let add = Circuit::point_add().gate_counts();
+        assert!(per_bit.0 > double.and + add.and);
+        assert!(per_bit.0 - double.and - add.and <= POINT_BITS);
+    }
+
+    #[test]
+    fn test_bristol_round_trip() {
+        let circuit = Circuit::field_square();
+        let text = circuit.to_bristol();
+
+        let counts = circuit.gate_counts();
+        let mut lines = text.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("{} {}", counts.total(), circuit.num_wires())
+        );
+        assert_eq!(lines.next().unwrap(), "1 233");
+        assert_eq!(lines.next().unwrap(), "1 233");
+
+        assert_eq!(Circuit::from_bristol(&text), Ok(circuit));
+        assert_eq!(
+            Circuit::from_bristol("1 3\n1 1\n1 1\n\n2 1 0 5 2 XOR\n"),
+            Err(BristolError::InvalidGate(0))
+        );
+        assert_eq!(
+            Circuit::from_bristol("1 3\n1 1\n1 1\n\n2 1 0 0 2 MAND\n"),
+            Err(BristolError::InvalidGate(0))
+        );
+        assert!(Circuit::from_bristol("1 3\n1 1\n").is_err());
+    }
+
+    #[test]
+    fn test_outputs_are_copied_when_needed() {
+        // an output that is an input wire, a constant, and a repeated wire
+        let mut builder = Builder::default();
+        let a = builder.input(2);
+        let x = builder.xor(a[0], a[1]);
+        let circuit = builder.finish(vec![vec![a[0], Bit::Const(true), x, x]]);
+
+        for (a0, a1) in [(false, true), (true, true)] {
+            let out = circuit.evaluate(&[vec![a0, a1]]);
+            assert_eq!(out, vec![vec![a0, true, a0 ^ a1, a0 ^ a1]]);
+        }
+    }
+}
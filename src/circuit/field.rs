@@ -0,0 +1,156 @@
+use super::{Bit, Builder};
+use crate::gf233::GF233_BITS;
+
+/// Below this size, polynomial products are computed with the schoolbook
+/// method; above it, Karatsuba trades one AND-heavy sub-product for XORs.
+/// Recursing all the way down minimizes AND gates, which are the costly
+/// ones under free-XOR garbling (6 323 instead of 54 289 per product).
+const KARATSUBA_THRESHOLD: usize = 1;
+
+/// GF(2^233) element as wires, lowest degree first.
+#[derive(Clone, Debug)]
+pub(super) struct FieldWires(Vec<Bit>);
+
+impl FieldWires {
+    pub(super) fn input(builder: &mut Builder) -> Self {
+        Self(builder.input(GF233_BITS))
+    }
+
+    pub(super) fn from_bits(bits: Vec<Bit>) -> Self {
+        debug_assert_eq!(bits.len(), GF233_BITS);
+        Self(bits)
+    }
+
+    pub(super) fn constant(value: bool) -> Self {
+        let mut bits = vec![Bit::Const(false); GF233_BITS];
+        bits[0] = Bit::Const(value);
+        Self(bits)
+    }
+
+    pub(super) fn into_bits(self) -> Vec<Bit> {
+        self.0
+    }
+
+    pub(super) fn add(&self, other: &Self, builder: &mut Builder) -> Self {
+        Self(xor_all(&self.0, &other.0, builder))
+    }
+
+    pub(super) fn mul(&self, other: &Self, builder: &mut Builder) -> Self {
+        let product = poly_mul(&self.0, &other.0, builder);
+        Self(reduce(product, builder))
+    }
+
+    /// Squaring spreads the bits and reduces, both of which are linear.
+    pub(super) fn square(&self, builder: &mut Builder) -> Self {
+        let mut spread = vec![Bit::Const(false); 2 * GF233_BITS - 1];
+        for (i, bit) in self.0.iter().enumerate() {
+            spread[2 * i] = *bit;
+        }
+        Self(reduce(spread, builder))
+    }
+
+    pub(super) fn square_n(&self, n: usize, builder: &mut Builder) -> Self {
+        let mut res = self.clone();
+        for _ in 0..n {
+            res = res.square(builder);
+        }
+        res
+    }
+
+    /// Itoh-Tsujii inversion with the same addition chain as
+    /// [`crate::gf233::Gf233::inverse`].
+    pub(super) fn inverse(&self, builder: &mut Builder) -> Self {
+        let b1 = self.clone();
+        let b2 = b1.square(builder).mul(&b1, builder);
+        let b3 = b2.square(builder).mul(&b1, builder);
+        let b6 = b3.square_n(3, builder).mul(&b3, builder);
+        let b7 = b6.square(builder).mul(&b1, builder);
+        let b14 = b7.square_n(7, builder).mul(&b7, builder);
+        let b28 = b14.square_n(14, builder).mul(&b14, builder);
+        let b29 = b28.square(builder).mul(&b1, builder);
+        let b58 = b29.square_n(29, builder).mul(&b29, builder);
+        let b116 = b58.square_n(58, builder).mul(&b58, builder);
+        let b232 = b116.square_n(116, builder).mul(&b116, builder);
+        b232.square(builder)
+    }
+
+    /// Returns `a` where `bit` is set and `b` elsewhere.
+    pub(super) fn select(bit: Bit, a: &Self, b: &Self, builder: &mut Builder) -> Self {
+        let bits =
+            a.0.iter()
+                .zip(&b.0)
+                .map(|(x, y)| {
+                    // y ^ (bit & (x ^ y))
+                    let diff = builder.xor(*x, *y);
+                    let masked = builder.and(bit, diff);
+                    builder.xor(*y, masked)
+                })
+                .collect();
+        Self(bits)
+    }
+}
+
+fn xor_all(a: &[Bit], b: &[Bit], builder: &mut Builder) -> Vec<Bit> {
+    a.iter().zip(b).map(|(x, y)| builder.xor(*x, *y)).collect()
+}
+
+/// Product of two polynomials of the same length `n`, of length `2n - 1`.
+fn poly_mul(a: &[Bit], b: &[Bit], builder: &mut Builder) -> Vec<Bit> {
+    let n = a.len();
+    debug_assert_eq!(n, b.len());
+
+    if n <= KARATSUBA_THRESHOLD {
+        let mut c = vec![Bit::Const(false); 2 * n - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                let t = builder.and(*x, *y);
+                c[i + j] = builder.xor(c[i + j], t);
+            }
+        }
+        return c;
+    }
+
+    // a = a0 + z^h a1, with a1 zero-padded to h bits for the middle product
+    let h = n.div_ceil(2);
+    let (a0, a1) = a.split_at(h);
+    let (b0, b1) = b.split_at(h);
+
+    let lo = poly_mul(a0, b0, builder);
+    let hi = poly_mul(a1, b1, builder);
+
+    let pad = |v: &[Bit]| {
+        let mut v = v.to_vec();
+        v.resize(h, Bit::Const(false));
+        v
+    };
+    let sa = xor_all(a0, &pad(a1), builder);
+    let sb = xor_all(b0, &pad(b1), builder);
+    let mut mid = poly_mul(&sa, &sb, builder);
+
+    // mid = (a0 + a1)(b0 + b1) - a0 b0 - a1 b1
+    for (i, bit) in lo.iter().enumerate() {
+        mid[i] = builder.xor(mid[i], *bit);
+    }
+    for (i, bit) in hi.iter().enumerate() {
+        mid[i] = builder.xor(mid[i], *bit);
+    }
+
+    let mut c = vec![Bit::Const(false); 2 * n - 1];
+    c[..lo.len()].copy_from_slice(&lo);
+    c[2 * h..2 * h + hi.len()].copy_from_slice(&hi);
+    for (i, bit) in mid.iter().enumerate() {
+        c[h + i] = builder.xor(c[h + i], *bit);
+    }
+    c
+}
+
+/// Reduces modulo z^233 + z^74 + 1 using z^233 = z^74 + 1.
+fn reduce(mut c: Vec<Bit>, builder: &mut Builder) -> Vec<Bit> {
+    for i in (GF233_BITS..c.len()).rev() {
+        let t = c[i];
+        c[i - GF233_BITS] = builder.xor(c[i - GF233_BITS], t);
+        c[i - GF233_BITS + 74] = builder.xor(c[i - GF233_BITS + 74], t);
+    }
+    c.truncate(GF233_BITS);
+    c
+}
@@ -0,0 +1,91 @@
+use super::field::FieldWires;
+use super::{Bit, Builder, POINT_BITS};
+use crate::gf233::GF233_BITS;
+
+/// xsk233 point in `(X, S, Z, T)` coordinates as wires.
+#[derive(Clone, Debug)]
+pub(super) struct PointWires {
+    x: FieldWires,
+    s: FieldWires,
+    z: FieldWires,
+    t: FieldWires,
+}
+
+impl PointWires {
+    /// Declares the point as a single input value of [`POINT_BITS`] wires.
+    pub(super) fn input(builder: &mut Builder) -> Self {
+        let bits = builder.input(POINT_BITS);
+        let mut coordinates = bits
+            .chunks_exact(GF233_BITS)
+            .map(|chunk| FieldWires::from_bits(chunk.to_vec()));
+        let mut next = || coordinates.next().unwrap();
+        Self {
+            x: next(),
+            s: next(),
+            z: next(),
+            t: next(),
+        }
+    }
+
+    /// The neutral element `(0, 1, 1, 0)` as constants.
+    pub(super) fn neutral() -> Self {
+        Self {
+            x: FieldWires::constant(false),
+            s: FieldWires::constant(true),
+            z: FieldWires::constant(true),
+            t: FieldWires::constant(false),
+        }
+    }
+
+    pub(super) fn into_bits(self) -> Vec<Bit> {
+        [self.x, self.s, self.z, self.t]
+            .into_iter()
+            .flat_map(FieldWires::into_bits)
+            .collect()
+    }
+
+    /// Complete addition, as in `xsk233_add()`.
+    pub(super) fn add(&self, other: &Self, builder: &mut Builder) -> Self {
+        let x1x2 = self.x.mul(&other.x, builder);
+        let s1s2 = self.s.mul(&other.s, builder);
+        let z1z2 = self.z.mul(&other.z, builder);
+        let st1 = self.s.add(&self.t, builder);
+        let st2 = other.s.add(&other.t, builder);
+        let d = st1.mul(&st2, builder);
+        let f = x1x2.square(builder);
+        let g = z1z2.square(builder);
+
+        let x = d.add(&s1s2, builder);
+        let gs = g.mul(&s1s2, builder);
+        let fd = f.mul(&d, builder);
+        let s = gs.add(&fd, builder);
+        let z = f.add(&g, builder);
+        let t = x.mul(&z, builder);
+        Self { x, s, z, t }
+    }
+
+    /// Doubling, as in `xsk233_double()`.
+    pub(super) fn double(&self, builder: &mut Builder) -> Self {
+        let zz = self.z.square(builder);
+        let x = self.t.square(builder);
+        let st = self.s.add(&self.t, builder);
+        let z = self.s.mul(&st, builder);
+        let e = st.add(&zz, builder).square(builder);
+        let tmp = z.add(&x, builder);
+        let s = e
+            .mul(&tmp.add(&e, builder), builder)
+            .add(&tmp.square(builder), builder);
+        let t = x.mul(&z, builder);
+        Self { x, s, z, t }
+    }
+
+    /// Returns `a` if `bit` is set and `b` otherwise.
+    pub(super) fn select(bit: Bit, a: &Self, b: &Self, builder: &mut Builder) -> Self {
+        Self {
+            x: FieldWires::select(bit, &a.x, &b.x, builder),
+            s: FieldWires::select(bit, &a.s, &b.s, builder),
+            z: FieldWires::select(bit, &a.z, &b.z, builder),
+            t: FieldWires::select(bit, &a.t, &b.t, builder),
+        }
+    }
+}
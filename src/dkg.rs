@@ -0,0 +1,729 @@
+//! Pedersen distributed key generation, after Gennaro, Jarecki, Krawczyk
+//! and Rabin (GJKR).
+//!
+//! `participants` parties, numbered from 1, jointly generate a group key
+//! without a dealer, so that any `threshold` of them hold enough shares to
+//! use it. Every party deals a random secret, and the group secret is the
+//! sum of the secrets of the qualified dealers. All messages are either
+//! broadcast or sent over private channels:
+//!
+//! 1. every [`Participant`] broadcasts its [`PedersenCommitment`] and
+//!    sends each other party its [`Share`]; the receivers check the shares
+//!    with [`Participant::receive`] and broadcast a [`Complaint`] against
+//!    every invalid or missing one;
+//! 2. accused dealers reveal the disputed shares with
+//!    [`Participant::answer`], and everyone computes the same set of
+//!    qualified dealers with [`Participant::qualify`]: a dealer is
+//!    disqualified if its commitment is malformed, if it fails to answer a
+//!    complaint with a valid share, or if it gets complaints from
+//!    `threshold` parties or more;
+//! 3. the qualified dealers broadcast the [`FeldmanCommitment`] to their
+//!    polynomial, from which the group key is derived; a receiver whose
+//!    share does not match it complains by revealing the share, with
+//!    [`Participant::check_feldman`];
+//! 4. the secret of a dealer with a valid complaint against it is
+//!    reconstructed from the shares the others reveal with
+//!    [`Participant::reveal`], and [`Participant::finalize`] outputs the
+//!    [`DkgOutput`].
+//!
+//! Unlike the Feldman-based DKG of [`crate::frost::dkg`], the hiding
+//! commitments of the first phase keep a rushing adversary from biasing
+//! the group key. The second generator `H` of the Pedersen commitments is
+//! [`hash_to_curve_vartime`] of a fixed string, so that nobody knows its
+//! discrete logarithm.
+
+use crate::affine::Xsk233Affine;
+use crate::frost::{KeyPackage, PublicKeyPackage};
+use crate::group::Xsk233Projective;
+use crate::hash::hash_to_curve_vartime;
+use crate::shamir;
+use crate::xsk233::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::Zero;
+use ark_std::UniformRand;
+use ark_std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use zeroize::Zeroize;
+
+const GENERATOR_DST: &[u8] = b"xsk233-dkg-v1-generator";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DkgError {
+    /// `threshold` is zero or above `participants`.
+    InvalidParameters,
+    /// The index is zero or above `participants`.
+    InvalidIndex(u32),
+    /// Fewer than `threshold` dealers are qualified.
+    NotEnoughQualified,
+    /// Fewer than `threshold` valid shares were revealed to reconstruct
+    /// the secret of this dealer.
+    NotEnoughShares(u32),
+    /// This party has no valid share from a qualified dealer, e.g. because
+    /// the broadcast complaints or answers differ from the ones it sent.
+    MissingShare(u32),
+    /// The Feldman commitment of a qualified dealer is missing, and no
+    /// complaint exposed the dealer.
+    MissingCommitment(u32),
+}
+
+impl Display for DkgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            DkgError::InvalidParameters => write!(f, "invalid threshold or number of participants"),
+            DkgError::InvalidIndex(i) => write!(f, "invalid participant index {i}"),
+            DkgError::NotEnoughQualified => write!(f, "not enough qualified dealers"),
+            DkgError::NotEnoughShares(i) => {
+                write!(
+                    f,
+                    "not enough shares to reconstruct the secret of dealer {i}"
+                )
+            }
+            DkgError::MissingShare(i) => write!(f, "missing share of dealer {i}"),
+            DkgError::MissingCommitment(i) => write!(f, "missing commitment of dealer {i}"),
+        }
+    }
+}
+
+impl Error for DkgError {}
+
+/// Number of parties, and how many of them are needed to use the key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parameters {
+    threshold: u32,
+    participants: u32,
+}
+
+impl Parameters {
+    pub fn new(threshold: u32, participants: u32) -> Result<Self, DkgError> {
+        if threshold == 0 || threshold > participants {
+            return Err(DkgError::InvalidParameters);
+        }
+        Ok(Self {
+            threshold,
+            participants,
+        })
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn participants(&self) -> u32 {
+        self.participants
+    }
+
+    fn check_index(&self, index: u32) -> Result<(), DkgError> {
+        if index == 0 || index > self.participants {
+            return Err(DkgError::InvalidIndex(index));
+        }
+        Ok(())
+    }
+}
+
+/// Pedersen commitment `C_k = a_k*G + b_k*H` to the coefficients of a
+/// dealer, broadcast in the first phase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PedersenCommitment(pub Vec<Xsk233Affine>);
+
+/// Feldman commitment `A_k = a_k*G` to the coefficients of a dealer,
+/// broadcast in the third phase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeldmanCommitment(pub Vec<Xsk233Affine>);
+
+/// Share `(a(j), b(j))` of the secret of a dealer for party `j`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    value: Fr,
+    blinding: Fr,
+}
+
+impl Debug for Share {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Share").finish_non_exhaustive()
+    }
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.value.zeroize();
+        self.blinding.zeroize();
+    }
+}
+
+/// Broadcast by `accuser` when the share of `accused` is invalid or
+/// missing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Complaint {
+    pub accuser: u32,
+    pub accused: u32,
+}
+
+/// Share of `dealer` for `holder`, made public: by a dealer answering a
+/// [`Complaint`], or by a holder in the third and fourth phases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reveal {
+    pub dealer: u32,
+    pub holder: u32,
+    pub share: Share,
+}
+
+/// Result of the key generation for one party.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DkgOutput {
+    index: u32,
+    threshold: u32,
+    secret_share: Fr,
+    group_key: Xsk233Affine,
+    verification_shares: BTreeMap<u32, Xsk233Affine>,
+    qualified: BTreeSet<u32>,
+}
+
+impl DkgOutput {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Share of the group secret of this party.
+    pub fn secret_share(&self) -> &Fr {
+        &self.secret_share
+    }
+
+    pub fn group_key(&self) -> &Xsk233Affine {
+        &self.group_key
+    }
+
+    /// Public keys of the shares of all parties.
+    pub fn verification_shares(&self) -> &BTreeMap<u32, Xsk233Affine> {
+        &self.verification_shares
+    }
+
+    /// Dealers whose secrets make up the group secret.
+    pub fn qualified(&self) -> &BTreeSet<u32> {
+        &self.qualified
+    }
+
+    /// The key material of this party for FROST signing.
+    pub fn to_frost(&self) -> (KeyPackage, PublicKeyPackage) {
+        (
+            KeyPackage::new(
+                self.index,
+                self.secret_share,
+                self.group_key,
+                self.threshold,
+            ),
+            PublicKeyPackage::new(self.verification_shares.clone(), self.group_key),
+        )
+    }
+}
+
+impl Debug for DkgOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("DkgOutput")
+            .field("index", &self.index)
+            .field("group_key", &self.group_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for DkgOutput {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+/// State of one party through the protocol.
+pub struct Participant {
+    params: Parameters,
+    index: u32,
+    coefficients: Vec<Fr>,
+    blinding: Vec<Fr>,
+    commitments: BTreeMap<u32, PedersenCommitment>,
+    shares: BTreeMap<u32, Share>,
+    qualified: BTreeSet<u32>,
+}
+
+impl Debug for Participant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Participant")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Participant {
+    fn drop(&mut self) {
+        self.coefficients.zeroize();
+        self.blinding.zeroize();
+    }
+}
+
+impl Participant {
+    /// Draws the polynomials of party `index` as a dealer.
+    pub fn new<R: Rng + ?Sized>(
+        rng: &mut R,
+        params: Parameters,
+        index: u32,
+    ) -> Result<Self, DkgError> {
+        params.check_index(index)?;
+        let secret = Fr::rand(rng);
+        let coefficients = shamir::random_polynomial(rng, secret, params.threshold);
+        let blind = Fr::rand(rng);
+        let blinding = shamir::random_polynomial(rng, blind, params.threshold);
+        let mut participant = Self {
+            params,
+            index,
+            coefficients,
+            blinding,
+            commitments: BTreeMap::new(),
+            shares: BTreeMap::new(),
+            qualified: BTreeSet::new(),
+        };
+        participant
+            .commitments
+            .insert(index, participant.commitment());
+        participant
+            .shares
+            .insert(index, participant.share_for(index));
+        Ok(participant)
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Commitment to broadcast in the first phase.
+    pub fn commitment(&self) -> PedersenCommitment {
+        let h = second_generator();
+        // the coefficients are secret: no variable-time double_mul()
+        let points: Vec<_> = self
+            .coefficients
+            .iter()
+            .zip(&self.blinding)
+            .map(|(a, b)| Xsk233Affine::generator() * a + h * b)
+            .collect();
+        PedersenCommitment(Xsk233Projective::normalize_batch(&points))
+    }
+
+    /// Share to send privately to party `holder` in the first phase.
+    pub fn share_for(&self, holder: u32) -> Share {
+        Share {
+            value: shamir::evaluate(&self.coefficients, holder),
+            blinding: shamir::evaluate(&self.blinding, holder),
+        }
+    }
+
+    /// Records the commitment of `dealer` and the share it sent, if any.
+    /// Returns the complaint to broadcast if the share is missing or does
+    /// not match the commitment.
+    pub fn receive(
+        &mut self,
+        dealer: u32,
+        commitment: PedersenCommitment,
+        share: Option<Share>,
+    ) -> Option<Complaint> {
+        let valid = share
+            .as_ref()
+            .is_some_and(|s| self.params.check_pedersen(&commitment, self.index, s));
+        self.commitments.insert(dealer, commitment);
+        if let Some(share) = share.filter(|_| valid) {
+            self.shares.insert(dealer, share);
+            return None;
+        }
+        Some(Complaint {
+            accuser: self.index,
+            accused: dealer,
+        })
+    }
+
+    /// Reveals the shares disputed by the complaints against this party.
+    pub fn answer(&self, complaints: &[Complaint]) -> Vec<Reveal> {
+        complaints
+            .iter()
+            .filter(|c| c.accused == self.index)
+            .map(|c| Reveal {
+                dealer: self.index,
+                holder: c.accuser,
+                share: self.share_for(c.accuser),
+            })
+            .collect()
+    }
+
+    /// Computes the set of qualified dealers from all the complaints and
+    /// answers, which were broadcast, and keeps the valid answers to the
+    /// complaints of this party.
+    pub fn qualify(
+        &mut self,
+        complaints: &[Complaint],
+        answers: &[Reveal],
+    ) -> Result<&BTreeSet<u32>, DkgError> {
+        let params = self.params;
+        self.qualified = (1..=params.participants)
+            .filter(|dealer| {
+                let Some(commitment) = self.commitments.get(dealer) else {
+                    return false;
+                };
+                if commitment.0.len() != params.threshold as usize {
+                    return false;
+                }
+                let accusers: BTreeSet<_> = complaints
+                    .iter()
+                    .filter(|c| c.accused == *dealer)
+                    .map(|c| c.accuser)
+                    .collect();
+                accusers.len() < params.threshold as usize
+                    && accusers.iter().all(|accuser| {
+                        answers.iter().any(|a| {
+                            a.dealer == *dealer
+                                && a.holder == *accuser
+                                && params.check_pedersen(commitment, *accuser, &a.share)
+                        })
+                    })
+            })
+            .collect();
+
+        for answer in answers {
+            if answer.holder == self.index
+                && self.qualified.contains(&answer.dealer)
+                && !self.shares.contains_key(&answer.dealer)
+            {
+                self.shares.insert(answer.dealer, answer.share.clone());
+            }
+        }
+        if self.qualified.len() < params.threshold as usize {
+            return Err(DkgError::NotEnoughQualified);
+        }
+        if let Some(dealer) = self.missing_share() {
+            return Err(DkgError::MissingShare(dealer));
+        }
+        Ok(&self.qualified)
+    }
+
+    /// Commitment to broadcast in the third phase, if this party is
+    /// qualified.
+    pub fn feldman_commitment(&self) -> FeldmanCommitment {
+        FeldmanCommitment(shamir::commit(&self.coefficients))
+    }
+
+    /// Checks the shares of this party against the Feldman commitments of
+    /// the qualified dealers, and returns the complaints to broadcast: the
+    /// shares that do not match, or whose commitment is missing.
+    pub fn check_feldman(
+        &self,
+        commitments: &BTreeMap<u32, FeldmanCommitment>,
+    ) -> Result<Vec<Reveal>, DkgError> {
+        let mut complaints = Vec::new();
+        for dealer in &self.qualified {
+            let share = self.share_of(*dealer)?;
+            if !commitments
+                .get(dealer)
+                .is_some_and(|c| self.params.check_feldman(c, self.index, share))
+            {
+                complaints.push(Reveal {
+                    dealer: *dealer,
+                    holder: self.index,
+                    share: share.clone(),
+                });
+            }
+        }
+        Ok(complaints)
+    }
+
+    /// Reveals the shares of this party for `dealers`, whose secrets must
+    /// be reconstructed.
+    pub fn reveal(&self, dealers: &BTreeSet<u32>) -> Result<Vec<Reveal>, DkgError> {
+        dealers
+            .iter()
+            .filter(|dealer| self.qualified.contains(dealer))
+            .map(|dealer| {
+                Ok(Reveal {
+                    dealer: *dealer,
+                    holder: self.index,
+                    share: self.share_of(*dealer)?.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Dealers exposed by the valid complaints of the third phase: the
+    /// revealed share matches the Pedersen commitment but not the Feldman
+    /// one.
+    pub fn exposed(
+        &self,
+        commitments: &BTreeMap<u32, FeldmanCommitment>,
+        complaints: &[Reveal],
+    ) -> BTreeSet<u32> {
+        complaints
+            .iter()
+            .filter(|r| {
+                self.qualified.contains(&r.dealer)
+                    && self
+                        .commitments
+                        .get(&r.dealer)
+                        .is_some_and(|c| self.params.check_pedersen(c, r.holder, &r.share))
+                    && !commitments
+                        .get(&r.dealer)
+                        .is_some_and(|c| self.params.check_feldman(c, r.holder, &r.share))
+            })
+            .map(|r| r.dealer)
+            .collect()
+    }
+
+    /// Derives the output of this party from the Feldman commitments, the
+    /// complaints of the third phase and the shares revealed to
+    /// reconstruct the exposed dealers.
+    pub fn finalize(
+        &self,
+        commitments: &BTreeMap<u32, FeldmanCommitment>,
+        complaints: &[Reveal],
+        reveals: &[Reveal],
+    ) -> Result<DkgOutput, DkgError> {
+        let params = self.params;
+        let exposed = self.exposed(commitments, complaints);
+
+        let mut group_key = Xsk233Projective::zero();
+        let mut verification_shares = vec![Xsk233Projective::zero(); params.participants as usize];
+        for dealer in &self.qualified {
+            if exposed.contains(dealer) {
+                let pedersen = self
+                    .commitments
+                    .get(dealer)
+                    .ok_or(DkgError::MissingCommitment(*dealer))?;
+                let mut shares: Vec<_> = reveals
+                    .iter()
+                    .filter(|r| {
+                        r.dealer == *dealer && params.check_pedersen(pedersen, r.holder, &r.share)
+                    })
+                    .map(|r| (r.holder, r.share.value))
+                    .collect();
+                shares.sort_by_key(|(holder, _)| *holder);
+                shares.dedup_by_key(|(holder, _)| *holder);
+                if shares.len() < params.threshold as usize {
+                    return Err(DkgError::NotEnoughShares(*dealer));
+                }
+                let g = Xsk233Affine::generator();
+                group_key += g * shamir::interpolate(&shares, 0);
+                for (j, share) in (1..).zip(&mut verification_shares) {
+                    *share += g * shamir::interpolate(&shares, j);
+                }
+            } else {
+                let commitment = &commitments
+                    .get(dealer)
+                    .ok_or(DkgError::MissingCommitment(*dealer))?
+                    .0;
+                group_key += commitment[0];
+                for (j, share) in (1..).zip(&mut verification_shares) {
+                    *share += shamir::evaluate_commitment(commitment, j);
+                }
+            }
+        }
+
+        let secret_share = self
+            .qualified
+            .iter()
+            .map(|d| self.share_of(*d).map(|s| s.value))
+            .sum::<Result<Fr, _>>()?;
+        let verification_shares = (1..)
+            .zip(Xsk233Projective::normalize_batch(&verification_shares))
+            .collect();
+        Ok(DkgOutput {
+            index: self.index,
+            threshold: params.threshold,
+            secret_share,
+            group_key: group_key.into_affine(),
+            verification_shares,
+            qualified: self.qualified.clone(),
+        })
+    }
+}
+
+impl Participant {
+    fn share_of(&self, dealer: u32) -> Result<&Share, DkgError> {
+        self.shares
+            .get(&dealer)
+            .ok_or(DkgError::MissingShare(dealer))
+    }
+
+    /// A qualified dealer without a valid share for this party, if any.
+    fn missing_share(&self) -> Option<u32> {
+        self.qualified
+            .iter()
+            .find(|dealer| !self.shares.contains_key(dealer))
+            .copied()
+    }
+}
+
+impl Parameters {
+    /// Checks `share` of `holder` against a Pedersen commitment.
+    fn check_pedersen(&self, commitment: &PedersenCommitment, holder: u32, share: &Share) -> bool {
+        commitment.0.len() == self.threshold as usize
+            && shamir::evaluate_commitment(&commitment.0, holder)
+                == Xsk233Projective::double_mul(
+                    &share.value,
+                    &Xsk233Affine::generator(),
+                    &share.blinding,
+                    &second_generator(),
+                )
+    }
+
+    /// Checks `share` of `holder` against a Feldman commitment.
+    fn check_feldman(&self, commitment: &FeldmanCommitment, holder: u32, share: &Share) -> bool {
+        commitment.0.len() == self.threshold as usize
+            && shamir::evaluate_commitment(&commitment.0, holder)
+                == Xsk233Affine::generator() * share.value
+    }
+}
+
+/// The generator `H` of the Pedersen commitments.
+fn second_generator() -> Xsk233Affine {
+    hash_to_curve_vartime(GENERATOR_DST, b"H")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frost;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_malicious_participants() {
+        let mut rng = test_rng();
+        let params = Parameters::new(3, 5).unwrap();
+        let mut parties: Vec<_> = (1..=5)
+            .map(|i| Participant::new(&mut rng, params, i).unwrap())
+            .collect();
+
+        // phase 1: 2 sends a bad share to 4 but answers the complaint,
+        // 5 sends a bad share to 1 and stays silent
+        let bad_share = |s: Share| Share {
+            value: s.value + Fr::from(1u64),
+            blinding: s.blinding,
+        };
+        let mut complaints = Vec::new();
+        for dealer in 1..=5 {
+            let commitment = parties[dealer as usize - 1].commitment();
+            for holder in (1..=5).filter(|h| *h != dealer) {
+                let mut share = parties[dealer as usize - 1].share_for(holder);
+                if (dealer, holder) == (2, 4) || (dealer, holder) == (5, 1) {
+                    share = bad_share(share);
+                }
+                let holder = &mut parties[holder as usize - 1];
+                complaints.extend(holder.receive(dealer, commitment.clone(), Some(share)));
+            }
+        }
+        assert_eq!(complaints.len(), 2);
+
+        // phase 2; a broadcast that drops the complaint of 4 leaves it
+        // without a valid share of the qualified dealer 2
+        assert_eq!(
+            parties[3].qualify(&complaints[1..], &[]).err(),
+            Some(DkgError::MissingShare(2))
+        );
+        let answers = parties[1].answer(&complaints);
+        for party in &mut parties {
+            let qualified = party.qualify(&complaints, &answers).unwrap();
+            assert_eq!(*qualified, BTreeSet::from([1, 2, 3, 4]));
+        }
+
+        // phase 3: 3 broadcasts a commitment to another polynomial
+        let mut feldman: BTreeMap<_, _> = parties[..4]
+            .iter()
+            .map(|p| (p.index(), p.feldman_commitment()))
+            .collect();
+        let honest_feldman = feldman[&3].clone();
+        feldman.get_mut(&3).unwrap().0[1] = Xsk233Affine::generator();
+        let feldman_complaints: Vec<_> = parties
+            .iter()
+            .flat_map(|p| p.check_feldman(&feldman).unwrap())
+            .collect();
+        assert!(feldman_complaints.iter().all(|r| r.dealer == 3));
+
+        // phase 4: the others reveal their shares of 3, which 1 corrupts
+        let exposed = parties[0].exposed(&feldman, &feldman_complaints);
+        assert_eq!(exposed, BTreeSet::from([3]));
+        let mut reveals: Vec<_> = parties
+            .iter()
+            .filter(|p| p.index() != 3)
+            .flat_map(|p| p.reveal(&exposed).unwrap())
+            .collect();
+        reveals[0].share = bad_share(reveals[0].share.clone());
+
+        let outputs: Vec<_> = parties
+            .iter()
+            .map(|p| p.finalize(&feldman, &feldman_complaints, &reveals).unwrap())
+            .collect();
+        assert_eq!(
+            parties[0].finalize(&BTreeMap::new(), &[], &[]).err(),
+            Some(DkgError::MissingCommitment(1))
+        );
+        let group_key = (1..=4)
+            .map(|i| {
+                let c = if i == 3 {
+                    &honest_feldman
+                } else {
+                    &feldman[&i]
+                };
+                c.0[0]
+            })
+            .sum::<Xsk233Projective>();
+        for output in &outputs {
+            assert_eq!(*output.group_key(), group_key);
+            assert_eq!(
+                output.verification_shares(),
+                outputs[0].verification_shares()
+            );
+            assert_eq!(
+                output.verification_shares()[&output.index()],
+                Xsk233Affine::generator() * output.secret_share()
+            );
+        }
+
+        // any three parties can sign under the group key
+        let signers = [1, 3, 5];
+        let mut nonces = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        let keys: BTreeMap<_, _> = signers
+            .iter()
+            .map(|i| (*i, outputs[*i as usize - 1].to_frost()))
+            .collect();
+        for (i, (key, _)) in &keys {
+            let (n, c) = frost::commit(&mut rng, key);
+            nonces.insert(*i, n);
+            commitments.insert(*i, c);
+        }
+        let package = frost::SigningPackage::new(commitments, b"gjkr");
+        let shares = nonces
+            .into_iter()
+            .map(|(i, n)| (i, frost::sign(&package, n, &keys[&i].0).unwrap()))
+            .collect();
+        let signature = frost::aggregate(&package, &shares, &keys[&1].1).unwrap();
+        assert!(signature.verify(outputs[0].group_key(), b"gjkr"));
+    }
+
+    #[test]
+    fn test_parameters() {
+        let mut rng = test_rng();
+        assert_eq!(Parameters::new(0, 3), Err(DkgError::InvalidParameters));
+        assert_eq!(Parameters::new(4, 3), Err(DkgError::InvalidParameters));
+        let params = Parameters::new(2, 3).unwrap();
+        assert_eq!(
+            Participant::new(&mut rng, params, 4).err(),
+            Some(DkgError::InvalidIndex(4))
+        );
+
+        // too many complaints leave too few qualified dealers
+        let mut party = Participant::new(&mut rng, params, 1).unwrap();
+        let complaints: Vec<_> = (2..=3)
+            .map(|dealer| {
+                party
+                    .receive(dealer, PedersenCommitment(vec![]), None)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            party.qualify(&complaints, &[]).err(),
+            Some(DkgError::NotEnoughQualified)
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Chaum-Pedersen proofs of discrete logarithm equality.
+//!
+//! A [`DleqProof`] shows that `A = x*G` and `B = x*H` for the same secret
+//! `x`, without revealing it. The proof is made non-interactive with the
+//! Fiat-Shamir transform: the challenge is drawn from a [`Transcript`] of
+//! the whole statement and the commitments, so a proof is only valid for
+//! the points it was made for.
+//!
+//! Batched proofs cover many pairs `B_i = x*H_i` with a single proof: both
+//! sides combine the pairs with coefficients derived from the statement
+//! and run the single-pair protocol on the combined points.
+
+use crate::affine::Xsk233Affine;
+use crate::group::Xsk233Projective;
+use crate::transcript::Transcript;
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, scalar_from_bytes, scalar_to_bytes};
+use ark_ec::CurveGroup;
+use ark_std::UniformRand;
+use ark_std::rand::Rng;
+use zeroize::Zeroize;
+
+/// Size of an encoded proof: the challenge and the response.
+pub const DLEQ_PROOF_SIZE: usize = 2 * SCALAR_SIZE;
+
+const CHALLENGE_LABEL: &[u8] = b"xsk233-dleq";
+const BATCH_LABEL: &[u8] = b"xsk233-dleq-batch";
+
+/// Non-interactive proof that `log_G(A) = log_H(B)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DleqProof {
+    c: Fr,
+    s: Fr,
+}
+
+impl DleqProof {
+    /// Proves that `x*G` and `x*H` have the same discrete logarithm `x`.
+    pub fn prove<R: Rng + ?Sized>(rng: &mut R, x: &Fr, g: &Xsk233Affine, h: &Xsk233Affine) -> Self {
+        let a = (*g * x).into_affine();
+        let b = (*h * x).into_affine();
+
+        let mut k = Fr::rand(rng);
+        let r1 = (*g * k).into_affine();
+        let r2 = (*h * k).into_affine();
+        let c = challenge(g, h, &a, &b, &r1, &r2);
+        let s = k - c * x;
+        k.zeroize();
+        Self { c, s }
+    }
+
+    /// Verifies the proof for `A = x*G` and `B = x*H`.
+    pub fn verify(
+        &self,
+        g: &Xsk233Affine,
+        h: &Xsk233Affine,
+        a: &Xsk233Affine,
+        b: &Xsk233Affine,
+    ) -> bool {
+        // R1 = s*G + c*A, R2 = s*H + c*B
+        let r1 = Xsk233Projective::double_mul(&self.s, g, &self.c, a).into_affine();
+        let r2 = Xsk233Projective::double_mul(&self.s, h, &self.c, b).into_affine();
+        challenge(g, h, a, b, &r1, &r2) == self.c
+    }
+
+    /// Proves that every `x*H_i` has the same discrete logarithm as `x*G`.
+    pub fn prove_batch<R: Rng + ?Sized>(
+        rng: &mut R,
+        x: &Fr,
+        g: &Xsk233Affine,
+        hs: &[Xsk233Affine],
+    ) -> Self {
+        let a = (*g * x).into_affine();
+        let bs = Xsk233Projective::normalize_batch(&hs.iter().map(|h| *h * x).collect::<Vec<_>>());
+        let h = combine(g, &a, hs, &bs, hs);
+        Self::prove(rng, x, g, &h)
+    }
+
+    /// Verifies a batched proof for `A = x*G` and `B_i = x*H_i`. Fails if
+    /// `hs` and `bs` have different lengths.
+    pub fn verify_batch(
+        &self,
+        g: &Xsk233Affine,
+        a: &Xsk233Affine,
+        hs: &[Xsk233Affine],
+        bs: &[Xsk233Affine],
+    ) -> bool {
+        if hs.len() != bs.len() {
+            return false;
+        }
+        let h = combine(g, a, hs, bs, hs);
+        let b = combine(g, a, hs, bs, bs);
+        self.verify(g, &h, a, &b)
+    }
+
+    /// Encodes the challenge and the response.
+    pub fn to_bytes(&self) -> [u8; DLEQ_PROOF_SIZE] {
+        let mut bytes = [0u8; DLEQ_PROOF_SIZE];
+        bytes[..SCALAR_SIZE].copy_from_slice(&scalar_to_bytes(&self.c));
+        bytes[SCALAR_SIZE..].copy_from_slice(&scalar_to_bytes(&self.s));
+        bytes
+    }
+
+    /// Decodes a proof; returns `None` if a scalar is not canonical.
+    pub fn from_bytes(bytes: &[u8; DLEQ_PROOF_SIZE]) -> Option<Self> {
+        let (c, s) = bytes.split_at(SCALAR_SIZE);
+        Some(Self {
+            c: scalar_from_bytes(c.try_into().unwrap())?,
+            s: scalar_from_bytes(s.try_into().unwrap())?,
+        })
+    }
+}
+
+fn challenge(
+    g: &Xsk233Affine,
+    h: &Xsk233Affine,
+    a: &Xsk233Affine,
+    b: &Xsk233Affine,
+    r1: &Xsk233Affine,
+    r2: &Xsk233Affine,
+) -> Fr {
+    let mut transcript = Transcript::new(CHALLENGE_LABEL);
+    transcript.append_point(b"G", g);
+    transcript.append_point(b"H", h);
+    transcript.append_point(b"A", a);
+    transcript.append_point(b"B", b);
+    transcript.append_point(b"R1", r1);
+    transcript.append_point(b"R2", r2);
+    transcript.challenge_scalar(b"c")
+}
+
+/// Computes `sum(z_i * P_i)` for `points` among `hs` or `bs`, with
+/// coefficients `z_i` bound to the whole batch statement.
+fn combine(
+    g: &Xsk233Affine,
+    a: &Xsk233Affine,
+    hs: &[Xsk233Affine],
+    bs: &[Xsk233Affine],
+    points: &[Xsk233Affine],
+) -> Xsk233Affine {
+    let mut transcript = Transcript::new(BATCH_LABEL);
+    transcript.append_point(b"G", g);
+    transcript.append_point(b"A", a);
+    transcript.append_u64(b"n", hs.len() as u64);
+    for (h, b) in hs.iter().zip(bs) {
+        transcript.append_point(b"H", h);
+        transcript.append_point(b"B", b);
+    }
+    points
+        .iter()
+        .map(|p| *p * transcript.challenge_scalar(b"z"))
+        .sum::<Xsk233Projective>()
+        .into_affine()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineRepr;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_prove_and_verify() {
+        let mut rng = test_rng();
+        let x = Fr::rand(&mut rng);
+        let g = Xsk233Affine::generator();
+        let h = Xsk233Affine::rand(&mut rng);
+        let a = (g * x).into_affine();
+        let b = (h * x).into_affine();
+
+        let proof = DleqProof::prove(&mut rng, &x, &g, &h);
+        assert!(proof.verify(&g, &h, &a, &b));
+
+        // other statements are rejected
+        let other = (h * (x + Fr::from(1u64))).into_affine();
+        assert!(!proof.verify(&g, &h, &a, &other));
+        assert!(!proof.verify(&g, &h, &b, &a));
+        let forged = DleqProof::prove(&mut rng, &(x + Fr::from(1u64)), &g, &h);
+        assert!(!forged.verify(&g, &h, &a, &b));
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut rng = test_rng();
+        let x = Fr::rand(&mut rng);
+        let g = Xsk233Affine::generator();
+        let a = (g * x).into_affine();
+        let hs: Vec<_> = (0..5).map(|_| Xsk233Affine::rand(&mut rng)).collect();
+        let mut bs: Vec<_> = hs.iter().map(|h| (*h * x).into_affine()).collect();
+
+        let proof = DleqProof::prove_batch(&mut rng, &x, &g, &hs);
+        assert!(proof.verify_batch(&g, &a, &hs, &bs));
+        assert!(!proof.verify_batch(&g, &a, &hs, &bs[1..]));
+
+        bs.swap(0, 1);
+        assert!(!proof.verify_batch(&g, &a, &hs, &bs));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = test_rng();
+        let x = Fr::rand(&mut rng);
+        let g = Xsk233Affine::generator();
+        let h = Xsk233Affine::rand(&mut rng);
+        let proof = DleqProof::prove(&mut rng, &x, &g, &h);
+
+        let bytes = proof.to_bytes();
+        assert_eq!(DleqProof::from_bytes(&bytes), Some(proof));
+
+        // the response must be reduced
+        let mut bytes = bytes;
+        bytes[DLEQ_PROOF_SIZE - 1] = 0xff;
+        assert_eq!(DleqProof::from_bytes(&bytes), None);
+    }
+}
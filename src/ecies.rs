@@ -0,0 +1,420 @@
+//! ECIES hybrid encryption to xsk233 public keys.
+//!
+//! The sender draws an ephemeral key `r`, sends `R = r*G` in its 30-byte
+//! compressed encoding and derives a symmetric key from the shared point
+//! `r*Y` with HKDF-SHA256, whose salt binds both `R` and the recipient key
+//! `Y`. The payload is then sealed with ChaCha20-Poly1305 or AES-256-GCM.
+//!
+//! A message encrypted with [`encrypt`] is `R || ciphertext || tag`. For
+//! payloads too large to hold in memory, [`StreamEncryptor`] splits them
+//! into chunks with the STREAM construction (Hoang, Reyhanitabar, Rogaway
+//! and Vizár): each chunk is sealed under its position and a final-chunk
+//! flag, so that reordered, dropped or truncated chunks are detected.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::keys::SecretKey;
+use aes_gcm::Aes256Gcm;
+use ark_ec::AffineRepr;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::error::Error;
+use zeroize::Zeroizing;
+
+/// Size of the authentication tag appended to every sealed message or
+/// chunk.
+pub const TAG_SIZE: usize = 16;
+
+/// Size of the stream header, the encoded ephemeral key.
+pub const HEADER_SIZE: usize = COMPRESSED_POINT_SIZE;
+
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+/// STREAM nonces end with a 32-bit counter and a last-chunk flag byte.
+const STREAM_NONCE_SIZE: usize = NONCE_SIZE - 5;
+
+const KDF_LABEL: &[u8] = b"xsk233-ecies";
+
+/// AEAD used to seal the payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cipher {
+    ChaCha20Poly1305,
+    Aes256Gcm,
+}
+
+impl Cipher {
+    fn id(self) -> u8 {
+        match self {
+            Cipher::ChaCha20Poly1305 => 1,
+            Cipher::Aes256Gcm => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EciesError {
+    /// The message is shorter than an ephemeral key and a tag.
+    Truncated,
+    /// The ephemeral key is not a valid encoding of a non-neutral point.
+    InvalidEphemeralKey,
+    /// Authentication failed: the message was modified, or it was not
+    /// encrypted to this key with this cipher.
+    Decryption,
+    /// A stream reached the maximum number of chunks.
+    StreamExhausted,
+}
+
+impl Display for EciesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            EciesError::Truncated => write!(f, "truncated message"),
+            EciesError::InvalidEphemeralKey => write!(f, "invalid ephemeral key"),
+            EciesError::Decryption => write!(f, "decryption failed"),
+            EciesError::StreamExhausted => write!(f, "too many stream chunks"),
+        }
+    }
+}
+
+impl Error for EciesError {}
+
+/// Symmetric key and nonce derived from the shared point.
+struct DerivedKey {
+    key: Zeroizing<[u8; KEY_SIZE]>,
+    nonce: [u8; NONCE_SIZE],
+}
+
+/// Derives the symmetric key for `mode` (0 for single messages, 1 for
+/// streams) from the shared point `shared` between `ephemeral` and
+/// `recipient`.
+fn derive_key(
+    cipher: Cipher,
+    mode: u8,
+    ephemeral: &Xsk233Affine,
+    recipient: &Xsk233Affine,
+    shared: &Xsk233Affine,
+) -> DerivedKey {
+    let mut salt = [0u8; 2 * COMPRESSED_POINT_SIZE];
+    salt[..COMPRESSED_POINT_SIZE].copy_from_slice(&ephemeral.to_bytes());
+    salt[COMPRESSED_POINT_SIZE..].copy_from_slice(&recipient.to_bytes());
+    let ikm = Zeroizing::new(shared.to_bytes());
+    let hkdf = Hkdf::<Sha256>::new(Some(&salt), ikm.as_slice());
+
+    let mut okm = Zeroizing::new([0u8; KEY_SIZE + NONCE_SIZE]);
+    hkdf.expand_multi_info(&[KDF_LABEL, &[cipher.id(), mode]], okm.as_mut_slice())
+        .expect("output length is valid");
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+    key.copy_from_slice(&okm[..KEY_SIZE]);
+    DerivedKey {
+        key,
+        nonce: okm[KEY_SIZE..].try_into().unwrap(),
+    }
+}
+
+/// Sender side of the key agreement: returns the ephemeral key and the
+/// derived key.
+fn encapsulate<R: Rng + ?Sized>(
+    rng: &mut R,
+    cipher: Cipher,
+    mode: u8,
+    recipient: &Xsk233Affine,
+) -> (Xsk233Affine, DerivedKey) {
+    let ephemeral = SecretKey::random(rng);
+    let public = ephemeral.public_key();
+    let shared: Xsk233Affine = (*recipient * ephemeral.scalar()).into();
+    let key = derive_key(cipher, mode, &public, recipient, &shared);
+    (public, key)
+}
+
+/// Recipient side of the key agreement.
+fn decapsulate(
+    secret: &SecretKey,
+    cipher: Cipher,
+    mode: u8,
+    header: &[u8; HEADER_SIZE],
+) -> Result<DerivedKey, EciesError> {
+    let ephemeral = Xsk233Affine::from_bytes(header)
+        .filter(|p| !p.is_zero())
+        .ok_or(EciesError::InvalidEphemeralKey)?;
+    let shared: Xsk233Affine = (ephemeral * secret.scalar()).into();
+    Ok(derive_key(
+        cipher,
+        mode,
+        &ephemeral,
+        &secret.public_key(),
+        &shared,
+    ))
+}
+
+/// Encrypts `plaintext` to `recipient`, authenticating `aad` along with
+/// it. The result is [`HEADER_SIZE`] + `plaintext.len()` + [`TAG_SIZE`]
+/// bytes long.
+pub fn encrypt<R: Rng + ?Sized>(
+    rng: &mut R,
+    recipient: &Xsk233Affine,
+    cipher: Cipher,
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let (ephemeral, key) = encapsulate(rng, cipher, 0, recipient);
+    let payload = Payload {
+        msg: plaintext,
+        aad,
+    };
+    let nonce = (&key.nonce).into();
+    let sealed = match cipher {
+        Cipher::ChaCha20Poly1305 => {
+            ChaCha20Poly1305::new(key.key.as_slice().into()).encrypt(nonce, payload)
+        }
+        Cipher::Aes256Gcm => Aes256Gcm::new(key.key.as_slice().into()).encrypt(nonce, payload),
+    }
+    .expect("message fits in a single AEAD call");
+
+    let mut message = Vec::with_capacity(HEADER_SIZE + sealed.len());
+    message.extend_from_slice(&ephemeral.to_bytes());
+    message.extend_from_slice(&sealed);
+    message
+}
+
+/// Decrypts a message produced by [`encrypt`] with the same `cipher` and
+/// `aad`.
+pub fn decrypt(
+    secret: &SecretKey,
+    cipher: Cipher,
+    aad: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, EciesError> {
+    if message.len() < HEADER_SIZE + TAG_SIZE {
+        return Err(EciesError::Truncated);
+    }
+    let (header, sealed) = message.split_at(HEADER_SIZE);
+    let key = decapsulate(secret, cipher, 0, header.try_into().unwrap())?;
+    let payload = Payload { msg: sealed, aad };
+    let nonce = (&key.nonce).into();
+    match cipher {
+        Cipher::ChaCha20Poly1305 => {
+            ChaCha20Poly1305::new(key.key.as_slice().into()).decrypt(nonce, payload)
+        }
+        Cipher::Aes256Gcm => Aes256Gcm::new(key.key.as_slice().into()).decrypt(nonce, payload),
+    }
+    .map_err(|_| EciesError::Decryption)
+}
+
+// the AES key schedule takes a kilobyte, so it is boxed
+enum Encryptor {
+    ChaCha20Poly1305(EncryptorBE32<ChaCha20Poly1305>),
+    Aes256Gcm(Box<EncryptorBE32<Aes256Gcm>>),
+}
+
+enum Decryptor {
+    ChaCha20Poly1305(DecryptorBE32<ChaCha20Poly1305>),
+    Aes256Gcm(Box<DecryptorBE32<Aes256Gcm>>),
+}
+
+/// Chunked encryption of a large payload to an xsk233 public key.
+///
+/// The [`header`](Self::header) is sent first, then every chunk as sealed
+/// by [`encrypt_chunk`](Self::encrypt_chunk) and finally the one sealed by
+/// [`finish`](Self::finish). Chunk boundaries are not encoded: the
+/// transport must delimit them, and each sealed chunk is [`TAG_SIZE`]
+/// bytes longer than its plaintext.
+pub struct StreamEncryptor {
+    header: [u8; HEADER_SIZE],
+    inner: Encryptor,
+}
+
+impl StreamEncryptor {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R, recipient: &Xsk233Affine, cipher: Cipher) -> Self {
+        let (ephemeral, key) = encapsulate(rng, cipher, 1, recipient);
+        let nonce = key.nonce[..STREAM_NONCE_SIZE].into();
+        let inner = match cipher {
+            Cipher::ChaCha20Poly1305 => {
+                Encryptor::ChaCha20Poly1305(EncryptorBE32::new(key.key.as_slice().into(), nonce))
+            }
+            Cipher::Aes256Gcm => Encryptor::Aes256Gcm(Box::new(EncryptorBE32::new(
+                key.key.as_slice().into(),
+                nonce,
+            ))),
+        };
+        Self {
+            header: ephemeral.to_bytes(),
+            inner,
+        }
+    }
+
+    pub fn header(&self) -> &[u8; HEADER_SIZE] {
+        &self.header
+    }
+
+    /// Seals the next chunk of the payload.
+    pub fn encrypt_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, EciesError> {
+        match &mut self.inner {
+            Encryptor::ChaCha20Poly1305(e) => e.encrypt_next(chunk),
+            Encryptor::Aes256Gcm(e) => e.encrypt_next(chunk),
+        }
+        .map_err(|_| EciesError::StreamExhausted)
+    }
+
+    /// Seals the last chunk of the payload, which may be empty.
+    pub fn finish(self, chunk: &[u8]) -> Result<Vec<u8>, EciesError> {
+        match self.inner {
+            Encryptor::ChaCha20Poly1305(e) => e.encrypt_last(chunk),
+            Encryptor::Aes256Gcm(e) => (*e).encrypt_last(chunk),
+        }
+        .map_err(|_| EciesError::StreamExhausted)
+    }
+}
+
+/// Decryption of a stream produced by [`StreamEncryptor`].
+///
+/// The payload is only complete once [`finish`](Self::finish) succeeds on
+/// the last chunk; a stream that ends before it was truncated.
+pub struct StreamDecryptor {
+    inner: Decryptor,
+}
+
+impl StreamDecryptor {
+    pub fn new(
+        secret: &SecretKey,
+        cipher: Cipher,
+        header: &[u8; HEADER_SIZE],
+    ) -> Result<Self, EciesError> {
+        let key = decapsulate(secret, cipher, 1, header)?;
+        let nonce = key.nonce[..STREAM_NONCE_SIZE].into();
+        let inner = match cipher {
+            Cipher::ChaCha20Poly1305 => {
+                Decryptor::ChaCha20Poly1305(DecryptorBE32::new(key.key.as_slice().into(), nonce))
+            }
+            Cipher::Aes256Gcm => Decryptor::Aes256Gcm(Box::new(DecryptorBE32::new(
+                key.key.as_slice().into(),
+                nonce,
+            ))),
+        };
+        Ok(Self { inner })
+    }
+
+    /// Opens the next chunk.
+    pub fn decrypt_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, EciesError> {
+        match &mut self.inner {
+            Decryptor::ChaCha20Poly1305(d) => d.decrypt_next(chunk),
+            Decryptor::Aes256Gcm(d) => d.decrypt_next(chunk),
+        }
+        .map_err(|_| EciesError::Decryption)
+    }
+
+    /// Opens the last chunk.
+    pub fn finish(self, chunk: &[u8]) -> Result<Vec<u8>, EciesError> {
+        match self.inner {
+            Decryptor::ChaCha20Poly1305(d) => d.decrypt_last(chunk),
+            Decryptor::Aes256Gcm(d) => (*d).decrypt_last(chunk),
+        }
+        .map_err(|_| EciesError::Decryption)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    const CIPHERS: [Cipher; 2] = [Cipher::ChaCha20Poly1305, Cipher::Aes256Gcm];
+
+    #[test]
+    fn test_roundtrip() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        for cipher in CIPHERS {
+            for len in [0, 1, 100] {
+                let plaintext = vec![0x5a; len];
+                let message = encrypt(&mut rng, &secret.public_key(), cipher, b"aad", &plaintext);
+                assert_eq!(message.len(), HEADER_SIZE + len + TAG_SIZE);
+                assert_eq!(decrypt(&secret, cipher, b"aad", &message), Ok(plaintext));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tampering() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let other = SecretKey::random(&mut rng);
+        for cipher in CIPHERS {
+            let message = encrypt(&mut rng, &secret.public_key(), cipher, b"aad", b"payload");
+
+            // every bit of the message is authenticated
+            for i in 0..message.len() * 8 {
+                let mut tampered = message.clone();
+                tampered[i / 8] ^= 1 << (i % 8);
+                assert!(decrypt(&secret, cipher, b"aad", &tampered).is_err());
+            }
+            let err = Err(EciesError::Decryption);
+            assert_eq!(decrypt(&secret, cipher, b"other", &message), err);
+            assert_eq!(decrypt(&other, cipher, b"aad", &message), err);
+            let swapped = CIPHERS.into_iter().find(|c| *c != cipher).unwrap();
+            assert_eq!(decrypt(&secret, swapped, b"aad", &message), err);
+
+            assert_eq!(
+                decrypt(
+                    &secret,
+                    cipher,
+                    b"aad",
+                    &message[..HEADER_SIZE + TAG_SIZE - 1]
+                ),
+                Err(EciesError::Truncated)
+            );
+            let mut neutral = message.clone();
+            neutral[..HEADER_SIZE].fill(0);
+            assert_eq!(
+                decrypt(&secret, cipher, b"aad", &neutral),
+                Err(EciesError::InvalidEphemeralKey)
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        for cipher in CIPHERS {
+            let mut encryptor = StreamEncryptor::new(&mut rng, &secret.public_key(), cipher);
+            let header = *encryptor.header();
+            let mut chunks: Vec<_> = (0..3u8)
+                .map(|i| encryptor.encrypt_chunk(&[i; 64]).unwrap())
+                .collect();
+            chunks.push(encryptor.finish(b"end").unwrap());
+
+            let decrypt_all = |chunks: &[Vec<u8>]| {
+                let mut decryptor = StreamDecryptor::new(&secret, cipher, &header)?;
+                let (last, init) = chunks.split_last().unwrap();
+                let mut payload = Vec::new();
+                for chunk in init {
+                    payload.extend(decryptor.decrypt_chunk(chunk)?);
+                }
+                payload.extend(decryptor.finish(last)?);
+                Ok::<_, EciesError>(payload)
+            };
+            let mut expected: Vec<u8> = (0..3).flat_map(|i| [i; 64]).collect();
+            expected.extend(b"end");
+            assert_eq!(decrypt_all(&chunks), Ok(expected));
+
+            // reordered, truncated and modified streams are rejected
+            let err = Err(EciesError::Decryption);
+            let mut reordered = chunks.clone();
+            reordered.swap(0, 1);
+            assert_eq!(decrypt_all(&reordered), err);
+            assert_eq!(decrypt_all(&chunks[..3]), err);
+            assert_eq!(decrypt_all(&chunks[1..]), err);
+            let mut modified = chunks.clone();
+            modified[2][0] ^= 1;
+            assert_eq!(decrypt_all(&modified), err);
+
+            // streams and single messages use different keys
+            let mut message = header.to_vec();
+            message.extend(&chunks[3]);
+            assert_eq!(decrypt(&secret, cipher, b"", &message), err);
+        }
+    }
+}
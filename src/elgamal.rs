@@ -0,0 +1,483 @@
+//! ElGamal encryption over xsk233.
+//!
+//! A [`Ciphertext`] of a point `M` under the public key `Y = x*G` is the
+//! pair `(r*G, M + r*Y)`. Ciphertexts are additively homomorphic: their
+//! sum encrypts the sum of the plaintexts, and they can be multiplied by
+//! public scalars and re-randomized without the key.
+//!
+//! Integers are encrypted "in the exponent" as `m*G`, which makes sums of
+//! ciphertexts decrypt to sums of integers, e.g. for tallying votes.
+//! Decrypting them requires a discrete logarithm, which [`DiscreteLog`]
+//! solves with baby-step giant-step for values below a chosen bound.
+//!
+//! For threshold decryption, the secret key is Shamir-shared among
+//! parties holding [`KeyShare`]s; any `t` of them publish
+//! [`DecryptionShare`]s, each with a DLEQ proof against the party's
+//! verification key, which [`combine_shares`] interpolates.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::dleq::DleqProof;
+use crate::group::Xsk233Projective;
+use crate::keys::SecretKey;
+use crate::shamir;
+use crate::xsk233::Fr;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
+use ark_ff::Zero;
+use ark_std::UniformRand;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use ark_std::ops::{Add, Mul, Neg, Sub};
+use ark_std::rand::Rng;
+use std::collections::HashMap;
+use std::error::Error;
+use zeroize::Zeroize;
+
+/// Size of an encoded ciphertext.
+pub const CIPHERTEXT_SIZE: usize = 2 * COMPRESSED_POINT_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElGamalError {
+    /// No decryption shares were given.
+    NoShares,
+    /// A share has index zero, or two shares have the same index.
+    InvalidIndex,
+}
+
+impl Display for ElGamalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ElGamalError::NoShares => write!(f, "no decryption shares"),
+            ElGamalError::InvalidIndex => write!(f, "invalid or duplicate share index"),
+        }
+    }
+}
+
+impl Error for ElGamalError {}
+
+/// ElGamal key pair.
+#[derive(Clone, Debug)]
+pub struct Keypair {
+    secret: SecretKey,
+    public: Xsk233Affine,
+}
+
+impl Keypair {
+    pub fn new(secret: SecretKey) -> Self {
+        let public = secret.public_key();
+        Self { secret, public }
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(SecretKey::random(rng))
+    }
+
+    pub fn secret(&self) -> &SecretKey {
+        &self.secret
+    }
+
+    pub fn public(&self) -> &Xsk233Affine {
+        &self.public
+    }
+
+    /// Decrypts `ciphertext` to a point.
+    pub fn decrypt(&self, ciphertext: &Ciphertext) -> Xsk233Projective {
+        ciphertext.c2 - ciphertext.c1 * self.secret.scalar()
+    }
+
+    /// Decrypts an exponent-encoded integer; returns `None` if it is not
+    /// below the bound of `table`.
+    pub fn decrypt_exponent(&self, ciphertext: &Ciphertext, table: &DiscreteLog) -> Option<u64> {
+        table.solve(&self.decrypt(ciphertext))
+    }
+}
+
+/// ElGamal ciphertext `(r*G, M + r*Y)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ciphertext {
+    c1: Xsk233Projective,
+    c2: Xsk233Projective,
+}
+
+impl Ciphertext {
+    /// Encrypts the point `m` to `public`.
+    pub fn encrypt<R: Rng + ?Sized>(rng: &mut R, public: &Xsk233Affine, m: &Xsk233Affine) -> Self {
+        let mut r = Fr::rand(rng);
+        let ciphertext = Self::encrypt_with_randomness(public, m, &r);
+        r.zeroize();
+        ciphertext
+    }
+
+    /// Encrypts the integer `m` as `m*G`.
+    pub fn encrypt_exponent<R: Rng + ?Sized>(rng: &mut R, public: &Xsk233Affine, m: u64) -> Self {
+        let m = (Xsk233Affine::generator() * Fr::from(m)).into_affine();
+        Self::encrypt(rng, public, &m)
+    }
+
+    /// Encrypts `m` with the given randomness `r`, e.g. to prove statements
+    /// about the ciphertext.
+    pub fn encrypt_with_randomness(public: &Xsk233Affine, m: &Xsk233Affine, r: &Fr) -> Self {
+        Self {
+            c1: Xsk233Projective::generator() * r,
+            c2: *public * r + m,
+        }
+    }
+
+    /// The ciphertext of the neutral element with zero randomness, the
+    /// identity for ciphertext addition.
+    pub fn zero() -> Self {
+        Self {
+            c1: Xsk233Projective::zero(),
+            c2: Xsk233Projective::zero(),
+        }
+    }
+
+    pub fn c1(&self) -> &Xsk233Projective {
+        &self.c1
+    }
+
+    pub fn c2(&self) -> &Xsk233Projective {
+        &self.c2
+    }
+
+    /// Returns a fresh encryption of the same plaintext, unlinkable to
+    /// `self` without the secret key.
+    pub fn rerandomize<R: Rng + ?Sized>(&self, rng: &mut R, public: &Xsk233Affine) -> Self {
+        let mut r = Fr::rand(rng);
+        let zero = Self::encrypt_with_randomness(public, &Xsk233Affine::zero(), &r);
+        r.zeroize();
+        *self + zero
+    }
+
+    pub fn to_bytes(&self) -> [u8; CIPHERTEXT_SIZE] {
+        let mut bytes = [0u8; CIPHERTEXT_SIZE];
+        bytes[..COMPRESSED_POINT_SIZE].copy_from_slice(&self.c1.to_bytes());
+        bytes[COMPRESSED_POINT_SIZE..].copy_from_slice(&self.c2.to_bytes());
+        bytes
+    }
+
+    /// Decodes a ciphertext; returns `None` if a point is invalid.
+    pub fn from_bytes(bytes: &[u8; CIPHERTEXT_SIZE]) -> Option<Self> {
+        let (c1, c2) = bytes.split_at(COMPRESSED_POINT_SIZE);
+        Some(Self {
+            c1: Xsk233Affine::from_bytes(c1.try_into().unwrap())?.into(),
+            c2: Xsk233Affine::from_bytes(c2.try_into().unwrap())?.into(),
+        })
+    }
+}
+
+impl Add for Ciphertext {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.c1 += &other.c1;
+        self.c2 += &other.c2;
+        self
+    }
+}
+
+impl Sub for Ciphertext {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        self.c1 -= &other.c1;
+        self.c2 -= &other.c2;
+        self
+    }
+}
+
+impl Neg for Ciphertext {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            c1: -self.c1,
+            c2: -self.c2,
+        }
+    }
+}
+
+impl Mul<Fr> for Ciphertext {
+    type Output = Self;
+
+    fn mul(self, k: Fr) -> Self {
+        Self {
+            c1: self.c1 * k,
+            c2: self.c2 * k,
+        }
+    }
+}
+
+impl<'a> Mul<&'a Fr> for Ciphertext {
+    type Output = Self;
+
+    fn mul(self, k: &'a Fr) -> Self {
+        self * *k
+    }
+}
+
+/// Shamir share `x_i = f(i)` of an ElGamal secret key `x = f(0)`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct KeyShare {
+    index: u32,
+    secret: Fr,
+}
+
+impl KeyShare {
+    /// `secret` is the evaluation at the non-zero `index`.
+    pub fn new(index: u32, secret: Fr) -> Self {
+        assert_ne!(index, 0, "share indices start at 1");
+        Self { index, secret }
+    }
+
+    /// Splits `secret` into `parties` shares with indices `1..=parties`,
+    /// any `threshold` of which can decrypt, with a trusted dealer.
+    ///
+    /// # Panics
+    ///
+    /// If `threshold` is zero or greater than `parties`.
+    pub fn deal<R: Rng + ?Sized>(
+        rng: &mut R,
+        secret: &SecretKey,
+        threshold: u32,
+        parties: u32,
+    ) -> Vec<Self> {
+        assert!(0 < threshold && threshold <= parties, "invalid threshold");
+        let mut coeffs = shamir::random_polynomial(rng, *secret.scalar(), threshold);
+        let shares = (1..=parties)
+            .map(|index| Self {
+                index,
+                secret: shamir::evaluate(&coeffs, index),
+            })
+            .collect();
+        coeffs.zeroize();
+        shares
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn secret(&self) -> &Fr {
+        &self.secret
+    }
+
+    /// The public `x_i*G` against which decryption shares are verified.
+    pub fn verification_key(&self) -> Xsk233Affine {
+        (Xsk233Affine::generator() * self.secret).into_affine()
+    }
+}
+
+impl ark_std::fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("KeyShare")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Partial decryption `x_i*C1` by the holder of a [`KeyShare`], with a
+/// proof that it used the same `x_i` as its verification key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecryptionShare {
+    index: u32,
+    share: Xsk233Affine,
+    proof: DleqProof,
+}
+
+impl DecryptionShare {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R, key: &KeyShare, ciphertext: &Ciphertext) -> Self {
+        let c1 = ciphertext.c1.into_affine();
+        Self {
+            index: key.index,
+            share: (c1 * key.secret).into_affine(),
+            proof: DleqProof::prove(rng, &key.secret, &Xsk233Affine::generator(), &c1),
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Checks the share against the verification key of its party.
+    pub fn verify(&self, verification_key: &Xsk233Affine, ciphertext: &Ciphertext) -> bool {
+        self.proof.verify(
+            &Xsk233Affine::generator(),
+            &ciphertext.c1.into_affine(),
+            verification_key,
+            &self.share,
+        )
+    }
+}
+
+/// Decrypts `ciphertext` from `threshold` verified shares or more.
+///
+/// The shares are not verified here; with fewer shares than the threshold
+/// the result is a meaningless point.
+pub fn combine_shares(
+    ciphertext: &Ciphertext,
+    shares: &[DecryptionShare],
+) -> Result<Xsk233Projective, ElGamalError> {
+    if shares.is_empty() {
+        return Err(ElGamalError::NoShares);
+    }
+    let indices: Vec<_> = shares.iter().map(|s| s.index).collect();
+    for (k, i) in indices.iter().enumerate() {
+        if *i == 0 || indices[..k].contains(i) {
+            return Err(ElGamalError::InvalidIndex);
+        }
+    }
+
+    // x*C1 = sum(l_i * x_i*C1) with the Lagrange coefficients at zero
+    let shared: Xsk233Projective = shares
+        .iter()
+        .map(|s| s.share * shamir::lagrange_at_zero(s.index, &indices))
+        .sum();
+    Ok(ciphertext.c2 - shared)
+}
+
+/// Baby-step giant-step table for discrete logarithms `m` of `m*G` with
+/// `0 <= m < bound`.
+///
+/// The table holds about `sqrt(bound)` points and a lookup takes as many
+/// additions, so it is worth keeping around between decryptions.
+pub struct DiscreteLog {
+    bound: u64,
+    step: u64,
+    baby_steps: HashMap<[u8; COMPRESSED_POINT_SIZE], u64>,
+    giant_step: Xsk233Projective,
+}
+
+impl DiscreteLog {
+    /// # Panics
+    ///
+    /// If `bound` is zero.
+    pub fn new(bound: u64) -> Self {
+        assert_ne!(bound, 0, "empty range");
+        let step = bound.isqrt() + u64::from(bound.isqrt().pow(2) != bound);
+        let g = Xsk233Projective::generator();
+
+        let mut points = Vec::with_capacity(step as usize);
+        let mut p = Xsk233Projective::zero();
+        for _ in 0..step {
+            points.push(p);
+            p += &g;
+        }
+        let baby_steps = Xsk233Projective::normalize_batch(&points)
+            .iter()
+            .zip(0..)
+            .map(|(p, j)| (p.to_bytes(), j))
+            .collect();
+        Self {
+            bound,
+            step,
+            baby_steps,
+            giant_step: g * Fr::from(step),
+        }
+    }
+
+    pub fn bound(&self) -> u64 {
+        self.bound
+    }
+
+    /// Finds `m < bound` with `point = m*G`.
+    ///
+    /// This runs in variable time: it leaks `m`.
+    pub fn solve(&self, point: &Xsk233Projective) -> Option<u64> {
+        let mut p = *point;
+        for i in 0..self.step {
+            if let Some(j) = self.baby_steps.get(&p.to_bytes()) {
+                let m = i * self.step + j;
+                return (m < self.bound).then_some(m);
+            }
+            p -= &self.giant_step;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_encryption() {
+        let mut rng = test_rng();
+        let keypair = Keypair::random(&mut rng);
+        let m = Xsk233Affine::rand(&mut rng);
+        let ciphertext = Ciphertext::encrypt(&mut rng, keypair.public(), &m);
+        assert_eq!(keypair.decrypt(&ciphertext), m);
+
+        let rerandomized = ciphertext.rerandomize(&mut rng, keypair.public());
+        assert_ne!(rerandomized, ciphertext);
+        assert_eq!(keypair.decrypt(&rerandomized), m);
+
+        let bytes = ciphertext.to_bytes();
+        assert_eq!(Ciphertext::from_bytes(&bytes), Some(ciphertext));
+        assert_eq!(Ciphertext::zero().to_bytes(), [0; CIPHERTEXT_SIZE]);
+    }
+
+    #[test]
+    fn test_homomorphism() {
+        let mut rng = test_rng();
+        let keypair = Keypair::random(&mut rng);
+        let table = DiscreteLog::new(1000);
+        let a = Ciphertext::encrypt_exponent(&mut rng, keypair.public(), 123);
+        let b = Ciphertext::encrypt_exponent(&mut rng, keypair.public(), 45);
+
+        let decrypt = |c| keypair.decrypt_exponent(&c, &table);
+        assert_eq!(decrypt(a + b), Some(168));
+        assert_eq!(decrypt(a - b), Some(78));
+        assert_eq!(decrypt(a * Fr::from(3u64)), Some(369));
+        assert_eq!(decrypt(-(b - a)), Some(78));
+        assert_eq!(decrypt(a * Fr::from(9u64)), None);
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        for bound in [1, 2, 10, 17] {
+            let table = DiscreteLog::new(bound);
+            for m in 0..bound + 3 {
+                let p = Xsk233Projective::generator() * Fr::from(m);
+                assert_eq!(table.solve(&p), (m < bound).then_some(m));
+            }
+        }
+    }
+
+    #[test]
+    fn test_threshold_decryption() {
+        let mut rng = test_rng();
+        let keypair = Keypair::random(&mut rng);
+        let shares = KeyShare::deal(&mut rng, keypair.secret(), 3, 5);
+        let m = Xsk233Affine::rand(&mut rng);
+        let ciphertext = Ciphertext::encrypt(&mut rng, keypair.public(), &m);
+
+        let partials: Vec<_> = shares
+            .iter()
+            .map(|key| DecryptionShare::new(&mut rng, key, &ciphertext))
+            .collect();
+        for (key, partial) in shares.iter().zip(&partials) {
+            assert!(partial.verify(&key.verification_key(), &ciphertext));
+        }
+        assert!(!partials[0].verify(&shares[1].verification_key(), &ciphertext));
+
+        assert_eq!(combine_shares(&ciphertext, &partials[2..]), Ok(m.into()));
+        assert_eq!(
+            combine_shares(&ciphertext, &partials[..2]).map(|p| p == m),
+            Ok(false)
+        );
+        assert_eq!(
+            combine_shares(&ciphertext, &[partials[0], partials[0], partials[1]]),
+            Err(ElGamalError::InvalidIndex)
+        );
+        assert_eq!(
+            combine_shares(&ciphertext, &[]),
+            Err(ElGamalError::NoShares)
+        );
+    }
+}
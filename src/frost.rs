@@ -0,0 +1,598 @@
+//! FROST threshold Schnorr signatures (RFC 9591) over xsk233.
+//!
+//! Keys are shared among `max_signers` participants so that any
+//! `min_signers` of them can sign, either by a trusted dealer with
+//! [`trusted_dealer_keygen`] or without one with the [`dkg`] module. The
+//! signatures are ordinary [`Signature`]s under the group key.
+//!
+//! Signing takes two rounds, with a coordinator that may be one of the
+//! signers:
+//!
+//! 1. every signer calls [`commit`], keeps its [`SigningNonces`] and sends
+//!    its [`SigningCommitments`] to the coordinator, which collects them
+//!    with the message in a [`SigningPackage`];
+//! 2. every signer checks the message, computes its [`SignatureShare`]
+//!    with [`sign`] and sends it back, and the coordinator combines the
+//!    shares with [`aggregate`].
+//!
+//! When the aggregated signature is invalid, [`aggregate`] verifies every
+//! share and names the misbehaving signer (identifiable abort).
+//!
+//! The ciphersuite follows the RFC with the context string
+//! `FROST-xsk233-SHA256-v1`: `H1` and `H3` are [`hash_to_scalar`] with that
+//! context, `H4` and `H5` are SHA-256, and `H2` is the challenge of
+//! [`schnorr`](crate::schnorr) so that signatures verify with
+//! [`Signature::verify`].
+
+pub mod dkg;
+
+use crate::affine::Xsk233Affine;
+use crate::group::Xsk233Projective;
+use crate::hash::hash_to_scalar;
+use crate::keys::SecretKey;
+use crate::schnorr::{self, Signature};
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, shamir};
+use crate::{scalar_from_bytes, scalar_to_bytes};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::error::Error;
+use zeroize::Zeroize;
+
+const CONTEXT_STRING: &[u8] = b"FROST-xsk233-SHA256-v1";
+
+/// Participant identifier, a non-zero integer.
+pub type Identifier = u32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrostError {
+    /// `min_signers` is below 2 or above `max_signers`.
+    InvalidMinSigners,
+    /// An identifier is zero or not one of the participants.
+    InvalidIdentifier(Identifier),
+    /// Fewer than `min_signers` commitments or shares.
+    NotEnoughSigners,
+    /// The signing package lacks the commitments of the signer, or has
+    /// different ones.
+    IncorrectCommitment,
+    /// The shares do not come from the signers of the signing package.
+    IncorrectSignatureShares,
+    /// The signature share of this signer is invalid.
+    InvalidSignatureShare(Identifier),
+    /// The aggregated signature is invalid although every share is valid,
+    /// so the public key package is inconsistent.
+    InvalidSignature,
+    /// A DKG round has packages missing or in excess.
+    IncorrectNumberOfPackages,
+    /// The DKG proof of knowledge of this participant is invalid.
+    InvalidProofOfKnowledge(Identifier),
+    /// The DKG secret share sent by this participant does not match its
+    /// commitment.
+    InvalidSecretShare(Identifier),
+}
+
+impl Display for FrostError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            FrostError::InvalidMinSigners => write!(f, "invalid minimum number of signers"),
+            FrostError::InvalidIdentifier(id) => write!(f, "invalid identifier {id}"),
+            FrostError::NotEnoughSigners => write!(f, "not enough signers"),
+            FrostError::IncorrectCommitment => write!(f, "incorrect signing commitment"),
+            FrostError::IncorrectSignatureShares => write!(f, "shares do not match the signers"),
+            FrostError::InvalidSignatureShare(id) => {
+                write!(f, "invalid signature share from participant {id}")
+            }
+            FrostError::InvalidSignature => write!(f, "invalid signature"),
+            FrostError::IncorrectNumberOfPackages => write!(f, "incorrect number of packages"),
+            FrostError::InvalidProofOfKnowledge(id) => {
+                write!(f, "invalid proof of knowledge from participant {id}")
+            }
+            FrostError::InvalidSecretShare(id) => {
+                write!(f, "invalid secret share from participant {id}")
+            }
+        }
+    }
+}
+
+impl Error for FrostError {}
+
+/// Signing key material of one participant.
+#[derive(Clone, PartialEq, Eq)]
+pub struct KeyPackage {
+    identifier: Identifier,
+    signing_share: Fr,
+    verifying_share: Xsk233Affine,
+    verifying_key: Xsk233Affine,
+    min_signers: u32,
+}
+
+impl KeyPackage {
+    /// Assembles the key material of a participant from the output of an
+    /// external key generation, such as [`crate::dkg`].
+    pub fn new(
+        identifier: Identifier,
+        signing_share: Fr,
+        verifying_key: Xsk233Affine,
+        min_signers: u32,
+    ) -> Self {
+        Self {
+            identifier,
+            signing_share,
+            verifying_share: (Xsk233Affine::generator() * signing_share).into_affine(),
+            verifying_key,
+            min_signers,
+        }
+    }
+
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+
+    pub fn signing_share(&self) -> &Fr {
+        &self.signing_share
+    }
+
+    /// `signing_share*G`, against which the signature shares of this
+    /// participant are verified.
+    pub fn verifying_share(&self) -> &Xsk233Affine {
+        &self.verifying_share
+    }
+
+    /// The group public key.
+    pub fn verifying_key(&self) -> &Xsk233Affine {
+        &self.verifying_key
+    }
+
+    pub fn min_signers(&self) -> u32 {
+        self.min_signers
+    }
+}
+
+impl Debug for KeyPackage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("KeyPackage")
+            .field("identifier", &self.identifier)
+            .field("verifying_key", &self.verifying_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for KeyPackage {
+    fn drop(&mut self) {
+        self.signing_share.zeroize();
+    }
+}
+
+/// Public key material of the group, known to the coordinator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKeyPackage {
+    verifying_shares: BTreeMap<Identifier, Xsk233Affine>,
+    verifying_key: Xsk233Affine,
+}
+
+impl PublicKeyPackage {
+    pub fn new(
+        verifying_shares: BTreeMap<Identifier, Xsk233Affine>,
+        verifying_key: Xsk233Affine,
+    ) -> Self {
+        Self {
+            verifying_shares,
+            verifying_key,
+        }
+    }
+
+    pub fn verifying_shares(&self) -> &BTreeMap<Identifier, Xsk233Affine> {
+        &self.verifying_shares
+    }
+
+    pub fn verifying_key(&self) -> &Xsk233Affine {
+        &self.verifying_key
+    }
+}
+
+/// Splits `secret` among participants `1..=max_signers`, any
+/// `min_signers` of which can sign.
+pub fn trusted_dealer_keygen<R: Rng + ?Sized>(
+    rng: &mut R,
+    secret: &SecretKey,
+    max_signers: u32,
+    min_signers: u32,
+) -> Result<(BTreeMap<Identifier, KeyPackage>, PublicKeyPackage), FrostError> {
+    check_min_signers(max_signers, min_signers)?;
+    let mut coeffs = shamir::random_polynomial(rng, *secret.scalar(), min_signers);
+    let verifying_key = secret.public_key();
+    let key_packages: BTreeMap<_, _> = (1..=max_signers)
+        .map(|identifier| {
+            let signing_share = shamir::evaluate(&coeffs, identifier);
+            let key_package =
+                KeyPackage::new(identifier, signing_share, verifying_key, min_signers);
+            (identifier, key_package)
+        })
+        .collect();
+    coeffs.zeroize();
+
+    let public_key_package = PublicKeyPackage {
+        verifying_shares: key_packages
+            .iter()
+            .map(|(id, key)| (*id, key.verifying_share))
+            .collect(),
+        verifying_key,
+    };
+    Ok((key_packages, public_key_package))
+}
+
+fn check_min_signers(max_signers: u32, min_signers: u32) -> Result<(), FrostError> {
+    if min_signers < 2 || min_signers > max_signers {
+        return Err(FrostError::InvalidMinSigners);
+    }
+    Ok(())
+}
+
+/// Secret nonces of one signing session. They are consumed by [`sign`]
+/// and must never be reused.
+pub struct SigningNonces {
+    hiding: Fr,
+    binding: Fr,
+    commitments: SigningCommitments,
+}
+
+impl SigningNonces {
+    pub fn commitments(&self) -> &SigningCommitments {
+        &self.commitments
+    }
+}
+
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+
+/// Public commitments `(D, E)` to the nonces of a signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigningCommitments {
+    hiding: Xsk233Affine,
+    binding: Xsk233Affine,
+}
+
+impl SigningCommitments {
+    pub fn new(hiding: Xsk233Affine, binding: Xsk233Affine) -> Self {
+        Self { hiding, binding }
+    }
+
+    pub fn hiding(&self) -> &Xsk233Affine {
+        &self.hiding
+    }
+
+    pub fn binding(&self) -> &Xsk233Affine {
+        &self.binding
+    }
+}
+
+/// Round one: generates the nonces of a signing session.
+pub fn commit<R: Rng + ?Sized>(
+    rng: &mut R,
+    key_package: &KeyPackage,
+) -> (SigningNonces, SigningCommitments) {
+    let hiding = nonce_generate(rng, &key_package.signing_share);
+    let binding = nonce_generate(rng, &key_package.signing_share);
+    let g = Xsk233Affine::generator();
+    let commitments = SigningCommitments {
+        hiding: (g * hiding).into_affine(),
+        binding: (g * binding).into_affine(),
+    };
+    let nonces = SigningNonces {
+        hiding,
+        binding,
+        commitments,
+    };
+    (nonces, commitments)
+}
+
+/// The commitments of all signers of a session and the message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigningPackage {
+    commitments: BTreeMap<Identifier, SigningCommitments>,
+    message: Vec<u8>,
+}
+
+impl SigningPackage {
+    pub fn new(commitments: BTreeMap<Identifier, SigningCommitments>, message: &[u8]) -> Self {
+        Self {
+            commitments,
+            message: message.to_vec(),
+        }
+    }
+
+    pub fn commitments(&self) -> &BTreeMap<Identifier, SigningCommitments> {
+        &self.commitments
+    }
+
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    fn signers(&self) -> Vec<Identifier> {
+        self.commitments.keys().copied().collect()
+    }
+}
+
+/// Signature share `z_i` of one signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignatureShare(Fr);
+
+impl SignatureShare {
+    pub fn to_bytes(&self) -> [u8; SCALAR_SIZE] {
+        scalar_to_bytes(&self.0)
+    }
+
+    pub fn from_bytes(bytes: &[u8; SCALAR_SIZE]) -> Option<Self> {
+        scalar_from_bytes(bytes).map(Self)
+    }
+}
+
+/// Binding factors, the group commitment and the challenge of a session.
+struct SessionState {
+    binding_factors: BTreeMap<Identifier, Fr>,
+    group_commitment: Xsk233Affine,
+    challenge: Fr,
+}
+
+impl SessionState {
+    fn new(package: &SigningPackage, verifying_key: &Xsk233Affine) -> Self {
+        let binding_factors = compute_binding_factors(package, verifying_key);
+        let group_commitment = package
+            .commitments
+            .iter()
+            .map(|(id, c)| c.binding * binding_factors[id] + c.hiding)
+            .sum::<Xsk233Projective>()
+            .into_affine();
+        let challenge = schnorr::challenge(&group_commitment, verifying_key, &package.message);
+        Self {
+            binding_factors,
+            group_commitment,
+            challenge,
+        }
+    }
+}
+
+/// Round two: computes the signature share of `key_package` for
+/// `package`, consuming the nonces of round one.
+pub fn sign(
+    package: &SigningPackage,
+    nonces: SigningNonces,
+    key_package: &KeyPackage,
+) -> Result<SignatureShare, FrostError> {
+    if (package.commitments.len() as u32) < key_package.min_signers {
+        return Err(FrostError::NotEnoughSigners);
+    }
+    if package.commitments.get(&key_package.identifier) != Some(&nonces.commitments) {
+        return Err(FrostError::IncorrectCommitment);
+    }
+
+    let state = SessionState::new(package, &key_package.verifying_key);
+    let lambda = shamir::lagrange_at_zero(key_package.identifier, &package.signers());
+    let rho = state.binding_factors[&key_package.identifier];
+    Ok(SignatureShare(
+        nonces.hiding + nonces.binding * rho + lambda * key_package.signing_share * state.challenge,
+    ))
+}
+
+/// Checks the share of `identifier` against its verifying share.
+pub fn verify_signature_share(
+    identifier: Identifier,
+    verifying_share: &Xsk233Affine,
+    share: &SignatureShare,
+    package: &SigningPackage,
+    verifying_key: &Xsk233Affine,
+) -> Result<(), FrostError> {
+    let state = SessionState::new(package, verifying_key);
+    verify_share_with(&state, identifier, verifying_share, share, package)
+}
+
+fn verify_share_with(
+    state: &SessionState,
+    identifier: Identifier,
+    verifying_share: &Xsk233Affine,
+    share: &SignatureShare,
+    package: &SigningPackage,
+) -> Result<(), FrostError> {
+    let commitments = package
+        .commitments
+        .get(&identifier)
+        .ok_or(FrostError::InvalidIdentifier(identifier))?;
+    let lambda = shamir::lagrange_at_zero(identifier, &package.signers());
+
+    // z_i*G = D_i + rho_i*E_i + c*lambda_i*Y_i
+    let expected = commitments.binding * state.binding_factors[&identifier]
+        + commitments.hiding
+        + *verifying_share * (state.challenge * lambda);
+    if Xsk233Affine::generator() * share.0 != expected {
+        return Err(FrostError::InvalidSignatureShare(identifier));
+    }
+    Ok(())
+}
+
+/// Combines the shares of all signers of `package` into a signature, or
+/// names a signer whose share is invalid.
+pub fn aggregate(
+    package: &SigningPackage,
+    shares: &BTreeMap<Identifier, SignatureShare>,
+    public_key_package: &PublicKeyPackage,
+) -> Result<Signature, FrostError> {
+    if !shares.keys().eq(package.commitments.keys()) {
+        return Err(FrostError::IncorrectSignatureShares);
+    }
+    let state = SessionState::new(package, &public_key_package.verifying_key);
+    let z = shares.values().map(|share| share.0).sum();
+    let signature = Signature::new(state.group_commitment, z);
+    if signature.verify(&public_key_package.verifying_key, &package.message) {
+        return Ok(signature);
+    }
+
+    for (id, share) in shares {
+        let verifying_share = public_key_package
+            .verifying_shares
+            .get(id)
+            .ok_or(FrostError::InvalidIdentifier(*id))?;
+        verify_share_with(&state, *id, verifying_share, share, package)?;
+    }
+    // the verifying shares do not match the verifying key
+    Err(FrostError::InvalidSignature)
+}
+
+fn hash_to_bytes(tag: &[u8], input: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(CONTEXT_STRING)
+        .chain_update(tag)
+        .chain_update(input)
+        .finalize()
+        .into()
+}
+
+fn h1(input: &[u8]) -> Fr {
+    hash_to_scalar(&[CONTEXT_STRING, b"rho"].concat(), input)
+}
+
+fn h3(input: &[u8]) -> Fr {
+    hash_to_scalar(&[CONTEXT_STRING, b"nonce"].concat(), input)
+}
+
+fn nonce_generate<R: Rng + ?Sized>(rng: &mut R, secret: &Fr) -> Fr {
+    let mut input = [0u8; 32 + SCALAR_SIZE];
+    rng.fill_bytes(&mut input[..32]);
+    input[32..].copy_from_slice(&scalar_to_bytes(secret));
+    let nonce = h3(&input);
+    input.zeroize();
+    nonce
+}
+
+fn compute_binding_factors(
+    package: &SigningPackage,
+    verifying_key: &Xsk233Affine,
+) -> BTreeMap<Identifier, Fr> {
+    let mut encoded_commitments = Vec::new();
+    for (id, c) in &package.commitments {
+        encoded_commitments.extend_from_slice(&scalar_to_bytes(&Fr::from(*id)));
+        encoded_commitments.extend_from_slice(&c.hiding.to_bytes());
+        encoded_commitments.extend_from_slice(&c.binding.to_bytes());
+    }
+    let prefix = [
+        &verifying_key.to_bytes()[..],
+        &hash_to_bytes(b"msg", &package.message),
+        &hash_to_bytes(b"com", &encoded_commitments),
+    ]
+    .concat();
+    package
+        .commitments
+        .keys()
+        .map(|id| {
+            let input = [&prefix[..], &scalar_to_bytes(&Fr::from(*id))].concat();
+            (*id, h1(&input))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    /// Runs both signing rounds with the signers in `signers`.
+    fn run_signing<R: Rng>(
+        rng: &mut R,
+        key_packages: &BTreeMap<Identifier, KeyPackage>,
+        signers: &[Identifier],
+        message: &[u8],
+    ) -> (SigningPackage, BTreeMap<Identifier, SignatureShare>) {
+        let mut nonces = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        for id in signers {
+            let (n, c) = commit(rng, &key_packages[id]);
+            nonces.insert(*id, n);
+            commitments.insert(*id, c);
+        }
+        let package = SigningPackage::new(commitments, message);
+        let shares = nonces
+            .into_iter()
+            .map(|(id, n)| (id, sign(&package, n, &key_packages[&id]).unwrap()))
+            .collect();
+        (package, shares)
+    }
+
+    #[test]
+    fn test_trusted_dealer() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let (key_packages, public) = trusted_dealer_keygen(&mut rng, &secret, 5, 3).unwrap();
+        assert_eq!(public.verifying_key(), &secret.public_key());
+
+        for signers in [&[1, 2, 3][..], &[2, 4, 5], &[1, 2, 3, 4, 5]] {
+            let (package, shares) = run_signing(&mut rng, &key_packages, signers, b"message");
+            for (id, share) in &shares {
+                verify_signature_share(
+                    *id,
+                    &public.verifying_shares()[id],
+                    share,
+                    &package,
+                    public.verifying_key(),
+                )
+                .unwrap();
+            }
+            let signature = aggregate(&package, &shares, &public).unwrap();
+            assert!(signature.verify(public.verifying_key(), b"message"));
+        }
+
+        assert_eq!(
+            trusted_dealer_keygen(&mut rng, &secret, 3, 1).err(),
+            Some(FrostError::InvalidMinSigners)
+        );
+    }
+
+    #[test]
+    fn test_identifiable_abort() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let (key_packages, public) = trusted_dealer_keygen(&mut rng, &secret, 4, 3).unwrap();
+        let (package, mut shares) = run_signing(&mut rng, &key_packages, &[1, 3, 4], b"msg");
+
+        let bad = SignatureShare(shares[&3].0 + Fr::from(1u64));
+        shares.insert(3, bad);
+        assert_eq!(
+            aggregate(&package, &shares, &public),
+            Err(FrostError::InvalidSignatureShare(3))
+        );
+        shares.remove(&3);
+        assert_eq!(
+            aggregate(&package, &shares, &public),
+            Err(FrostError::IncorrectSignatureShares)
+        );
+    }
+
+    #[test]
+    fn test_signing_errors() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let (key_packages, _) = trusted_dealer_keygen(&mut rng, &secret, 4, 3).unwrap();
+
+        let (n1, c1) = commit(&mut rng, &key_packages[&1]);
+        let (_, c2) = commit(&mut rng, &key_packages[&2]);
+        let package = SigningPackage::new(BTreeMap::from([(1, c1), (2, c2)]), b"msg");
+        assert_eq!(
+            sign(&package, n1, &key_packages[&1]),
+            Err(FrostError::NotEnoughSigners)
+        );
+
+        let (n1, _) = commit(&mut rng, &key_packages[&1]);
+        let (_, c3) = commit(&mut rng, &key_packages[&3]);
+        let package = SigningPackage::new(BTreeMap::from([(1, c1), (2, c2), (3, c3)]), b"msg");
+        assert_eq!(
+            sign(&package, n1, &key_packages[&1]),
+            Err(FrostError::IncorrectCommitment)
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Dealerless FROST key generation.
+//!
+//! This is the key generation of the FROST paper (Komlo and Goldberg),
+//! a Pedersen DKG where every participant also proves knowledge of its
+//! secret to prevent rogue-key attacks. It takes two rounds:
+//!
+//! 1. [`part1`]: every participant draws a random polynomial and
+//!    broadcasts its Feldman commitment with a proof of knowledge of the
+//!    constant term, as a [`Round1Package`];
+//! 2. [`part2`]: after checking all proofs, every participant sends to
+//!    each other one its share in a [`Round2Package`], over a private
+//!    channel;
+//!
+//! and [`part3`] checks the received shares and outputs the
+//! [`KeyPackage`] and [`PublicKeyPackage`]. Any invalid package aborts the
+//! protocol and names its sender.
+
+use super::{FrostError, Identifier, KeyPackage, PublicKeyPackage, check_min_signers};
+use crate::affine::Xsk233Affine;
+use crate::group::Xsk233Projective;
+use crate::hash::hash_to_scalar;
+use crate::scalar_to_bytes;
+use crate::schnorr::{self, Signature};
+use crate::shamir;
+use crate::xsk233::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::UniformRand;
+use ark_std::fmt::{Debug, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use std::collections::BTreeMap;
+use zeroize::Zeroize;
+
+const POK_DST: &[u8] = b"FROST-xsk233-SHA256-v1dkg";
+
+/// State of a participant between [`part1`] and [`part2`].
+pub struct Round1SecretPackage {
+    identifier: Identifier,
+    coefficients: Vec<Fr>,
+    commitment: Vec<Xsk233Affine>,
+    max_signers: u32,
+    min_signers: u32,
+}
+
+impl Debug for Round1SecretPackage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Round1SecretPackage")
+            .field("identifier", &self.identifier)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Round1SecretPackage {
+    fn drop(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+/// Broadcast of round one: the commitment to the polynomial of a
+/// participant and the proof of knowledge of its constant term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Round1Package {
+    commitment: Vec<Xsk233Affine>,
+    proof_of_knowledge: Signature,
+}
+
+impl Round1Package {
+    pub fn commitment(&self) -> &[Xsk233Affine] {
+        &self.commitment
+    }
+}
+
+/// State of a participant between [`part2`] and [`part3`].
+pub struct Round2SecretPackage {
+    identifier: Identifier,
+    commitment: Vec<Xsk233Affine>,
+    own_share: Fr,
+    max_signers: u32,
+    min_signers: u32,
+}
+
+impl Debug for Round2SecretPackage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Round2SecretPackage")
+            .field("identifier", &self.identifier)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Round2SecretPackage {
+    fn drop(&mut self) {
+        self.own_share.zeroize();
+    }
+}
+
+/// Secret share sent privately to one participant in round two.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Round2Package {
+    signing_share: Fr,
+}
+
+impl Debug for Round2Package {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Round2Package").finish_non_exhaustive()
+    }
+}
+
+impl Drop for Round2Package {
+    fn drop(&mut self) {
+        self.signing_share.zeroize();
+    }
+}
+
+fn pok_challenge(identifier: Identifier, public: &Xsk233Affine, r: &Xsk233Affine) -> Fr {
+    let input = [
+        &scalar_to_bytes(&Fr::from(identifier))[..],
+        &public.to_bytes(),
+        &r.to_bytes(),
+    ]
+    .concat();
+    hash_to_scalar(POK_DST, &input)
+}
+
+/// Round one for participant `identifier`, one of `1..=max_signers`.
+pub fn part1<R: Rng + ?Sized>(
+    rng: &mut R,
+    identifier: Identifier,
+    max_signers: u32,
+    min_signers: u32,
+) -> Result<(Round1SecretPackage, Round1Package), FrostError> {
+    check_min_signers(max_signers, min_signers)?;
+    if identifier == 0 || identifier > max_signers {
+        return Err(FrostError::InvalidIdentifier(identifier));
+    }
+
+    let secret = Fr::rand(rng);
+    let coefficients = shamir::random_polynomial(rng, secret, min_signers);
+    let commitment = shamir::commit(&coefficients);
+
+    let k = schnorr::nonce(rng, &coefficients[0]);
+    let r = (Xsk233Affine::generator() * *k).into_affine();
+    let c = pok_challenge(identifier, &commitment[0], &r);
+    let proof_of_knowledge = Signature::new(r, *k + c * coefficients[0]);
+
+    let package = Round1Package {
+        commitment: commitment.clone(),
+        proof_of_knowledge,
+    };
+    let secret_package = Round1SecretPackage {
+        identifier,
+        coefficients,
+        commitment,
+        max_signers,
+        min_signers,
+    };
+    Ok((secret_package, package))
+}
+
+/// Round two: checks the round one packages of all other participants
+/// and computes the shares to send them.
+pub fn part2(
+    secret_package: Round1SecretPackage,
+    round1_packages: &BTreeMap<Identifier, Round1Package>,
+) -> Result<(Round2SecretPackage, BTreeMap<Identifier, Round2Package>), FrostError> {
+    check_participants(
+        secret_package.identifier,
+        secret_package.max_signers,
+        round1_packages,
+    )?;
+    for (id, package) in round1_packages {
+        if package.commitment.len() != secret_package.min_signers as usize {
+            return Err(FrostError::InvalidProofOfKnowledge(*id));
+        }
+        let proof = &package.proof_of_knowledge;
+        let c = pok_challenge(*id, &package.commitment[0], proof.r());
+        // mu*G - c*C_0 = R
+        if Xsk233Projective::mul_add_generator(proof.z(), &-c, &package.commitment[0]) != *proof.r()
+        {
+            return Err(FrostError::InvalidProofOfKnowledge(*id));
+        }
+    }
+
+    let round2_packages = round1_packages
+        .keys()
+        .map(|id| {
+            let signing_share = shamir::evaluate(&secret_package.coefficients, *id);
+            (*id, Round2Package { signing_share })
+        })
+        .collect();
+    let own_share = shamir::evaluate(&secret_package.coefficients, secret_package.identifier);
+    let next = Round2SecretPackage {
+        identifier: secret_package.identifier,
+        commitment: secret_package.commitment.clone(),
+        own_share,
+        max_signers: secret_package.max_signers,
+        min_signers: secret_package.min_signers,
+    };
+    Ok((next, round2_packages))
+}
+
+/// Final step: checks the shares received in round two against the
+/// commitments of round one and derives the keys.
+pub fn part3(
+    secret_package: &Round2SecretPackage,
+    round1_packages: &BTreeMap<Identifier, Round1Package>,
+    round2_packages: &BTreeMap<Identifier, Round2Package>,
+) -> Result<(KeyPackage, PublicKeyPackage), FrostError> {
+    let identifier = secret_package.identifier;
+    check_participants(identifier, secret_package.max_signers, round1_packages)?;
+    if !round2_packages.keys().eq(round1_packages.keys()) {
+        return Err(FrostError::IncorrectNumberOfPackages);
+    }
+
+    let mut signing_share = secret_package.own_share;
+    for (id, package) in round2_packages {
+        let commitment = &round1_packages[id].commitment;
+        if shamir::evaluate_commitment(commitment, identifier)
+            != Xsk233Affine::generator() * package.signing_share
+        {
+            return Err(FrostError::InvalidSecretShare(*id));
+        }
+        signing_share += package.signing_share;
+    }
+
+    // coefficient-wise sum of all commitments
+    let mut group_commitment =
+        vec![Xsk233Projective::default(); secret_package.min_signers as usize];
+    for commitment in round1_packages
+        .values()
+        .map(|p| &p.commitment)
+        .chain([&secret_package.commitment])
+    {
+        for (sum, c) in group_commitment.iter_mut().zip(commitment) {
+            *sum += c;
+        }
+    }
+    let group_commitment = Xsk233Projective::normalize_batch(&group_commitment);
+
+    let verifying_key = group_commitment[0];
+    let verifying_shares: BTreeMap<_, _> = (1..=secret_package.max_signers)
+        .map(|id| {
+            let share = shamir::evaluate_commitment(&group_commitment, id).into_affine();
+            (id, share)
+        })
+        .collect();
+    let key_package = KeyPackage {
+        identifier,
+        signing_share,
+        verifying_share: verifying_shares[&identifier],
+        verifying_key,
+        min_signers: secret_package.min_signers,
+    };
+    let public_key_package = PublicKeyPackage {
+        verifying_shares,
+        verifying_key,
+    };
+    Ok((key_package, public_key_package))
+}
+
+/// Checks that `packages` come from exactly the other participants.
+fn check_participants<T>(
+    identifier: Identifier,
+    max_signers: u32,
+    packages: &BTreeMap<Identifier, T>,
+) -> Result<(), FrostError> {
+    if packages.len() != max_signers as usize - 1 {
+        return Err(FrostError::IncorrectNumberOfPackages);
+    }
+    match packages
+        .keys()
+        .find(|id| **id == 0 || **id == identifier || **id > max_signers)
+    {
+        Some(id) => Err(FrostError::InvalidIdentifier(*id)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SigningPackage, aggregate, commit, sign};
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_dkg_and_signing() {
+        let mut rng = test_rng();
+        let (max_signers, min_signers) = (5, 3);
+
+        let mut round1_secrets = BTreeMap::new();
+        let mut round1_packages = BTreeMap::new();
+        for id in 1..=max_signers {
+            let (secret, package) = part1(&mut rng, id, max_signers, min_signers).unwrap();
+            round1_secrets.insert(id, secret);
+            round1_packages.insert(id, package);
+        }
+        let others = |id: Identifier| {
+            let mut packages = round1_packages.clone();
+            packages.remove(&id);
+            packages
+        };
+
+        // round two: received[j][i] is the share sent by i to j
+        let mut round2_secrets = BTreeMap::new();
+        let mut received: BTreeMap<Identifier, BTreeMap<_, _>> = BTreeMap::new();
+        for (id, secret) in round1_secrets {
+            let (secret, packages) = part2(secret, &others(id)).unwrap();
+            round2_secrets.insert(id, secret);
+            for (to, package) in packages {
+                received.entry(to).or_default().insert(id, package);
+            }
+        }
+
+        let mut key_packages = BTreeMap::new();
+        let mut public_key_packages = Vec::new();
+        for (id, secret) in &round2_secrets {
+            let (key, public) = part3(secret, &others(*id), &received[id]).unwrap();
+            key_packages.insert(*id, key);
+            public_key_packages.push(public);
+        }
+        assert!(public_key_packages.windows(2).all(|w| w[0] == w[1]));
+        let public = &public_key_packages[0];
+
+        let signers = [1, 4, 5];
+        let mut nonces = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        for id in signers {
+            let (n, c) = commit(&mut rng, &key_packages[&id]);
+            nonces.insert(id, n);
+            commitments.insert(id, c);
+        }
+        let package = SigningPackage::new(commitments, b"dkg");
+        let shares = nonces
+            .into_iter()
+            .map(|(id, n)| (id, sign(&package, n, &key_packages[&id]).unwrap()))
+            .collect();
+        let signature = aggregate(&package, &shares, public).unwrap();
+        assert!(signature.verify(public.verifying_key(), b"dkg"));
+
+        // a tampered share is traced back to its sender
+        let mut tampered = received[&2].clone();
+        tampered.get_mut(&3).unwrap().signing_share += Fr::from(1u64);
+        assert_eq!(
+            part3(&round2_secrets[&2], &others(2), &tampered).err(),
+            Some(FrostError::InvalidSecretShare(3))
+        );
+    }
+
+    #[test]
+    fn test_invalid_proof_of_knowledge() {
+        let mut rng = test_rng();
+        let (s1, _) = part1(&mut rng, 1, 3, 2).unwrap();
+        let (_, p2) = part1(&mut rng, 2, 3, 2).unwrap();
+        let (_, mut p3) = part1(&mut rng, 3, 3, 2).unwrap();
+        // claim the commitment of participant 2 as one's own
+        p3.commitment = p2.commitment.clone();
+        p3.proof_of_knowledge = p2.proof_of_knowledge;
+        let packages = BTreeMap::from([(2, p2), (3, p3)]);
+        assert_eq!(
+            part2(s1, &packages).err(),
+            Some(FrostError::InvalidProofOfKnowledge(3))
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use ark_std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    ops::{Add, AddAssign, Mul, MulAssign},
+};
+
+/// Size of the canonical little-endian encoding of a field element.
+pub const GF233_ENCODED_SIZE: usize = 30;
+
+/// Number of meaningful bits in a field element.
+pub const GF233_BITS: usize = 233;
+
+/// Mask of the meaningful bits of the top limb (bits 192..232).
+const TOP_LIMB_MASK: u64 = (1 << 41) - 1;
+
+/// Element of GF(2^233) = GF(2)[z]/(z^233 + z^74 + 1) in polynomial basis.
+///
+/// This is the field xsk233 is defined over. xs233-sys keeps its field
+/// arithmetic internal and its in-memory point layout depends on the build
+/// flags, so whenever coordinates are needed on the Rust side they are
+/// recomputed from the canonical point encoding with this type.
+///
+/// Limbs are little-endian: bit `i` of the polynomial is bit `i % 64` of
+/// limb `i / 64`. Bits above 232 are always zero.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Gf233([u64; 4]);
+
+impl Gf233 {
+    pub const ZERO: Self = Self([0; 4]);
+    pub const ONE: Self = Self([1, 0, 0, 0]);
+
+    /// Builds an element from little-endian limbs, rejecting values that
+    /// have bits set above position 232.
+    pub const fn from_limbs(limbs: [u64; 4]) -> Option<Self> {
+        if limbs[3] & !TOP_LIMB_MASK != 0 {
+            return None;
+        }
+        Some(Self(limbs))
+    }
+
+    pub const fn limbs(&self) -> &[u64; 4] {
+        &self.0
+    }
+
+    /// Decodes the 30-byte little-endian encoding used by xs233. As in the
+    /// C library, encodings with bits set above position 232 are rejected.
+    pub fn from_bytes(bytes: &[u8; GF233_ENCODED_SIZE]) -> Option<Self> {
+        if bytes[29] > 1 {
+            return None;
+        }
+
+        let mut buf = [0u8; 32];
+        buf[..GF233_ENCODED_SIZE].copy_from_slice(bytes);
+
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(buf.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Some(Self(limbs))
+    }
+
+    pub fn to_bytes(&self) -> [u8; GF233_ENCODED_SIZE] {
+        let mut buf = [0u8; 32];
+        for (limb, chunk) in self.0.iter().zip(buf.chunks_exact_mut(8)) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+
+        let mut bytes = [0u8; GF233_ENCODED_SIZE];
+        bytes.copy_from_slice(&buf[..GF233_ENCODED_SIZE]);
+        bytes
+    }
+
+    /// Decodes a 30-byte big-endian octet string, as used by SEC1 for
+    /// elements of binary fields.
+    pub fn from_be_bytes(bytes: &[u8; GF233_ENCODED_SIZE]) -> Option<Self> {
+        let mut le = *bytes;
+        le.reverse();
+        Self::from_bytes(&le)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; GF233_ENCODED_SIZE] {
+        let mut bytes = self.to_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Returns bit `i` (the coefficient of `z^i`).
+    pub fn bit(&self, i: usize) -> bool {
+        debug_assert!(i < GF233_BITS);
+        (self.0[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    /// Builds an element from its coefficients, lowest degree first.
+    pub fn from_bits(bits: &[bool; GF233_BITS]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, bit) in bits.iter().enumerate() {
+            limbs[i / 64] |= (*bit as u64) << (i % 64);
+        }
+        Self(limbs)
+    }
+
+    pub fn to_bits(&self) -> [bool; GF233_BITS] {
+        let mut bits = [false; GF233_BITS];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            *bit = self.bit(i);
+        }
+        bits
+    }
+
+    pub fn square(&self) -> Self {
+        let mut wide = [0u64; 8];
+        for (i, limb) in self.0.iter().enumerate() {
+            wide[2 * i] = spread(*limb as u32);
+            wide[2 * i + 1] = spread((*limb >> 32) as u32);
+        }
+        Self(reduce(wide))
+    }
+
+    /// Computes `self^(2^n)`.
+    pub fn square_n(&self, n: usize) -> Self {
+        let mut res = *self;
+        for _ in 0..n {
+            res = res.square();
+        }
+        res
+    }
+
+    /// Computes the multiplicative inverse with the Itoh-Tsujii addition
+    /// chain 1, 2, 3, 6, 7, 14, 28, 29, 58, 116, 232. As in xs233, the
+    /// inverse of zero is zero.
+    pub fn inverse(&self) -> Self {
+        let b1 = *self;
+        let b2 = b1.square() * b1;
+        let b3 = b2.square() * b1;
+        let b6 = b3.square_n(3) * b3;
+        let b7 = b6.square() * b1;
+        let b14 = b7.square_n(7) * b7;
+        let b28 = b14.square_n(14) * b14;
+        let b29 = b28.square() * b1;
+        let b58 = b29.square_n(29) * b29;
+        let b116 = b58.square_n(58) * b58;
+        let b232 = b116.square_n(116) * b116;
+        b232.square()
+    }
+
+    /// Square root, which is the inverse of the Frobenius map.
+    pub fn sqrt(&self) -> Self {
+        self.square_n(GF233_BITS - 1)
+    }
+
+    /// Absolute trace. For the trinomial z^233 + z^74 + 1 it only depends
+    /// on the coefficients of z^0 and z^159.
+    pub fn trace(&self) -> bool {
+        self.bit(0) ^ self.bit(159)
+    }
+
+    /// Half-trace: for `a` with trace 0, returns `x` with `x^2 + x = a`.
+    pub fn halftrace(&self) -> Self {
+        let mut acc = *self;
+        let mut term = *self;
+        for _ in 0..(GF233_BITS - 1) / 2 {
+            term = term.square_n(2);
+            acc += term;
+        }
+        acc
+    }
+}
+
+impl Debug for Gf233 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", hex::encode(self.to_be_bytes()))
+    }
+}
+
+impl Add for Gf233 {
+    type Output = Self;
+
+    #[inline]
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for Gf233 {
+    /// Addition in characteristic 2 is a bitwise XOR.
+    #[inline]
+    #[allow(clippy::suspicious_op_assign_impl)]
+    fn add_assign(&mut self, other: Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a ^= b;
+        }
+    }
+}
+
+impl Mul for Gf233 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut wide = [0u64; 8];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                let (lo, hi) = clmul64(*a, *b);
+                wide[i + j] ^= lo;
+                wide[i + j + 1] ^= hi;
+            }
+        }
+        Self(reduce(wide))
+    }
+}
+
+impl MulAssign for Gf233 {
+    #[inline]
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+/// Carry-less 64x64 -> 128 multiplication with 4-bit windows.
+fn clmul64(a: u64, b: u64) -> (u64, u64) {
+    let a = a as u128;
+    let mut table = [0u128; 16];
+    for k in 1..16 {
+        table[k] = if k & 1 == 1 {
+            table[k - 1] ^ a
+        } else {
+            table[k >> 1] << 1
+        };
+    }
+
+    let mut res = 0u128;
+    for i in (0..16).rev() {
+        res <<= 4;
+        res ^= table[((b >> (4 * i)) & 0xF) as usize];
+    }
+    (res as u64, (res >> 64) as u64)
+}
+
+/// Interleaves zero bits into `x`, which squares it as a polynomial.
+fn spread(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    x = (x | (x << 1)) & 0x5555_5555_5555_5555;
+    x
+}
+
+/// Reduces a product of degree at most 464 modulo z^233 + z^74 + 1,
+/// using z^233 = z^74 + 1.
+fn reduce(mut c: [u64; 8]) -> [u64; 4] {
+    for i in (4..8).rev() {
+        let t = c[i];
+        // z^(64i + j - 233) = z^(64(i - 4) + j + 23)
+        c[i - 4] ^= t << 23;
+        c[i - 3] ^= t >> 41;
+        // z^(64i + j - 159) = z^(64(i - 3) + j + 33)
+        c[i - 3] ^= t << 33;
+        c[i - 2] ^= t >> 31;
+    }
+
+    let t = c[3] >> 41;
+    c[0] ^= t;
+    c[1] ^= t << 10;
+    c[3] &= TOP_LIMB_MASK;
+
+    [c[0], c[1], c[2], c[3]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, thread_rng};
+
+    fn rand_element<R: Rng>(rng: &mut R) -> Gf233 {
+        let mut bytes = [0u8; GF233_ENCODED_SIZE];
+        rng.fill(&mut bytes[..]);
+        bytes[29] &= 1;
+        Gf233::from_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_ring_axioms() {
+        let mut rng = thread_rng();
+        for _ in 0..20 {
+            let a = rand_element(&mut rng);
+            let b = rand_element(&mut rng);
+            let c = rand_element(&mut rng);
+
+            assert_eq!(a * b, b * a);
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!(a * Gf233::ONE, a);
+            assert_eq!(a.square(), a * a);
+            assert_eq!(a + a, Gf233::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_reduction() {
+        // z^232 * z = z^233 = z^74 + 1
+        let mut top = [false; GF233_BITS];
+        top[232] = true;
+        let mut z = [false; GF233_BITS];
+        z[1] = true;
+
+        let mut expected = [false; GF233_BITS];
+        expected[0] = true;
+        expected[74] = true;
+
+        assert_eq!(
+            Gf233::from_bits(&top) * Gf233::from_bits(&z),
+            Gf233::from_bits(&expected)
+        );
+    }
+
+    #[test]
+    fn test_inverse_and_sqrt() {
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            let a = rand_element(&mut rng);
+            assert_eq!(a * a.inverse(), Gf233::ONE);
+            assert_eq!(a.sqrt().square(), a);
+        }
+        assert_eq!(Gf233::ZERO.inverse(), Gf233::ZERO);
+    }
+
+    #[test]
+    fn test_trace_and_halftrace() {
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            let a = rand_element(&mut rng);
+
+            let mut trace = a;
+            let mut term = a;
+            for _ in 1..GF233_BITS {
+                term = term.square();
+                trace += term;
+            }
+            assert_eq!(trace, if a.trace() { Gf233::ONE } else { Gf233::ZERO });
+
+            if !a.trace() {
+                let h = a.halftrace();
+                assert_eq!(h.square() + h, a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = thread_rng();
+        let a = rand_element(&mut rng);
+
+        assert_eq!(Gf233::from_bytes(&a.to_bytes()), Some(a));
+        assert_eq!(Gf233::from_be_bytes(&a.to_be_bytes()), Some(a));
+        assert_eq!(Gf233::from_bits(&a.to_bits()), a);
+
+        let mut non_canonical = a.to_bytes();
+        non_canonical[29] |= 2;
+        assert_eq!(Gf233::from_bytes(&non_canonical), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Hashing to xsk233 scalars and group elements.
+//!
+//! Both hashes expand their input with `expand_message_xmd` over SHA-256
+//! from RFC 9380 and take a domain separation tag, which should be unique
+//! to the protocol and the purpose of the hash.
+//!
+//! There is no map from field elements to xsk233 points in RFC 9380, so
+//! [`hash_to_curve`] uses try-and-increment over the 30-byte encodings: a
+//! quarter of all strings with the 7 top bits clear decode to a group
+//! element, and the first candidate that does is returned. Its constant
+//! time variant always decodes [`HASH_TO_CURVE_CANDIDATES`] candidates;
+//! [`hash_to_curve_vartime`] stops at the first success and gives the same
+//! result, for public inputs only.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::xsk233::Fr;
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha256};
+use std::os::raw::c_void;
+use xs233_sys::{xsk233_decode, xsk233_is_neutral, xsk233_neutral, xsk233_select};
+
+/// Number of candidate encodings tried by [`hash_to_curve`]. All of them
+/// fail with probability `(3/4)^256 < 2^-106`.
+pub const HASH_TO_CURVE_CANDIDATES: usize = 256;
+
+/// Bytes reduced into a scalar: 232 bits of group order plus 128 bits of
+/// security, as `L` in RFC 9380.
+const SCALAR_EXPAND_SIZE: usize = 45;
+
+const B_IN_BYTES: usize = 32;
+const S_IN_BYTES: usize = 64;
+
+/// `expand_message_xmd` with SHA-256 (RFC 9380, section 5.3.1). Tags
+/// longer than 255 bytes are hashed first, as in section 5.3.3.
+///
+/// # Panics
+///
+/// If `len` is above 8160 bytes, the maximum for SHA-256.
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    let ell = len.div_ceil(B_IN_BYTES);
+    assert!(ell <= 255, "requested output is too long");
+
+    let hashed_dst;
+    let dst = if dst.len() > 255 {
+        hashed_dst = Sha256::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(dst)
+            .finalize();
+        hashed_dst.as_slice()
+    } else {
+        dst
+    };
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+    let b0 = Sha256::new()
+        .chain_update([0u8; S_IN_BYTES])
+        .chain_update(msg)
+        .chain_update((len as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut bi = Sha256::new()
+        .chain_update(b0)
+        .chain_update([1u8])
+        .chain_update(&dst_prime)
+        .finalize();
+
+    let mut out = Vec::with_capacity(ell * B_IN_BYTES);
+    out.extend_from_slice(&bi);
+    for i in 2..=ell {
+        let mut xored = b0;
+        xored.iter_mut().zip(&bi).for_each(|(x, b)| *x ^= b);
+        bi = Sha256::new()
+            .chain_update(xored)
+            .chain_update([i as u8])
+            .chain_update(&dst_prime)
+            .finalize();
+        out.extend_from_slice(&bi);
+    }
+    out.truncate(len);
+    out
+}
+
+/// Hashes `msg` to a scalar, with a bias below 2^-128.
+pub fn hash_to_scalar(dst: &[u8], msg: &[u8]) -> Fr {
+    Fr::from_be_bytes_mod_order(&expand_message_xmd(msg, dst, SCALAR_EXPAND_SIZE))
+}
+
+/// Candidate encodings, with the unused top bits cleared.
+fn candidates(dst: &[u8], msg: &[u8]) -> Vec<[u8; COMPRESSED_POINT_SIZE]> {
+    let bytes = expand_message_xmd(msg, dst, HASH_TO_CURVE_CANDIDATES * COMPRESSED_POINT_SIZE);
+    bytes
+        .chunks_exact(COMPRESSED_POINT_SIZE)
+        .map(|chunk| {
+            let mut candidate: [u8; COMPRESSED_POINT_SIZE] = chunk.try_into().unwrap();
+            candidate[COMPRESSED_POINT_SIZE - 1] &= 0x01;
+            candidate
+        })
+        .collect()
+}
+
+/// Hashes `msg` to a group element whose discrete logarithm is unknown,
+/// in time independent of `msg`.
+///
+/// If none of the candidates decodes, which happens with probability below
+/// 2^-106, the neutral element is returned.
+pub fn hash_to_curve(dst: &[u8], msg: &[u8]) -> Xsk233Affine {
+    let mut result = unsafe { xsk233_neutral };
+    let mut found = 0u32;
+    for candidate in candidates(dst, msg) {
+        record_op!(Decode);
+        unsafe {
+            let mut point = xsk233_neutral;
+            let ok = xsk233_decode(&mut point, candidate.as_ptr() as *const c_void);
+            let take = ok & !xsk233_is_neutral(&point) & !found;
+            let previous = result;
+            xsk233_select(&mut result, &previous, &point, take);
+            found |= take;
+        }
+    }
+    Xsk233Affine::new_unchecked(result)
+}
+
+/// Same as [`hash_to_curve`], but returns as soon as a candidate decodes,
+/// so the running time depends on `msg`.
+pub fn hash_to_curve_vartime(dst: &[u8], msg: &[u8]) -> Xsk233Affine {
+    candidates(dst, msg)
+        .iter()
+        .filter_map(Xsk233Affine::from_bytes)
+        .find(|p| !p.is_zero())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // RFC 9380, appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        for (msg, len, expected) in [
+            (
+                &b""[..],
+                0x20,
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc",
+                0x20,
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                b"",
+                0x80,
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe\
+                 e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18\
+                 eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc\
+                 c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced",
+            ),
+        ] {
+            assert_eq!(hex::encode(expand_message_xmd(msg, dst, len)), expected);
+        }
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        let p = hash_to_curve(b"test", b"message");
+        assert!(!p.is_zero());
+        assert_eq!(p, hash_to_curve(b"test", b"message"));
+        assert_ne!(p, hash_to_curve(b"test", b"other"));
+        assert_ne!(p, hash_to_curve(b"other", b"message"));
+        for i in 0..32u8 {
+            assert_eq!(
+                hash_to_curve(b"test", &[i]),
+                hash_to_curve_vartime(b"test", &[i])
+            );
+        }
+    }
+}
@@ -0,0 +1,626 @@
+//! Hierarchical deterministic keys over xsk233, after BIP32.
+//!
+//! An [`ExtendedSecretKey`] is a [`SecretKey`] with a 32-byte chain code,
+//! from which child keys are derived one index at a time:
+//!
+//! ```text
+//! I = HMAC-SHA512(chain code, data || index)
+//! child key = t + k,  child chain code = I[32..]
+//! ```
+//!
+//! where the tweak `t` is read from `I[..32]`, and `data` is `0x00 || k`
+//! for hardened indices, or the compressed public key `K` otherwise. The
+//! public key of a non-hardened child is therefore `K + t*G`, which an
+//! [`ExtendedPublicKey`] derives without the secret key.
+//!
+//! The differences with BIP32 come from the group: the tweak is the first
+//! 232 bits of `I[..32]` with the top one cleared, which is below the
+//! 232-bit group order and at negligible distance from uniform, keys are
+//! identified by the first 4 bytes of the SHA-256 of their compressed
+//! encoding, and the xprv/xpub-style encodings carry 30-byte keys under
+//! their own version bytes. Derived keys are thus not compatible with
+//! secp256k1 wallets, but follow the same paths, e.g. `m/44'/0'/1`.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::group::Xsk233Projective;
+use crate::keys::{PRIVATE_KEY_SIZE, SecretKey};
+use crate::xsk233::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{One, PrimeField};
+use ark_std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+use std::error::Error;
+use std::str::FromStr;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Version bytes of encoded extended secret keys.
+pub const XPRV_VERSION: [u8; 4] = [0x04, 0x3a, 0x5d, 0x18];
+
+/// Version bytes of encoded extended public keys.
+pub const XPUB_VERSION: [u8; 4] = [0x04, 0x3a, 0x61, 0x4e];
+
+/// Size of an extended key before the Base58Check encoding:
+/// `version || depth || parent fingerprint || child number || chain code ||
+/// key`.
+pub const EXTENDED_KEY_SIZE: usize = 4 + 1 + 4 + 4 + 32 + COMPRESSED_POINT_SIZE;
+
+const MASTER_KEY: &[u8] = b"xsk233 seed";
+
+const HARDENED_BIT: u32 = 1 << 31;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// Metadata, chain code and key bytes of an encoded extended key.
+type DecodedKey = (Metadata, [u8; 32], Zeroizing<[u8; COMPRESSED_POINT_SIZE]>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HdError {
+    /// The seed is not between 16 and 64 bytes long.
+    InvalidSeed,
+    /// The derived key is zero or the neutral element; the next index
+    /// should be used instead.
+    InvalidChild,
+    /// Hardened children cannot be derived from a public key.
+    HardenedFromPublic,
+    /// The key is already at depth 255.
+    MaxDepth,
+    /// The derivation path or child number is malformed.
+    InvalidPath,
+    /// The encoded extended key is malformed.
+    InvalidEncoding,
+}
+
+impl Display for HdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HdError::InvalidSeed => write!(f, "invalid seed length"),
+            HdError::InvalidChild => write!(f, "invalid child key"),
+            HdError::HardenedFromPublic => write!(f, "hardened derivation from a public key"),
+            HdError::MaxDepth => write!(f, "maximum depth reached"),
+            HdError::InvalidPath => write!(f, "invalid derivation path"),
+            HdError::InvalidEncoding => write!(f, "invalid extended key encoding"),
+        }
+    }
+}
+
+impl Error for HdError {}
+
+/// Index of a child key, written `i` or `i'` (also `ih`) in paths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChildNumber {
+    Normal(u32),
+    Hardened(u32),
+}
+
+impl ChildNumber {
+    /// Returns `None` for indices of `2^31` or more.
+    pub fn normal(index: u32) -> Option<Self> {
+        (index < HARDENED_BIT).then_some(Self::Normal(index))
+    }
+
+    /// Returns `None` for indices of `2^31` or more.
+    pub fn hardened(index: u32) -> Option<Self> {
+        (index < HARDENED_BIT).then_some(Self::Hardened(index))
+    }
+
+    pub fn is_hardened(&self) -> bool {
+        matches!(self, Self::Hardened(_))
+    }
+
+    /// The serialized index, with the top bit set for hardened children.
+    pub fn to_u32(self) -> u32 {
+        match self {
+            Self::Normal(i) => i,
+            Self::Hardened(i) => i | HARDENED_BIT,
+        }
+    }
+}
+
+impl From<u32> for ChildNumber {
+    fn from(index: u32) -> Self {
+        if index & HARDENED_BIT == 0 {
+            Self::Normal(index)
+        } else {
+            Self::Hardened(index & !HARDENED_BIT)
+        }
+    }
+}
+
+impl Display for ChildNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Normal(i) => write!(f, "{i}"),
+            Self::Hardened(i) => write!(f, "{i}'"),
+        }
+    }
+}
+
+impl FromStr for ChildNumber {
+    type Err = HdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (digits, hardened) = match s.strip_suffix(['\'', 'h', 'H']) {
+            Some(digits) => (digits, true),
+            None => (s, false),
+        };
+        // reject signs and leading zeros, which `parse` accepts
+        if digits.is_empty()
+            || !digits.bytes().all(|b| b.is_ascii_digit())
+            || (digits.len() > 1 && digits.starts_with('0'))
+        {
+            return Err(HdError::InvalidPath);
+        }
+        let index = digits.parse().map_err(|_| HdError::InvalidPath)?;
+        let child = if hardened {
+            Self::hardened(index)
+        } else {
+            Self::normal(index)
+        };
+        child.ok_or(HdError::InvalidPath)
+    }
+}
+
+/// Sequence of child numbers from a master key, e.g. `m/44'/0'/1`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    pub fn children(&self) -> &[ChildNumber] {
+        &self.0
+    }
+
+    /// Returns the path extended by `child`.
+    pub fn child(&self, child: ChildNumber) -> Self {
+        let mut path = self.clone();
+        path.0.push(child);
+        path
+    }
+}
+
+impl From<Vec<ChildNumber>> for DerivationPath {
+    fn from(children: Vec<ChildNumber>) -> Self {
+        Self(children)
+    }
+}
+
+impl Display for DerivationPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "m")?;
+        for child in &self.0 {
+            write!(f, "/{child}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = HdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(HdError::InvalidPath);
+        }
+        let children = parts.map(str::parse).collect::<Result<Vec<_>, _>>()?;
+        if children.len() > u8::MAX as usize {
+            return Err(HdError::InvalidPath);
+        }
+        Ok(Self(children))
+    }
+}
+
+/// Position of an extended key in its tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Metadata {
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: ChildNumber,
+}
+
+impl Metadata {
+    const MASTER: Self = Self {
+        depth: 0,
+        parent_fingerprint: [0; 4],
+        child_number: ChildNumber::Normal(0),
+    };
+
+    fn child(&self, parent: &Xsk233Affine, child_number: ChildNumber) -> Result<Self, HdError> {
+        Ok(Self {
+            depth: self.depth.checked_add(1).ok_or(HdError::MaxDepth)?,
+            parent_fingerprint: fingerprint(parent),
+            child_number,
+        })
+    }
+}
+
+/// Secret key with a chain code, which derives both hardened and
+/// non-hardened children.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExtendedSecretKey {
+    secret: SecretKey,
+    chain_code: [u8; 32],
+    metadata: Metadata,
+}
+
+impl ExtendedSecretKey {
+    /// Derives the master key of a seed of 16 to 64 bytes.
+    pub fn new_master(seed: &[u8]) -> Result<Self, HdError> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(HdError::InvalidSeed);
+        }
+        let (tweak, chain_code) = hmac_split(MASTER_KEY, &[seed]);
+        let secret = SecretKey::new(*tweak).ok_or(HdError::InvalidChild)?;
+        Ok(Self {
+            secret,
+            chain_code: *chain_code,
+            metadata: Metadata::MASTER,
+        })
+    }
+
+    pub fn derive_child(&self, child_number: ChildNumber) -> Result<Self, HdError> {
+        let public = self.secret.public_key();
+        let metadata = self.metadata.child(&public, child_number)?;
+        let index = child_number.to_u32().to_be_bytes();
+        let (tweak, chain_code) = if child_number.is_hardened() {
+            let secret = self.secret.to_be_bytes();
+            hmac_split(&self.chain_code, &[&[0], secret.as_slice(), &index])
+        } else {
+            hmac_split(&self.chain_code, &[&public.to_bytes(), &index])
+        };
+        let secret = SecretKey::new(*tweak + self.secret.scalar()).ok_or(HdError::InvalidChild)?;
+        Ok(Self {
+            secret,
+            chain_code: *chain_code,
+            metadata,
+        })
+    }
+
+    /// Derives the descendant at `path`, taken relative to this key.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, HdError> {
+        path.children()
+            .iter()
+            .try_fold(self.clone(), |key, child| key.derive_child(*child))
+    }
+
+    pub fn public_key(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            public: self.secret.public_key(),
+            chain_code: self.chain_code,
+            metadata: self.metadata,
+        }
+    }
+
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.secret
+    }
+
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.metadata.depth
+    }
+
+    pub fn parent_fingerprint(&self) -> &[u8; 4] {
+        &self.metadata.parent_fingerprint
+    }
+
+    pub fn child_number(&self) -> ChildNumber {
+        self.metadata.child_number
+    }
+
+    /// Base58Check encoding under [`XPRV_VERSION`], with the key encoded
+    /// as `0x00 || k`.
+    pub fn to_base58(&self) -> Zeroizing<String> {
+        let mut key = Zeroizing::new([0u8; COMPRESSED_POINT_SIZE]);
+        key[1..].copy_from_slice(self.secret.to_be_bytes().as_slice());
+        let bytes = encode(&XPRV_VERSION, &self.metadata, &self.chain_code, &key);
+        Zeroizing::new(bs58::encode(bytes.as_slice()).with_check().into_string())
+    }
+
+    pub fn from_base58(s: &str) -> Result<Self, HdError> {
+        let (metadata, chain_code, key) = decode(&XPRV_VERSION, s)?;
+        if key[0] != 0 {
+            return Err(HdError::InvalidEncoding);
+        }
+        let secret = SecretKey::from_be_bytes(&key[1..]).ok_or(HdError::InvalidEncoding)?;
+        Ok(Self {
+            secret,
+            chain_code,
+            metadata,
+        })
+    }
+}
+
+impl Debug for ExtendedSecretKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ExtendedSecretKey")
+            .field("depth", &self.metadata.depth)
+            .field("child_number", &self.metadata.child_number)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for ExtendedSecretKey {
+    fn drop(&mut self) {
+        self.chain_code.zeroize();
+    }
+}
+
+/// Public key with a chain code, which derives the public keys of the
+/// non-hardened children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+    public: Xsk233Affine,
+    chain_code: [u8; 32],
+    metadata: Metadata,
+}
+
+impl ExtendedPublicKey {
+    /// Derives the public key `K + t*G` of a non-hardened child.
+    pub fn derive_child(&self, child_number: ChildNumber) -> Result<Self, HdError> {
+        if child_number.is_hardened() {
+            return Err(HdError::HardenedFromPublic);
+        }
+        let metadata = self.metadata.child(&self.public, child_number)?;
+        let index = child_number.to_u32().to_be_bytes();
+        let (tweak, chain_code) = hmac_split(&self.chain_code, &[&self.public.to_bytes(), &index]);
+        let public =
+            Xsk233Projective::mul_add_generator(&tweak, &Fr::one(), &self.public).into_affine();
+        if public.is_zero() {
+            return Err(HdError::InvalidChild);
+        }
+        Ok(Self {
+            public,
+            chain_code: *chain_code,
+            metadata,
+        })
+    }
+
+    /// Derives the descendant at `path`, which must not contain hardened
+    /// children.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, HdError> {
+        path.children()
+            .iter()
+            .try_fold(*self, |key, child| key.derive_child(*child))
+    }
+
+    pub fn public_key(&self) -> &Xsk233Affine {
+        &self.public
+    }
+
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    /// First 4 bytes of the SHA-256 of the compressed public key, which
+    /// children store as their parent fingerprint.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        fingerprint(&self.public)
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.metadata.depth
+    }
+
+    pub fn parent_fingerprint(&self) -> &[u8; 4] {
+        &self.metadata.parent_fingerprint
+    }
+
+    pub fn child_number(&self) -> ChildNumber {
+        self.metadata.child_number
+    }
+}
+
+/// Base58Check encoding under [`XPUB_VERSION`].
+impl Display for ExtendedPublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let bytes = encode(
+            &XPUB_VERSION,
+            &self.metadata,
+            &self.chain_code,
+            &self.public.to_bytes(),
+        );
+        f.write_str(&bs58::encode(bytes.as_slice()).with_check().into_string())
+    }
+}
+
+impl FromStr for ExtendedPublicKey {
+    type Err = HdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (metadata, chain_code, key) = decode(&XPUB_VERSION, s)?;
+        let public = Xsk233Affine::from_bytes(&key)
+            .filter(|p| !p.is_zero())
+            .ok_or(HdError::InvalidEncoding)?;
+        Ok(Self {
+            public,
+            chain_code,
+            metadata,
+        })
+    }
+}
+
+fn fingerprint(public: &Xsk233Affine) -> [u8; 4] {
+    Sha256::digest(public.to_bytes())[..4].try_into().unwrap()
+}
+
+/// Splits `HMAC-SHA512(key, data)` into the tweak and the chain code.
+fn hmac_split(key: &[u8], data: &[&[u8]]) -> (Zeroizing<Fr>, Zeroizing<[u8; 32]>) {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts any key size");
+    for part in data {
+        mac.update(part);
+    }
+    let mut i = Zeroizing::new(<[u8; 64]>::from(mac.finalize().into_bytes()));
+
+    // the first 232 bits without the top one are below the group order
+    let mut tweak = Zeroizing::new([0u8; PRIVATE_KEY_SIZE]);
+    tweak.copy_from_slice(&i[..PRIVATE_KEY_SIZE]);
+    tweak[0] &= 0x7f;
+    let mut chain_code = Zeroizing::new([0u8; 32]);
+    chain_code.copy_from_slice(&i[32..]);
+    i.zeroize();
+    (
+        Zeroizing::new(Fr::from_be_bytes_mod_order(tweak.as_slice())),
+        chain_code,
+    )
+}
+
+fn encode(
+    version: &[u8; 4],
+    metadata: &Metadata,
+    chain_code: &[u8; 32],
+    key: &[u8; COMPRESSED_POINT_SIZE],
+) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(EXTENDED_KEY_SIZE));
+    bytes.extend_from_slice(version);
+    bytes.push(metadata.depth);
+    bytes.extend_from_slice(&metadata.parent_fingerprint);
+    bytes.extend_from_slice(&metadata.child_number.to_u32().to_be_bytes());
+    bytes.extend_from_slice(chain_code);
+    bytes.extend_from_slice(key);
+    bytes
+}
+
+fn decode(version: &[u8; 4], s: &str) -> Result<DecodedKey, HdError> {
+    let bytes = Zeroizing::new(
+        bs58::decode(s)
+            .with_check(None)
+            .into_vec()
+            .map_err(|_| HdError::InvalidEncoding)?,
+    );
+    if bytes.len() != EXTENDED_KEY_SIZE || bytes[..4] != *version {
+        return Err(HdError::InvalidEncoding);
+    }
+    let metadata = Metadata {
+        depth: bytes[4],
+        parent_fingerprint: bytes[5..9].try_into().unwrap(),
+        child_number: u32::from_be_bytes(bytes[9..13].try_into().unwrap()).into(),
+    };
+    // master keys have neither a parent nor an index
+    if metadata.depth == 0 && metadata != Metadata::MASTER {
+        return Err(HdError::InvalidEncoding);
+    }
+    let chain_code = bytes[13..45].try_into().unwrap();
+    let key = Zeroizing::new(bytes[45..].try_into().unwrap());
+    Ok((metadata, chain_code, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivation() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedSecretKey::new_master(&seed).unwrap();
+        assert_eq!(
+            ExtendedSecretKey::new_master(&seed[..15]),
+            Err(HdError::InvalidSeed)
+        );
+
+        // public derivation matches private derivation below the hardened
+        // account key
+        let account = master.derive_path(&"m/44'/0'".parse().unwrap()).unwrap();
+        let path: DerivationPath = "m/1/7".parse().unwrap();
+        let child = account.derive_path(&path).unwrap();
+        assert_eq!(
+            account.public_key().derive_path(&path),
+            Ok(child.public_key())
+        );
+        assert_eq!(
+            master.derive_path(&"m/44'/0'/1/7".parse().unwrap()),
+            Ok(child.clone())
+        );
+        assert_eq!(child.depth(), 4);
+        assert_eq!(child.child_number(), ChildNumber::Normal(7));
+        assert_eq!(
+            *child.parent_fingerprint(),
+            account
+                .derive_child(ChildNumber::Normal(1))
+                .unwrap()
+                .public_key()
+                .fingerprint()
+        );
+
+        // hardened and normal children differ, and need the secret key
+        let hardened = account.derive_child(ChildNumber::Hardened(1)).unwrap();
+        assert_ne!(
+            hardened.public_key().public_key(),
+            child.public_key().public_key()
+        );
+        assert_eq!(
+            account.public_key().derive_child(ChildNumber::Hardened(1)),
+            Err(HdError::HardenedFromPublic)
+        );
+
+        let xprv = child.to_base58();
+        assert_eq!(ExtendedSecretKey::from_base58(&xprv), Ok(child.clone()));
+        let xpub = child.public_key().to_string();
+        assert_eq!(xpub.parse(), Ok(child.public_key()));
+        assert_eq!(
+            ExtendedSecretKey::from_base58(&xpub),
+            Err(HdError::InvalidEncoding)
+        );
+        assert_eq!(
+            xprv.parse::<ExtendedPublicKey>(),
+            Err(HdError::InvalidEncoding)
+        );
+        let mut corrupted = xpub.into_bytes();
+        corrupted[20] = if corrupted[20] == b'2' { b'3' } else { b'2' };
+        assert_eq!(
+            String::from_utf8(corrupted)
+                .unwrap()
+                .parse::<ExtendedPublicKey>(),
+            Err(HdError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_path_parsing() {
+        let path: DerivationPath = "m/44'/0h/1".parse().unwrap();
+        assert_eq!(
+            path.children(),
+            [
+                ChildNumber::Hardened(44),
+                ChildNumber::Hardened(0),
+                ChildNumber::Normal(1)
+            ]
+        );
+        assert_eq!(path.to_string(), "m/44'/0'/1");
+        assert_eq!("m".parse(), Ok(DerivationPath::default()));
+        assert_eq!(
+            DerivationPath::default().child(ChildNumber::Normal(3)),
+            "m/3".parse().unwrap()
+        );
+        assert_eq!(
+            ChildNumber::from(HARDENED_BIT | 5),
+            ChildNumber::Hardened(5)
+        );
+        assert_eq!(ChildNumber::Hardened(5).to_u32(), HARDENED_BIT | 5);
+
+        for invalid in [
+            "",
+            "44'/0'",
+            "m/",
+            "m//1",
+            "m/-1",
+            "m/+1",
+            "m/01",
+            "m/1''",
+            "m/x",
+            "m/2147483648",
+            "m/2147483648'",
+            "M/1",
+        ] {
+            assert_eq!(
+                invalid.parse::<DerivationPath>(),
+                Err(HdError::InvalidPath),
+                "{invalid}"
+            );
+        }
+        assert!("m/2147483647'".parse::<DerivationPath>().is_ok());
+    }
+}
@@ -0,0 +1,294 @@
+//! DHKEM(xsk233, HKDF-SHA256) for HPKE (RFC 9180).
+//!
+//! This is the KEM of RFC 9180, section 4.1, instantiated with xsk233 in
+//! the way the NIST curves are:
+//!
+//! * public keys and encapsulated keys are the 30-byte compressed
+//!   encodings (`Npk = Nenc = 30`), and the neutral element is rejected;
+//! * private keys are 29-byte big-endian scalars (`Nsk = 29`), derived by
+//!   rejection sampling in `DeriveKeyPair` with a bit mask of `0xff`;
+//! * the Diffie-Hellman output is the encoding of the shared point.
+//!
+//! No KEM identifier is registered for xsk233; [`KEM_ID`] is taken from
+//! the unassigned range and is part of every derivation, so keys and
+//! secrets will change if an identifier is ever assigned.
+//!
+//! The functions follow the KEM interface of the RFC (`Encap`, `Decap`,
+//! `AuthEncap`, `AuthDecap`, ...). The key schedule and AEAD are not
+//! provided. This module does not implement the `Kem` trait of the `hpke`
+//! crate, which is not a dependency of this crate, so it cannot be passed
+//! to `hpke::setup_sender` or `hpke::setup_receiver` yet.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::keys::{PRIVATE_KEY_SIZE, SecretKey};
+use ark_ec::AffineRepr;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::error::Error;
+use zeroize::Zeroizing;
+
+/// KEM identifier, from the unassigned range of the HPKE KEM registry.
+pub const KEM_ID: u16 = 0xff33;
+
+/// Size of the KEM shared secret, the output size of SHA-256.
+pub const N_SECRET: usize = 32;
+/// Size of an encapsulated key.
+pub const N_ENC: usize = COMPRESSED_POINT_SIZE;
+/// Size of a serialized public key.
+pub const N_PK: usize = COMPRESSED_POINT_SIZE;
+/// Size of a serialized private key.
+pub const N_SK: usize = PRIVATE_KEY_SIZE;
+
+const SUITE_ID: [u8; 5] = {
+    let id = KEM_ID.to_be_bytes();
+    [b'K', b'E', b'M', id[0], id[1]]
+};
+
+/// KEM shared secret.
+pub type SharedSecret = Zeroizing<[u8; N_SECRET]>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HpkeError {
+    /// `DeriveKeyPair` found no valid scalar in 256 candidates.
+    DeriveKeyPair,
+    /// A public or encapsulated key is not the encoding of a non-neutral
+    /// point.
+    InvalidPublicKey,
+    /// A private key is not a non-zero scalar below the group order.
+    InvalidPrivateKey,
+}
+
+impl Display for HpkeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HpkeError::DeriveKeyPair => write!(f, "key pair derivation failed"),
+            HpkeError::InvalidPublicKey => write!(f, "invalid public key"),
+            HpkeError::InvalidPrivateKey => write!(f, "invalid private key"),
+        }
+    }
+}
+
+impl Error for HpkeError {}
+
+fn labeled_extract(salt: &[u8], label: &[u8], ikm: &[u8]) -> Hkdf<Sha256> {
+    let mut labeled_ikm = Zeroizing::new(Vec::new());
+    labeled_ikm.extend_from_slice(b"HPKE-v1");
+    labeled_ikm.extend_from_slice(&SUITE_ID);
+    labeled_ikm.extend_from_slice(label);
+    labeled_ikm.extend_from_slice(ikm);
+    Hkdf::new(Some(salt), &labeled_ikm)
+}
+
+fn labeled_expand(prk: &Hkdf<Sha256>, label: &[u8], info: &[u8], out: &mut [u8]) {
+    let len = (out.len() as u16).to_be_bytes();
+    prk.expand_multi_info(&[&len, b"HPKE-v1", &SUITE_ID, label, info], out)
+        .expect("output length is valid");
+}
+
+fn extract_and_expand(dh: &[u8], kem_context: &[u8]) -> SharedSecret {
+    let prk = labeled_extract(b"", b"eae_prk", dh);
+    let mut shared_secret = Zeroizing::new([0u8; N_SECRET]);
+    labeled_expand(
+        &prk,
+        b"shared_secret",
+        kem_context,
+        shared_secret.as_mut_slice(),
+    );
+    shared_secret
+}
+
+/// Deterministically derives a key pair from at least [`N_SK`] bytes of
+/// input keying material.
+pub fn derive_key_pair(ikm: &[u8]) -> Result<(SecretKey, Xsk233Affine), HpkeError> {
+    let prk = labeled_extract(b"", b"dkp_prk", ikm);
+    let mut bytes = Zeroizing::new([0u8; N_SK]);
+    for counter in 0..=u8::MAX {
+        labeled_expand(&prk, b"candidate", &[counter], bytes.as_mut_slice());
+        // the group order has 232 bits, so no bits are masked
+        if let Some(sk) = SecretKey::from_be_bytes(bytes.as_slice()) {
+            let pk = sk.public_key();
+            return Ok((sk, pk));
+        }
+    }
+    Err(HpkeError::DeriveKeyPair)
+}
+
+pub fn generate_key_pair<R: Rng + ?Sized>(rng: &mut R) -> (SecretKey, Xsk233Affine) {
+    let mut ikm = Zeroizing::new([0u8; N_SK]);
+    rng.fill_bytes(ikm.as_mut_slice());
+    derive_key_pair(ikm.as_slice()).expect("derivation fails with negligible probability")
+}
+
+pub fn serialize_public_key(pk: &Xsk233Affine) -> [u8; N_PK] {
+    pk.to_bytes()
+}
+
+/// Decodes a public key, rejecting the neutral element.
+pub fn deserialize_public_key(bytes: &[u8]) -> Result<Xsk233Affine, HpkeError> {
+    bytes
+        .try_into()
+        .ok()
+        .and_then(Xsk233Affine::from_bytes)
+        .filter(|pk| !pk.is_zero())
+        .ok_or(HpkeError::InvalidPublicKey)
+}
+
+pub fn serialize_private_key(sk: &SecretKey) -> Zeroizing<[u8; N_SK]> {
+    sk.to_be_bytes()
+}
+
+pub fn deserialize_private_key(bytes: &[u8]) -> Result<SecretKey, HpkeError> {
+    SecretKey::from_be_bytes(bytes).ok_or(HpkeError::InvalidPrivateKey)
+}
+
+fn dh(sk: &SecretKey, pk: &Xsk233Affine) -> [u8; COMPRESSED_POINT_SIZE] {
+    // non-zero scalars and non-neutral points in a prime-order group never
+    // give the neutral element
+    (*pk * sk.scalar()).to_bytes()
+}
+
+/// Returns a fresh shared secret and its encapsulation for `pk_r`.
+pub fn encap<R: Rng + ?Sized>(rng: &mut R, pk_r: &Xsk233Affine) -> (SharedSecret, [u8; N_ENC]) {
+    encap_with(&generate_key_pair(rng).0, pk_r, None)
+}
+
+/// Recovers the shared secret encapsulated in `enc` for `sk_r`.
+pub fn decap(enc: &[u8], sk_r: &SecretKey) -> Result<SharedSecret, HpkeError> {
+    decap_with(enc, sk_r, None)
+}
+
+/// Like [`encap`], also authenticating the sender key pair `sk_s`.
+pub fn auth_encap<R: Rng + ?Sized>(
+    rng: &mut R,
+    pk_r: &Xsk233Affine,
+    sk_s: &SecretKey,
+) -> (SharedSecret, [u8; N_ENC]) {
+    encap_with(&generate_key_pair(rng).0, pk_r, Some(sk_s))
+}
+
+/// Like [`decap`], failing to recover the shared secret unless the sender
+/// used the private key of `pk_s`.
+pub fn auth_decap(
+    enc: &[u8],
+    sk_r: &SecretKey,
+    pk_s: &Xsk233Affine,
+) -> Result<SharedSecret, HpkeError> {
+    decap_with(enc, sk_r, Some(pk_s))
+}
+
+/// `Encap` or `AuthEncap` with the ephemeral key `sk_e`.
+fn encap_with(
+    sk_e: &SecretKey,
+    pk_r: &Xsk233Affine,
+    sk_s: Option<&SecretKey>,
+) -> (SharedSecret, [u8; N_ENC]) {
+    let enc = serialize_public_key(&sk_e.public_key());
+    let mut dh_out = Zeroizing::new(dh(sk_e, pk_r).to_vec());
+    let mut kem_context = [enc, serialize_public_key(pk_r)].concat();
+    if let Some(sk_s) = sk_s {
+        dh_out.extend_from_slice(&dh(sk_s, pk_r));
+        kem_context.extend_from_slice(&serialize_public_key(&sk_s.public_key()));
+    }
+    (extract_and_expand(&dh_out, &kem_context), enc)
+}
+
+fn decap_with(
+    enc: &[u8],
+    sk_r: &SecretKey,
+    pk_s: Option<&Xsk233Affine>,
+) -> Result<SharedSecret, HpkeError> {
+    let pk_e = deserialize_public_key(enc)?;
+    let mut dh_out = Zeroizing::new(dh(sk_r, &pk_e).to_vec());
+    let mut kem_context = [enc, &serialize_public_key(&sk_r.public_key())].concat();
+    if let Some(pk_s) = pk_s {
+        dh_out.extend_from_slice(&dh(sk_r, pk_s));
+        kem_context.extend_from_slice(&serialize_public_key(pk_s));
+    }
+    Ok(extract_and_expand(&dh_out, &kem_context))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    /// Self-generated vectors: key pairs derived from `ikm` filled with 1
+    /// (ephemeral), 2 (recipient) and 3 (sender), in the layout of the RFC
+    /// 9180 test vectors.
+    const IKM_E: [u8; 32] = [1; 32];
+    const IKM_R: [u8; 32] = [2; 32];
+    const IKM_S: [u8; 32] = [3; 32];
+    const SK_EM: &str = "28086247b1cc8a8dd9f9268b525583e04f51abd398bd70d5e73a236def";
+    const PK_EM: &str = "f54c4f6d078de1bff19dd268be585d675bab9997bcf8bf7c9bb08ccc1f00";
+    const SK_RM: &str = "299ecb172abbd55ed521e289558ac8666535de42f074509ce430d23ae2";
+    const PK_RM: &str = "303b8813d0278ca316f5c6c91f20d1cf0ea6b30db09b81103cdeaa740a01";
+    const SK_SM: &str = "3c917a5b55eba0f2853bc95a6028f2bc6ac25c58e70804b2e9498b8b24";
+    const PK_SM: &str = "a618e4cf99e3c4dd21fbf8e3f5e158d3a3ab9d8f6a53d089639fb01d3600";
+    const BASE_SHARED_SECRET: &str =
+        "b303bc3011fb0be427f21cdf792d46d602805ac94e910f7898d15e37d57bc820";
+    const AUTH_SHARED_SECRET: &str =
+        "a4b716ca845a072fde3cb16e4b444def36ec252d6b5819ed1d14ff2bd3121f36";
+
+    #[test]
+    fn test_vectors() {
+        let (sk_e, pk_e) = derive_key_pair(&IKM_E).unwrap();
+        let (sk_r, pk_r) = derive_key_pair(&IKM_R).unwrap();
+        let (sk_s, pk_s) = derive_key_pair(&IKM_S).unwrap();
+        for (sk, pk, sk_hex, pk_hex) in [
+            (&sk_e, &pk_e, SK_EM, PK_EM),
+            (&sk_r, &pk_r, SK_RM, PK_RM),
+            (&sk_s, &pk_s, SK_SM, PK_SM),
+        ] {
+            assert_eq!(hex::encode(serialize_private_key(sk)), sk_hex);
+            assert_eq!(hex::encode(serialize_public_key(pk)), pk_hex);
+        }
+
+        let (shared_secret, enc) = encap_with(&sk_e, &pk_r, None);
+        assert_eq!(hex::encode(enc), PK_EM);
+        assert_eq!(hex::encode(&shared_secret), BASE_SHARED_SECRET);
+        assert_eq!(decap(&enc, &sk_r).unwrap(), shared_secret);
+
+        let (shared_secret, enc) = encap_with(&sk_e, &pk_r, Some(&sk_s));
+        assert_eq!(hex::encode(&shared_secret), AUTH_SHARED_SECRET);
+        assert_eq!(auth_decap(&enc, &sk_r, &pk_s).unwrap(), shared_secret);
+    }
+
+    #[test]
+    fn test_encap_decap() {
+        let mut rng = test_rng();
+        let (sk_r, pk_r) = generate_key_pair(&mut rng);
+        let (sk_s, pk_s) = generate_key_pair(&mut rng);
+
+        let (shared_secret, enc) = encap(&mut rng, &pk_r);
+        assert_eq!(decap(&enc, &sk_r).unwrap(), shared_secret);
+        assert_ne!(auth_decap(&enc, &sk_r, &pk_s).unwrap(), shared_secret);
+
+        let (shared_secret, enc) = auth_encap(&mut rng, &pk_r, &sk_s);
+        assert_eq!(auth_decap(&enc, &sk_r, &pk_s).unwrap(), shared_secret);
+        assert_ne!(auth_decap(&enc, &sk_r, &pk_r).unwrap(), shared_secret);
+        assert_ne!(decap(&enc, &sk_r).unwrap(), shared_secret);
+
+        // the neutral element and malformed encodings are rejected
+        let err = Err(HpkeError::InvalidPublicKey);
+        assert_eq!(decap(&[0; N_ENC], &sk_r), err);
+        assert_eq!(decap(&enc[1..], &sk_r), err);
+        assert!(deserialize_public_key(&[0xff; N_PK]).is_err());
+    }
+
+    #[test]
+    fn test_private_key_encoding() {
+        let (sk, _) = derive_key_pair(&IKM_R).unwrap();
+        let bytes = serialize_private_key(&sk);
+        assert_eq!(deserialize_private_key(bytes.as_slice()), Ok(sk));
+        assert_eq!(
+            deserialize_private_key(&[0; N_SK]),
+            Err(HpkeError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            deserialize_private_key(&[0xff; N_SK]),
+            Err(HpkeError::InvalidPrivateKey)
+        );
+    }
+}
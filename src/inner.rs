@@ -0,0 +1,184 @@
+use crate::affine::Xsk233Affine;
+use crate::gf233::Gf233;
+
+/// Point of xsk233 in the extended `(X, S, Z, T)` coordinates of the xs233
+/// paper, with `x = X/Z`, `s = S/Z^2` and `T = X*Z`.
+///
+/// xs233-sys keeps its coordinates opaque and does not expose the Frobenius
+/// endomorphism, so the tau-adic routines run on this Rust copy of the C
+/// formulas. Points enter and leave through the canonical 30-byte encoding.
+/// None of the operations are constant-time.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct InnerPoint {
+    x: Gf233,
+    s: Gf233,
+    z: Gf233,
+    t: Gf233,
+}
+
+impl InnerPoint {
+    pub(crate) const NEUTRAL: Self = Self {
+        x: Gf233::ZERO,
+        s: Gf233::ONE,
+        z: Gf233::ONE,
+        t: Gf233::ZERO,
+    };
+
+    pub(crate) fn from_affine(point: &Xsk233Affine) -> Self {
+        let w = Gf233::from_bytes(&point.to_bytes()).expect("xsk233_encode output is canonical");
+        if w.is_zero() {
+            return Self::NEUTRAL;
+        }
+
+        record_op!(Decode);
+        let x = decode_x(w).expect("xsk233_encode output decodes");
+        Self {
+            x,
+            s: x * w.square(),
+            z: Gf233::ONE,
+            t: x,
+        }
+    }
+
+    pub(crate) fn into_affine(self) -> Xsk233Affine {
+        self.try_into_affine()
+            .expect("group elements always decode")
+    }
+
+    /// Like [`Self::into_affine`], for coordinates that did not come out of
+    /// the group law and may not describe a point at all.
+    pub(crate) fn try_into_affine(self) -> Option<Xsk233Affine> {
+        // w = sqrt(s/x) = sqrt(S/T), and T = 0 only for the neutral element
+        record_op!(Encode);
+        let w = (self.s * self.t.inverse()).sqrt();
+        Xsk233Affine::from_bytes(&w.to_bytes())
+    }
+
+    /// Returns `[X, S, Z, T]`.
+    pub(crate) fn coordinates(&self) -> [Gf233; 4] {
+        [self.x, self.s, self.z, self.t]
+    }
+
+    pub(crate) fn from_coordinates([x, s, z, t]: [Gf233; 4]) -> Self {
+        Self { x, s, z, t }
+    }
+
+    /// Complete addition (7M + 2S).
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        record_op!(Add);
+        let x1x2 = self.x * other.x;
+        let s1s2 = self.s * other.s;
+        let z1z2 = self.z * other.z;
+        let d = (self.s + self.t) * (other.s + other.t);
+        let f = x1x2.square();
+        let g = z1z2.square();
+
+        let x = d + s1s2;
+        let s = g * s1s2 + f * d;
+        let z = f + g;
+        Self { x, s, z, t: x * z }
+    }
+
+    /// Doubling (3M + 4S).
+    pub(crate) fn double(&self) -> Self {
+        record_op!(Double);
+        let zz = self.z.square();
+        let x = self.t.square();
+        let z = self.s * (self.s + self.t);
+        let e = (self.s + self.t + zz).square();
+        let tmp = z + x;
+        let s = e * (tmp + e) + tmp.square();
+        Self { x, s, z, t: x * z }
+    }
+
+    pub(crate) fn neg(&self) -> Self {
+        record_op!(Neg);
+        Self {
+            s: self.s + self.t,
+            ..*self
+        }
+    }
+
+    /// The Frobenius endomorphism `tau`, which squares every coordinate.
+    pub(crate) fn frobenius(&self) -> Self {
+        record_op!(Frobenius);
+        Self {
+            x: self.x.square(),
+            s: self.s.square(),
+            z: self.z.square(),
+            t: self.t.square(),
+        }
+    }
+}
+
+/// Recovers the `x` coordinate of `P + N` from the encoding `w`, following
+/// the same steps as `xsk233_decode()`.
+pub(crate) fn decode_x(w: Gf233) -> Option<Gf233> {
+    let d = w.square() + w;
+    if d.is_zero() {
+        return None;
+    }
+
+    // x = d*f with f^2 + f = 1/d^2
+    let e = d.square().inverse();
+    if e.trace() {
+        return None;
+    }
+    let mut x = d * e.halftrace();
+    if x.trace() {
+        return None;
+    }
+
+    // choose between the two candidates so that the point can be halved
+    let lambda = x.halftrace();
+    if ((lambda + w) * x).trace() {
+        x += d;
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::Xsk233Projective;
+    use crate::xsk233::Fr;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::AdditiveGroup;
+    use ark_std::UniformRand;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_formulas_match_xs233() {
+        let mut rng = thread_rng();
+        let p = Xsk233Projective::rand(&mut rng).into_affine();
+        let q = Xsk233Projective::rand(&mut rng).into_affine();
+
+        let ip = InnerPoint::from_affine(&p);
+        let iq = InnerPoint::from_affine(&q);
+        let zero = InnerPoint::from_affine(&Xsk233Affine::zero());
+
+        assert_eq!(ip.into_affine(), p);
+        assert_eq!(ip.add(&iq).into_affine(), p + q);
+        assert_eq!(ip.add(&ip).into_affine(), p + p);
+        assert_eq!(
+            ip.double().into_affine(),
+            Xsk233Projective::from(p).double()
+        );
+        assert_eq!(ip.neg().into_affine(), -p);
+        assert_eq!(ip.add(&zero).into_affine(), p);
+        assert!(ip.add(&ip.neg()).into_affine().is_zero());
+    }
+
+    #[test]
+    fn test_frobenius_eigenvalue() {
+        // tau^2 + tau + 2 = 0 on xsk233
+        let p = Xsk233Affine::generator() * Fr::from(12345u64);
+        let ip = InnerPoint::from_affine(&p.into_affine());
+        let res = ip
+            .frobenius()
+            .frobenius()
+            .add(&ip.frobenius())
+            .add(&ip.double());
+        assert!(res.into_affine().is_zero());
+    }
+}
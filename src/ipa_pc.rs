@@ -0,0 +1,426 @@
+//! Transparent polynomial commitments from the inner-product argument.
+//!
+//! A polynomial `p` of degree below `n`, a power of two, is committed as
+//! `C = <p, G>` over its coefficients. Its opening at `z` to `v = p(z)`
+//! is an [`InnerProductProof`] for the vectors `p` and
+//! `b = (1, z, ..., z^(n-1))`:
+//!
+//! ```text
+//! C + <b, H> + v*Q = <p, G> + <b, H> + <p, b>*Q
+//! ```
+//!
+//! where `Q = w*U` for a challenge `w`, so the proofs have `2*log2(n)`
+//! points and need no trusted setup: all generators are
+//! [`hash_to_curve_vartime`] of fixed strings. Several polynomials are
+//! opened at the same point with a single proof on their random linear
+//! combination, and a [`QuerySet`] over several points with one such proof
+//! per distinct point.
+//!
+//! The commitments are not hiding. This module does not implement the
+//! `PolynomialCommitment` trait of `ark-poly-commit`, which is not a
+//! dependency of this crate: there are no universal parameters separate
+//! from the [`CommitterKey`], polynomials are not labeled but referred to
+//! by their index in the slices passed to each method, and the challenges
+//! come from a [`Transcript`] rather than a sponge.
+
+use crate::affine::Xsk233Affine;
+use crate::bulletproofs::ProofError;
+use crate::bulletproofs::inner_product::InnerProductProof;
+use crate::group::Xsk233Projective;
+use crate::hash::hash_to_curve_vartime;
+use crate::transcript::Transcript;
+use crate::xsk233::Fr;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, Zero};
+use ark_poly::Polynomial;
+use ark_poly::univariate::DensePolynomial;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::iter;
+
+const GENERATORS_DST: &[u8] = b"xsk233-ipa-pc-v1-generators";
+
+/// Openings to prove, as pairs of a polynomial index and a point.
+pub type QuerySet = BTreeSet<(usize, Fr)>;
+
+/// Values of the polynomials at the points of a [`QuerySet`].
+pub type Evaluations = BTreeMap<(usize, Fr), Fr>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpaPcError {
+    /// The degree of a polynomial is above the one supported by the key.
+    TooLargeDegree { degree: usize, max_degree: usize },
+    /// There are not as many commitments or values as polynomials.
+    MismatchedLengths,
+    /// A query refers to a polynomial index out of range.
+    UnknownPolynomial(usize),
+}
+
+impl Display for IpaPcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            IpaPcError::TooLargeDegree { degree, max_degree } => {
+                write!(f, "degree {degree} is above the maximum {max_degree}")
+            }
+            IpaPcError::MismatchedLengths => write!(f, "mismatched number of polynomials"),
+            IpaPcError::UnknownPolynomial(i) => write!(f, "unknown polynomial {i}"),
+        }
+    }
+}
+
+impl Error for IpaPcError {}
+
+/// Commitment `<p, G>` to the coefficients of a polynomial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Commitment(pub Xsk233Affine);
+
+/// Proof of the evaluations of one or several polynomials at a point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpeningProof(InnerProductProof);
+
+impl OpeningProof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        InnerProductProof::from_bytes(bytes).map(Self)
+    }
+}
+
+/// Proofs of a [`QuerySet`], one for each distinct point in increasing
+/// order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchProof(pub Vec<OpeningProof>);
+
+/// Generators for polynomials up to a maximum degree, used both to commit
+/// and to verify.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitterKey {
+    g: Vec<Xsk233Affine>,
+    h: Vec<Xsk233Affine>,
+    u: Xsk233Affine,
+}
+
+impl CommitterKey {
+    /// Derives the generators for polynomials of degree up to
+    /// `max_degree`, rounded up to a power of two minus one.
+    pub fn setup(max_degree: usize) -> Self {
+        let n = (max_degree + 1).next_power_of_two();
+        let derive = |label: u8, i: usize| {
+            let input = [&[label][..], &(i as u64).to_le_bytes()].concat();
+            hash_to_curve_vartime(GENERATORS_DST, &input)
+        };
+        Self {
+            g: (0..n).map(|i| derive(b'G', i)).collect(),
+            h: (0..n).map(|i| derive(b'H', i)).collect(),
+            u: hash_to_curve_vartime(GENERATORS_DST, b"U"),
+        }
+    }
+
+    /// Restricts the key to polynomials of degree up to `max_degree`,
+    /// which makes proofs smaller and faster.
+    pub fn trim(&self, max_degree: usize) -> Result<Self, IpaPcError> {
+        let n = (max_degree + 1).next_power_of_two();
+        self.check_degree(max_degree)?;
+        Ok(Self {
+            g: self.g[..n].to_vec(),
+            h: self.h[..n].to_vec(),
+            u: self.u,
+        })
+    }
+
+    pub fn max_degree(&self) -> usize {
+        self.g.len() - 1
+    }
+
+    pub fn commit(&self, poly: &DensePolynomial<Fr>) -> Result<Commitment, IpaPcError> {
+        self.check_degree(poly.degree())?;
+        let commitment = Xsk233Projective::msm_unchecked(&self.g, &poly.coeffs);
+        Ok(Commitment(commitment.into_affine()))
+    }
+
+    /// Evaluates all `polys` at `point` and proves the results with a
+    /// single proof.
+    pub fn open(
+        &self,
+        transcript: &mut Transcript,
+        polys: &[&DensePolynomial<Fr>],
+        commitments: &[Commitment],
+        point: &Fr,
+    ) -> Result<(Vec<Fr>, OpeningProof), IpaPcError> {
+        if polys.len() != commitments.len() {
+            return Err(IpaPcError::MismatchedLengths);
+        }
+        for poly in polys {
+            self.check_degree(poly.degree())?;
+        }
+        let values: Vec<_> = polys.iter().map(|p| p.evaluate(point)).collect();
+        let (rho, q) = self.challenges(transcript, commitments, point, &values);
+
+        // sum(rho^i * p_i), padded to the size of the key
+        let mut combined = vec![Fr::zero(); self.g.len()];
+        for (poly, rho_i) in polys.iter().zip(powers(&rho)) {
+            for (c, p) in combined.iter_mut().zip(&poly.coeffs) {
+                *c += rho_i * p;
+            }
+        }
+        let b = powers(point).take(self.g.len()).collect();
+        let proof =
+            InnerProductProof::prove(transcript, &q, self.g.clone(), self.h.clone(), combined, b);
+        Ok((values, OpeningProof(proof)))
+    }
+
+    /// Checks that the polynomials of `commitments` evaluate to `values` at
+    /// `point`.
+    pub fn check(
+        &self,
+        transcript: &mut Transcript,
+        commitments: &[Commitment],
+        point: &Fr,
+        values: &[Fr],
+        proof: &OpeningProof,
+    ) -> bool {
+        if commitments.len() != values.len() {
+            return false;
+        }
+        let (rho, q) = self.challenges(transcript, commitments, point, values);
+
+        // P = sum(rho^i * C_i) + <b, H> + sum(rho^i * v_i)*Q
+        let value: Fr = values.iter().zip(powers(&rho)).map(|(v, r)| r * v).sum();
+        let bases = [
+            &commitments.iter().map(|c| c.0).collect::<Vec<_>>()[..],
+            &self.h,
+            &[q],
+        ]
+        .concat();
+        let scalars: Vec<_> = powers(&rho)
+            .take(commitments.len())
+            .chain(powers(point).take(self.h.len()))
+            .chain([value])
+            .collect();
+        let p = Xsk233Projective::msm_unchecked(&bases, &scalars).into_affine();
+        proof.0.verify(transcript, &q, &p, &self.g, &self.h).is_ok()
+    }
+
+    /// Evaluates the polynomials at the points of `queries`, with one
+    /// [`Self::open`] proof per distinct point.
+    pub fn batch_open(
+        &self,
+        transcript: &mut Transcript,
+        polys: &[&DensePolynomial<Fr>],
+        commitments: &[Commitment],
+        queries: &QuerySet,
+    ) -> Result<(Evaluations, BatchProof), IpaPcError> {
+        if polys.len() != commitments.len() {
+            return Err(IpaPcError::MismatchedLengths);
+        }
+        let mut evaluations = Evaluations::new();
+        let mut proofs = Vec::new();
+        for (point, indices) in group_by_point(queries, polys.len())? {
+            let polys: Vec<_> = indices.iter().map(|i| polys[*i]).collect();
+            let commitments: Vec<_> = indices.iter().map(|i| commitments[*i]).collect();
+            let (values, proof) = self.open(transcript, &polys, &commitments, &point)?;
+            evaluations.extend(indices.into_iter().map(|i| (i, point)).zip(values));
+            proofs.push(proof);
+        }
+        Ok((evaluations, BatchProof(proofs)))
+    }
+
+    /// Checks that the polynomials of `commitments` evaluate to
+    /// `evaluations` at the points of `queries`.
+    pub fn batch_check(
+        &self,
+        transcript: &mut Transcript,
+        commitments: &[Commitment],
+        queries: &QuerySet,
+        evaluations: &Evaluations,
+        proof: &BatchProof,
+    ) -> bool {
+        let Ok(groups) = group_by_point(queries, commitments.len()) else {
+            return false;
+        };
+        if groups.len() != proof.0.len() {
+            return false;
+        }
+        groups
+            .into_iter()
+            .zip(&proof.0)
+            .all(|((point, indices), proof)| {
+                let Some(values) = indices
+                    .iter()
+                    .map(|i| evaluations.get(&(*i, point)).copied())
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return false;
+                };
+                let commitments: Vec<_> = indices.iter().map(|i| commitments[*i]).collect();
+                self.check(transcript, &commitments, &point, &values, proof)
+            })
+    }
+
+    fn check_degree(&self, degree: usize) -> Result<(), IpaPcError> {
+        if degree > self.max_degree() {
+            return Err(IpaPcError::TooLargeDegree {
+                degree,
+                max_degree: self.max_degree(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Draws the combination challenge `rho` and `Q = w*U`.
+    fn challenges(
+        &self,
+        transcript: &mut Transcript,
+        commitments: &[Commitment],
+        point: &Fr,
+        values: &[Fr],
+    ) -> (Fr, Xsk233Affine) {
+        transcript.append_message(b"dom-sep", b"ipa-pc v1");
+        transcript.append_u64(b"n", self.g.len() as u64);
+        for (c, v) in commitments.iter().zip(values) {
+            transcript.append_point(b"C", &c.0);
+            transcript.append_scalar(b"v", v);
+        }
+        transcript.append_scalar(b"z", point);
+        let rho = transcript.challenge_scalar(b"rho");
+        let w = transcript.challenge_scalar(b"w");
+        (rho, (self.u * w).into_affine())
+    }
+}
+
+/// The indices queried at each point, in increasing order of points.
+fn group_by_point(
+    queries: &QuerySet,
+    num_polys: usize,
+) -> Result<BTreeMap<Fr, Vec<usize>>, IpaPcError> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for (index, point) in queries {
+        if *index >= num_polys {
+            return Err(IpaPcError::UnknownPolynomial(*index));
+        }
+        groups.entry(*point).or_default().push(*index);
+    }
+    Ok(groups)
+}
+
+/// `1, x, x^2, ...`.
+fn powers(x: &Fr) -> impl Iterator<Item = Fr> + '_ {
+    iter::successors(Some(Fr::ONE), move |p| Some(*p * x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::DenseUVPolynomial;
+    use ark_std::{UniformRand, test_rng};
+
+    #[test]
+    fn test_open_and_check() {
+        let mut rng = test_rng();
+        let key = CommitterKey::setup(20);
+        assert_eq!(key.max_degree(), 31);
+        let key = key.trim(10).unwrap();
+        assert_eq!(key.max_degree(), 15);
+
+        let polys: Vec<_> = [12, 3, 0]
+            .iter()
+            .map(|d| DensePolynomial::<Fr>::rand(*d, &mut rng))
+            .collect();
+        let polys: Vec<_> = polys.iter().collect();
+        let commitments: Vec<_> = polys.iter().map(|p| key.commit(p).unwrap()).collect();
+        let point = Fr::rand(&mut rng);
+        let (values, proof) = key
+            .open(&mut Transcript::new(b"pc"), &polys, &commitments, &point)
+            .unwrap();
+        assert_eq!(values[0], polys[0].evaluate(&point));
+        let proof = OpeningProof::from_bytes(&proof.to_bytes()).unwrap();
+
+        let check = |commitments: &[Commitment], point: &Fr, values: &[Fr]| {
+            key.check(
+                &mut Transcript::new(b"pc"),
+                commitments,
+                point,
+                values,
+                &proof,
+            )
+        };
+        assert!(check(&commitments, &point, &values));
+        let mut wrong = values.clone();
+        wrong.swap(0, 1);
+        assert!(!check(&commitments, &point, &wrong));
+        assert!(!check(&commitments, &(point + Fr::ONE), &values));
+        assert!(!check(&commitments[..2], &point, &values[..2]));
+        let other = key.commit(&DensePolynomial::rand(12, &mut rng)).unwrap();
+        assert!(!check(
+            &[other, commitments[1], commitments[2]],
+            &point,
+            &values
+        ));
+
+        assert_eq!(
+            key.commit(&DensePolynomial::rand(16, &mut rng)),
+            Err(IpaPcError::TooLargeDegree {
+                degree: 16,
+                max_degree: 15
+            })
+        );
+    }
+
+    #[test]
+    fn test_batch_open() {
+        let mut rng = test_rng();
+        let key = CommitterKey::setup(7);
+        let polys: Vec<_> = [7, 3, 5]
+            .iter()
+            .map(|d| DensePolynomial::<Fr>::rand(*d, &mut rng))
+            .collect();
+        let polys: Vec<_> = polys.iter().collect();
+        let commitments: Vec<_> = polys.iter().map(|p| key.commit(p).unwrap()).collect();
+        let (z1, z2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let queries = QuerySet::from([(0, z1), (1, z1), (1, z2), (2, z2)]);
+
+        let (evaluations, proof) = key
+            .batch_open(
+                &mut Transcript::new(b"batch"),
+                &polys,
+                &commitments,
+                &queries,
+            )
+            .unwrap();
+        assert_eq!(proof.0.len(), 2);
+        for (i, z) in &queries {
+            assert_eq!(evaluations[&(*i, *z)], polys[*i].evaluate(z));
+        }
+        let check = |queries: &QuerySet, evaluations: &Evaluations| {
+            key.batch_check(
+                &mut Transcript::new(b"batch"),
+                &commitments,
+                queries,
+                evaluations,
+                &proof,
+            )
+        };
+        assert!(check(&queries, &evaluations));
+
+        let mut wrong = evaluations.clone();
+        *wrong.get_mut(&(1, z2)).unwrap() += Fr::ONE;
+        assert!(!check(&queries, &wrong));
+        let mut missing = queries.clone();
+        missing.remove(&(2, z2));
+        assert!(!check(&missing, &evaluations));
+        assert!(!check(&QuerySet::from([(3, z1)]), &evaluations));
+        assert_eq!(
+            key.batch_open(
+                &mut Transcript::new(b"batch"),
+                &polys,
+                &commitments,
+                &QuerySet::from([(3, z1)])
+            )
+            .err(),
+            Some(IpaPcError::UnknownPolynomial(3))
+        );
+    }
+}
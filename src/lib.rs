@@ -1,8 +1,55 @@
-use ark_ff::BigInt;
+use crate::xsk233::Fr;
+use ark_ff::{BigInt, PrimeField};
 
+/// Records a point operation when the `metrics` feature is enabled.
+macro_rules! record_op {
+    ($op:ident) => {
+        #[cfg(feature = "metrics")]
+        $crate::metrics::record($crate::metrics::Op::$op);
+    };
+}
+
+/// Stands in for [`record_op`] on xsb233, whose costs are not modelled.
+macro_rules! ignore_op {
+    ($op:ident) => {};
+}
+
+pub mod adaptor;
 pub mod affine;
 mod arithmetics;
+pub mod blind;
+pub mod bulletproofs;
+pub mod circuit;
+pub mod dkg;
+pub mod dleq;
+pub mod ecies;
+pub mod elgamal;
+pub mod frost;
+pub mod gf233;
 pub mod group;
+pub mod hash;
+pub mod hd;
+pub mod hpke;
+mod inner;
+pub mod ipa_pc;
+pub mod keys;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod musig2;
+pub mod oprf;
+mod point;
+#[cfg(feature = "r1cs")]
+pub mod r1cs;
+pub mod ring;
+pub mod schnorr;
+mod shamir;
+pub mod sigma;
+#[cfg(test)]
+mod test_templates;
+mod tnaf;
+pub mod transcript;
+pub mod vrf;
+pub mod xsb233;
 pub mod xsk233;
 
 fn bigint_to_le_bytes(scalar: BigInt<4>) -> Vec<u8> {
@@ -25,3 +72,28 @@ fn bigint_to_le_bytes(scalar: BigInt<4>) -> Vec<u8> {
     }
     bytes
 }
+
+/// Size of the encoding of a scalar, the same as that of a point.
+pub(crate) const SCALAR_SIZE: usize = 30;
+
+/// Little-endian encoding of a scalar on [`SCALAR_SIZE`] bytes.
+pub(crate) fn scalar_to_bytes(scalar: &Fr) -> [u8; SCALAR_SIZE] {
+    let limbs = scalar.into_bigint().0;
+    let mut bytes = [0u8; 32];
+    for (chunk, limb) in bytes.chunks_exact_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    bytes[..SCALAR_SIZE].try_into().unwrap()
+}
+
+/// Inverse of [`scalar_to_bytes`]; rejects encodings of integers that are
+/// not reduced modulo the group order.
+pub(crate) fn scalar_from_bytes(bytes: &[u8; SCALAR_SIZE]) -> Option<Fr> {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        *limb = u64::from_le_bytes(buf);
+    }
+    Fr::from_bigint(BigInt(limbs))
+}
@@ -0,0 +1,453 @@
+//! Operation counting for cost modelling, behind the `metrics` feature.
+//!
+//! Every xsk233 point operation records itself in thread-local counters,
+//! together with the GF(2^233) operations it performs. The field costs are
+//! not measured but derived from the formulas in xs233; for the scalar
+//! multiplication they follow the schedule of
+//! `xsk233_mul_frob()` in the portable build, which is what xs233-sys
+//! compiles without its `pclmul`/`sse41`/`avx2` features. Field additions
+//! are free and not counted.
+//!
+//! The multi-scalar multiplication of arkworks spreads its windows over the
+//! rayon thread pool; with this feature enabled,
+//! [`Xsk233Projective`](crate::group::Xsk233Projective) runs the same
+//! algorithm sequentially instead, so that its operations are counted on
+//! the calling thread.
+//!
+//! ```ignore
+//! let counter = OpCounter::new();
+//! let _ = Xsk233Projective::msm(&bases, &scalars);
+//! println!("{}", counter.counts());
+//! ```
+
+use ark_ec::VariableBaseMSM;
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{
+    cell::Cell,
+    cmp::Ordering,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{Add, AddAssign, Sub},
+};
+
+/// Totals of point and field operations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    /// Point additions and subtractions, including those performed inside
+    /// scalar multiplications.
+    pub point_add: u64,
+    pub point_double: u64,
+    pub point_neg: u64,
+    /// Applications of the Frobenius endomorphism.
+    pub frobenius: u64,
+    /// Calls to the constant-time scalar multiplication.
+    pub scalar_mul: u64,
+    pub equals: u64,
+    pub encode: u64,
+    pub decode: u64,
+
+    pub field_mul: u64,
+    pub field_square: u64,
+    pub field_inv: u64,
+    pub field_sqrt: u64,
+    pub field_halftrace: u64,
+}
+
+impl Add for OpCounts {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for OpCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.point_add += other.point_add;
+        self.point_double += other.point_double;
+        self.point_neg += other.point_neg;
+        self.frobenius += other.frobenius;
+        self.scalar_mul += other.scalar_mul;
+        self.equals += other.equals;
+        self.encode += other.encode;
+        self.decode += other.decode;
+        self.field_mul += other.field_mul;
+        self.field_square += other.field_square;
+        self.field_inv += other.field_inv;
+        self.field_sqrt += other.field_sqrt;
+        self.field_halftrace += other.field_halftrace;
+    }
+}
+
+impl Sub for OpCounts {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            point_add: self.point_add - other.point_add,
+            point_double: self.point_double - other.point_double,
+            point_neg: self.point_neg - other.point_neg,
+            frobenius: self.frobenius - other.frobenius,
+            scalar_mul: self.scalar_mul - other.scalar_mul,
+            equals: self.equals - other.equals,
+            encode: self.encode - other.encode,
+            decode: self.decode - other.decode,
+            field_mul: self.field_mul - other.field_mul,
+            field_square: self.field_square - other.field_square,
+            field_inv: self.field_inv - other.field_inv,
+            field_sqrt: self.field_sqrt - other.field_sqrt,
+            field_halftrace: self.field_halftrace - other.field_halftrace,
+        }
+    }
+}
+
+impl Display for OpCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(
+            f,
+            "points: {} add, {} double, {} neg, {} frobenius, {} scalar mul, \
+             {} equals, {} encode, {} decode",
+            self.point_add,
+            self.point_double,
+            self.point_neg,
+            self.frobenius,
+            self.scalar_mul,
+            self.equals,
+            self.encode,
+            self.decode
+        )?;
+        write!(
+            f,
+            "field: {} mul, {} square, {} inv, {} sqrt, {} halftrace",
+            self.field_mul,
+            self.field_square,
+            self.field_inv,
+            self.field_sqrt,
+            self.field_halftrace
+        )
+    }
+}
+
+/// Point operations that record themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Op {
+    Add,
+    Double,
+    Neg,
+    Frobenius,
+    ScalarMul,
+    Equals,
+    Encode,
+    Decode,
+}
+
+/// Number of window digits processed by `xsk233_mul_frob()` after the top
+/// one, and Frobenius applications per digit.
+const MUL_FROB_DIGITS: u64 = 47;
+const MUL_FROB_FROBENIUS_PER_DIGIT: u64 = 5;
+
+/// Point additions and Frobenius applications that build the 21-entry
+/// window table of `xsk233_mul_frob()`.
+const MUL_FROB_TABLE_ADDS: u64 = 10;
+const MUL_FROB_TABLE_FROBENIUS: u64 = 10;
+
+impl Op {
+    pub(crate) fn cost(self) -> OpCounts {
+        match self {
+            // X1X2, S1S2, Z1Z2, d, g*s1s2, f*d, X3*Z3; f and g are squares
+            Op::Add => OpCounts {
+                point_add: 1,
+                field_mul: 7,
+                field_square: 2,
+                ..Default::default()
+            },
+            // S*(S + T), E*(...), X'*Z'; Z^2, T^2, E, and (D + X')^2
+            Op::Double => OpCounts {
+                point_double: 1,
+                field_mul: 3,
+                field_square: 4,
+                ..Default::default()
+            },
+            // S <- S + T
+            Op::Neg => OpCounts {
+                point_neg: 1,
+                ..Default::default()
+            },
+            // squares the four coordinates
+            Op::Frobenius => OpCounts {
+                frobenius: 1,
+                field_square: 4,
+                ..Default::default()
+            },
+            // table construction, then one addition and a run of Frobenius
+            // maps per remaining window digit
+            Op::ScalarMul => {
+                let adds = MUL_FROB_TABLE_ADDS + MUL_FROB_DIGITS;
+                let frobenius =
+                    MUL_FROB_TABLE_FROBENIUS + MUL_FROB_DIGITS * MUL_FROB_FROBENIUS_PER_DIGIT;
+                OpCounts {
+                    point_add: adds,
+                    frobenius,
+                    scalar_mul: 1,
+                    field_mul: 7 * adds,
+                    field_square: 2 * adds + 4 * frobenius,
+                    ..Default::default()
+                }
+            }
+            // S1*T2 == S2*T1
+            Op::Equals => OpCounts {
+                equals: 1,
+                field_mul: 2,
+                ..Default::default()
+            },
+            // w = sqrt(S/T)
+            Op::Encode => OpCounts {
+                encode: 1,
+                field_mul: 1,
+                field_inv: 1,
+                field_sqrt: 1,
+                ..Default::default()
+            },
+            // d = w^2 + w, e = 1/d^2, x = d*halftrace(e), lambda = halftrace(x),
+            // trace(x*(lambda + w)), s = x*w^2
+            Op::Decode => OpCounts {
+                decode: 1,
+                field_mul: 3,
+                field_square: 3,
+                field_inv: 1,
+                field_halftrace: 2,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+thread_local! {
+    static COUNTS: Cell<OpCounts> = Cell::new(OpCounts::default());
+}
+
+pub(crate) fn record(op: Op) {
+    COUNTS.with(|counts| counts.set(counts.get() + op.cost()));
+}
+
+/// Returns the totals recorded on this thread since it started.
+pub fn thread_totals() -> OpCounts {
+    COUNTS.with(Cell::get)
+}
+
+/// Counts the operations performed on the current thread from its creation
+/// onwards. Counters can be nested freely.
+///
+/// Work done on other threads is not included.
+#[derive(Clone, Debug)]
+pub struct OpCounter {
+    start: OpCounts,
+}
+
+impl OpCounter {
+    pub fn new() -> Self {
+        Self {
+            start: thread_totals(),
+        }
+    }
+
+    /// Operations performed since the counter was created.
+    pub fn counts(&self) -> OpCounts {
+        thread_totals() - self.start
+    }
+
+    /// Restarts counting from zero.
+    pub fn reset(&mut self) {
+        self.start = thread_totals();
+    }
+}
+
+impl Default for OpCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs `f` and returns its result with the operations it performed.
+pub fn count<R>(f: impl FnOnce() -> R) -> (R, OpCounts) {
+    let counter = OpCounter::new();
+    let res = f();
+    (res, counter.counts())
+}
+
+/// Sequential version of the windowed NAF multi-scalar multiplication of
+/// arkworks, performing the same point operations.
+pub(crate) fn msm_bigint_wnaf<V: VariableBaseMSM>(
+    bases: &[V::MulBase],
+    bigints: &[<V::ScalarField as PrimeField>::BigInt],
+) -> V {
+    let size = bases.len().min(bigints.len());
+    let scalars = &bigints[..size];
+    let bases = &bases[..size];
+
+    let c = if size < 32 {
+        3
+    } else {
+        // ln(size), as computed by arkworks
+        (ark_std::log2(size) * 69 / 100) as usize + 2
+    };
+
+    let num_bits = V::ScalarField::MODULUS_BIT_SIZE as usize;
+    let digits_count = num_bits.div_ceil(c);
+    let scalar_digits = scalars
+        .iter()
+        .flat_map(|s| make_digits(s, c, num_bits))
+        .collect::<Vec<_>>();
+
+    let window_sums = (0..digits_count).map(|i| {
+        let mut buckets = vec![V::zero(); 1 << c];
+        for (digits, base) in scalar_digits.chunks(digits_count).zip(bases) {
+            match 0.cmp(&digits[i]) {
+                Ordering::Less => buckets[(digits[i] - 1) as usize] += base,
+                Ordering::Greater => buckets[(-digits[i] - 1) as usize] -= base,
+                Ordering::Equal => (),
+            }
+        }
+
+        let mut running_sum = V::zero();
+        let mut res = V::zero();
+        for b in buckets.into_iter().rev() {
+            running_sum += &b;
+            res += &running_sum;
+        }
+        res
+    });
+    let window_sums = window_sums.collect::<Vec<_>>();
+
+    // windows from high to low, then the lowest one
+    let higher = window_sums[1..]
+        .iter()
+        .rev()
+        .fold(V::zero(), |mut total, sum| {
+            total += sum;
+            for _ in 0..c {
+                total.double_in_place();
+            }
+            total
+        });
+    window_sums[0] + higher
+}
+
+/// Signed base-`2^w` digits of `a`, as used by the arkworks MSM.
+fn make_digits(a: &impl BigInteger, w: usize, num_bits: usize) -> impl Iterator<Item = i64> + '_ {
+    let scalar = a.as_ref();
+    let radix: u64 = 1 << w;
+    let window_mask: u64 = radix - 1;
+    let digits_count = num_bits.div_ceil(w);
+
+    let mut carry = 0u64;
+    (0..digits_count).map(move |i| {
+        let bit_offset = i * w;
+        let u64_idx = bit_offset / 64;
+        let bit_idx = bit_offset % 64;
+        let bit_buf = if bit_idx < 64 - w || u64_idx == scalar.len() - 1 {
+            scalar[u64_idx] >> bit_idx
+        } else {
+            (scalar[u64_idx] >> bit_idx) | (scalar[1 + u64_idx] << (64 - bit_idx))
+        };
+
+        // recentre the window from [0, 2^w) to [-2^(w-1), 2^(w-1))
+        let coef = carry + (bit_buf & window_mask);
+        carry = (coef + radix / 2) >> w;
+        let mut digit = (coef as i64) - (carry << w) as i64;
+        if i == digits_count - 1 {
+            digit += (carry << w) as i64;
+        }
+        digit
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::affine::Xsk233Affine;
+    use crate::group::Xsk233Projective;
+    use crate::xsk233::Fr;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::AdditiveGroup;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_point_operations() {
+        let g = Xsk233Projective::from(Xsk233Affine::generator());
+        let h = g.double();
+
+        let (_, counts) = count(|| g + h);
+        assert_eq!(counts, Op::Add.cost());
+
+        // subtraction negates, then adds
+        let (_, counts) = count(|| (g - h).double());
+        assert_eq!(counts, Op::Neg.cost() + Op::Add.cost() + Op::Double.cost());
+
+        let (_, counts) = count(|| -g);
+        assert_eq!(counts.point_neg, 1);
+        assert_eq!(counts.field_mul, 0);
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        let g = Xsk233Affine::generator();
+        let (_, counts) = count(|| g * Fr::from(12345u64));
+
+        assert_eq!(counts.scalar_mul, 1);
+        assert_eq!(counts.point_add, 57);
+        assert_eq!(counts.frobenius, 245);
+        assert_eq!(counts.field_mul, 399);
+        assert_eq!(counts.field_square, 1094);
+    }
+
+    #[test]
+    fn test_nested_counters() {
+        let g = Xsk233Affine::generator();
+        let outer = OpCounter::new();
+        let _ = g.to_bytes();
+        {
+            let mut inner = OpCounter::new();
+            let _ = g.into_group().double();
+            assert_eq!(inner.counts().point_double, 1);
+            inner.reset();
+            assert_eq!(inner.counts(), OpCounts::default());
+        }
+        let _ = Xsk233Affine::from_bytes(&g.to_bytes());
+
+        let counts = outer.counts();
+        assert_eq!(counts.point_double, 1);
+        assert_eq!(counts.encode, 2);
+        assert_eq!(counts.decode, 1);
+    }
+
+    #[test]
+    fn test_double_mul() {
+        let g = Xsk233Affine::generator();
+        let a = Fr::from(u64::MAX);
+        let (_, counts) = count(|| Xsk233Projective::double_mul(&a, &g, &a, &g));
+
+        // no constant-time scalar multiplication, one Frobenius per digit
+        assert_eq!(counts.scalar_mul, 0);
+        assert!(counts.frobenius > 0);
+        assert!(counts.point_add > 0);
+    }
+
+    #[test]
+    fn test_msm() {
+        let mut rng = ark_std::test_rng();
+        let scalars = (0..8).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let bases = (0..8)
+            .map(|_| Xsk233Projective::rand(&mut rng).into_affine())
+            .collect::<Vec<_>>();
+
+        let (msm, counts) = count(|| Xsk233Projective::msm(&bases, &scalars).unwrap());
+        let expected: Xsk233Projective = bases.iter().zip(&scalars).map(|(b, s)| *b * s).sum();
+        assert_eq!(msm, expected);
+
+        // 78 windows of 3 bits, with 8 buckets summed twice in each
+        assert_eq!(counts.point_double, 3 * 77);
+        assert!(counts.point_add >= 78 * 2 * 8);
+        assert_eq!(counts.scalar_mul, 0);
+    }
+}
@@ -0,0 +1,383 @@
+//! MuSig2 n-of-n multi-signatures over xsk233.
+//!
+//! The signers aggregate their public keys into a single key with a
+//! [`KeyAggContext`], and jointly produce ordinary [`Signature`]s under it
+//! in two rounds, the first of which does not depend on the message:
+//!
+//! 1. every signer calls [`nonce_gen`], keeps its [`SecretNonce`] and
+//!    broadcasts its [`PublicNonce`]; the public nonces are summed into an
+//!    [`AggregateNonce`];
+//! 2. every signer opens a [`Session`] for the message and computes its
+//!    [`PartialSignature`] with [`Session::sign`]; anyone can check it with
+//!    [`Session::verify_partial`] and combine them with
+//!    [`Session::aggregate`].
+//!
+//! The structure follows BIP 327, without the x-only keys and the even-y
+//! adjustments of BIP 340: key aggregation coefficients are
+//! `a_i = H_agg(L || X_i)`, with `L` the hash of all keys and `a_i = 1` for
+//! the second distinct key, and the nonce coefficient is
+//! `b = H_non(X || R_1 || R_2 || msg)`.
+//!
+//! A secret nonce must never be used twice, which [`Session::sign`]
+//! enforces by consuming it.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::group::Xsk233Projective;
+use crate::hash::hash_to_scalar;
+use crate::keys::SecretKey;
+use crate::schnorr::{self, Signature};
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, scalar_from_bytes, scalar_to_bytes};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::One;
+use ark_std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Size of an encoded [`PublicNonce`] or [`AggregateNonce`].
+pub const NONCE_SIZE: usize = 2 * COMPRESSED_POINT_SIZE;
+
+const KEYAGG_LIST_DST: &[u8] = b"xsk233-musig2-v1-keyagg-list";
+const KEYAGG_COEF_DST: &[u8] = b"xsk233-musig2-v1-keyagg-coef";
+const NONCE_COEF_DST: &[u8] = b"xsk233-musig2-v1-noncecoef";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Musig2Error {
+    /// No public keys to aggregate.
+    NoKeys,
+    /// A public key, or the aggregated key, is the neutral element.
+    InvalidKey,
+    /// The signing key is not one of the aggregated keys.
+    UnknownKey,
+    /// The secret nonce was generated for another key.
+    NonceKeyMismatch,
+}
+
+impl Display for Musig2Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Musig2Error::NoKeys => write!(f, "no public keys to aggregate"),
+            Musig2Error::InvalidKey => write!(f, "invalid public key"),
+            Musig2Error::UnknownKey => write!(f, "signing key is not an aggregated key"),
+            Musig2Error::NonceKeyMismatch => write!(f, "nonce was generated for another key"),
+        }
+    }
+}
+
+impl Error for Musig2Error {}
+
+/// Aggregation of the public keys of the signers, in a fixed order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyAggContext {
+    keys: Vec<Xsk233Affine>,
+    coefficients: Vec<Fr>,
+    aggregated_key: Xsk233Affine,
+}
+
+impl KeyAggContext {
+    /// Aggregates `keys` into `X = sum(a_i * X_i)`. The order of the keys
+    /// matters, so signers must agree on it, e.g. by sorting their
+    /// encodings.
+    pub fn new(keys: &[Xsk233Affine]) -> Result<Self, Musig2Error> {
+        if keys.is_empty() {
+            return Err(Musig2Error::NoKeys);
+        }
+        if keys.iter().any(Xsk233Affine::is_zero) {
+            return Err(Musig2Error::InvalidKey);
+        }
+
+        let list_hash = keys
+            .iter()
+            .fold(Sha256::new().chain_update(KEYAGG_LIST_DST), |h, k| {
+                h.chain_update(k.to_bytes())
+            })
+            .finalize();
+        let second = keys.iter().find(|k| **k != keys[0]);
+        let coefficients: Vec<_> = keys
+            .iter()
+            .map(|k| {
+                if Some(k) == second {
+                    Fr::one()
+                } else {
+                    hash_to_scalar(KEYAGG_COEF_DST, &[&list_hash[..], &k.to_bytes()].concat())
+                }
+            })
+            .collect();
+
+        let aggregated_key = keys
+            .iter()
+            .zip(&coefficients)
+            .map(|(k, a)| *k * a)
+            .sum::<Xsk233Projective>()
+            .into_affine();
+        if aggregated_key.is_zero() {
+            return Err(Musig2Error::InvalidKey);
+        }
+        Ok(Self {
+            keys: keys.to_vec(),
+            coefficients,
+            aggregated_key,
+        })
+    }
+
+    /// The aggregated key, under which the signatures verify.
+    pub fn aggregated_key(&self) -> &Xsk233Affine {
+        &self.aggregated_key
+    }
+
+    pub fn keys(&self) -> &[Xsk233Affine] {
+        &self.keys
+    }
+
+    /// Aggregation coefficient `a_i` of `key`, if it is one of the keys.
+    pub fn coefficient(&self, key: &Xsk233Affine) -> Option<Fr> {
+        let i = self.keys.iter().position(|k| k == key)?;
+        Some(self.coefficients[i])
+    }
+}
+
+/// Secret nonces `(k_1, k_2)` of a signer, for a single signature.
+pub struct SecretNonce {
+    k1: Fr,
+    k2: Fr,
+    public_key: Xsk233Affine,
+}
+
+impl Debug for SecretNonce {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SecretNonce")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for SecretNonce {
+    fn drop(&mut self) {
+        self.k1.zeroize();
+        self.k2.zeroize();
+    }
+}
+
+/// Public nonces `(R_1, R_2) = (k_1*G, k_2*G)` of a signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicNonce {
+    r1: Xsk233Affine,
+    r2: Xsk233Affine,
+}
+
+impl PublicNonce {
+    pub fn to_bytes(&self) -> [u8; NONCE_SIZE] {
+        nonce_to_bytes(&self.r1, &self.r2)
+    }
+
+    pub fn from_bytes(bytes: &[u8; NONCE_SIZE]) -> Option<Self> {
+        let (r1, r2) = nonce_from_bytes(bytes)?;
+        Some(Self { r1, r2 })
+    }
+}
+
+/// Generates the nonces of the holder of `secret` for one signing session.
+/// The nonces are hedged: they hash fresh randomness with the key.
+pub fn nonce_gen<R: Rng + ?Sized>(rng: &mut R, secret: &SecretKey) -> (SecretNonce, PublicNonce) {
+    let k1 = schnorr::nonce(rng, secret.scalar());
+    let k2 = schnorr::nonce(rng, secret.scalar());
+    let g = Xsk233Affine::generator();
+    let public = PublicNonce {
+        r1: (g * *k1).into_affine(),
+        r2: (g * *k2).into_affine(),
+    };
+    let secret_nonce = SecretNonce {
+        k1: *k1,
+        k2: *k2,
+        public_key: secret.public_key(),
+    };
+    (secret_nonce, public)
+}
+
+/// Sum of the public nonces of all signers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AggregateNonce {
+    r1: Xsk233Affine,
+    r2: Xsk233Affine,
+}
+
+impl AggregateNonce {
+    pub fn new(nonces: &[PublicNonce]) -> Self {
+        let r1: Xsk233Projective = nonces.iter().map(|n| n.r1).sum();
+        let r2: Xsk233Projective = nonces.iter().map(|n| n.r2).sum();
+        Self {
+            r1: r1.into_affine(),
+            r2: r2.into_affine(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; NONCE_SIZE] {
+        nonce_to_bytes(&self.r1, &self.r2)
+    }
+
+    pub fn from_bytes(bytes: &[u8; NONCE_SIZE]) -> Option<Self> {
+        let (r1, r2) = nonce_from_bytes(bytes)?;
+        Some(Self { r1, r2 })
+    }
+}
+
+fn nonce_to_bytes(r1: &Xsk233Affine, r2: &Xsk233Affine) -> [u8; NONCE_SIZE] {
+    let mut bytes = [0u8; NONCE_SIZE];
+    bytes[..COMPRESSED_POINT_SIZE].copy_from_slice(&r1.to_bytes());
+    bytes[COMPRESSED_POINT_SIZE..].copy_from_slice(&r2.to_bytes());
+    bytes
+}
+
+fn nonce_from_bytes(bytes: &[u8; NONCE_SIZE]) -> Option<(Xsk233Affine, Xsk233Affine)> {
+    let (r1, r2) = bytes.split_at(COMPRESSED_POINT_SIZE);
+    Some((
+        Xsk233Affine::from_bytes(r1.try_into().unwrap())?,
+        Xsk233Affine::from_bytes(r2.try_into().unwrap())?,
+    ))
+}
+
+/// Partial signature `s_i` of one signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialSignature(Fr);
+
+impl PartialSignature {
+    pub fn to_bytes(&self) -> [u8; SCALAR_SIZE] {
+        scalar_to_bytes(&self.0)
+    }
+
+    pub fn from_bytes(bytes: &[u8; SCALAR_SIZE]) -> Option<Self> {
+        scalar_from_bytes(bytes).map(Self)
+    }
+}
+
+/// Signing session for one message and aggregate nonce.
+#[derive(Clone, Debug)]
+pub struct Session<'a> {
+    context: &'a KeyAggContext,
+    b: Fr,
+    r: Xsk233Affine,
+    c: Fr,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(context: &'a KeyAggContext, nonce: &AggregateNonce, msg: &[u8]) -> Self {
+        let input = [
+            &context.aggregated_key.to_bytes()[..],
+            &nonce.r1.to_bytes(),
+            &nonce.r2.to_bytes(),
+            msg,
+        ]
+        .concat();
+        let b = hash_to_scalar(NONCE_COEF_DST, &input);
+        // R = R_1 + b*R_2
+        let r = (Xsk233Projective::from(nonce.r1) + nonce.r2 * b).into_affine();
+        let c = schnorr::challenge(&r, &context.aggregated_key, msg);
+        Self { context, b, r, c }
+    }
+
+    /// Computes `s_i = k_1 + b*k_2 + c*a_i*x_i`, consuming the nonce.
+    pub fn sign(
+        &self,
+        nonce: SecretNonce,
+        secret: &SecretKey,
+    ) -> Result<PartialSignature, Musig2Error> {
+        let public = secret.public_key();
+        if nonce.public_key != public {
+            return Err(Musig2Error::NonceKeyMismatch);
+        }
+        let a = self
+            .context
+            .coefficient(&public)
+            .ok_or(Musig2Error::UnknownKey)?;
+        let ax = Zeroizing::new(a * secret.scalar());
+        Ok(PartialSignature(
+            nonce.k1 + self.b * nonce.k2 + self.c * *ax,
+        ))
+    }
+
+    /// Checks the partial signature of the holder of `public`, who sent
+    /// `nonce` in the first round: `s_i*G = R_1 + b*R_2 + c*a_i*X_i`.
+    pub fn verify_partial(
+        &self,
+        partial: &PartialSignature,
+        nonce: &PublicNonce,
+        public: &Xsk233Affine,
+    ) -> bool {
+        let Some(a) = self.context.coefficient(public) else {
+            return false;
+        };
+        let mut expected = Xsk233Projective::double_mul(&self.b, &nonce.r2, &(self.c * a), public);
+        expected += nonce.r1;
+        Xsk233Affine::generator() * partial.0 == expected
+    }
+
+    /// Sums the partial signatures into a signature under the aggregated
+    /// key. It is only valid if every partial signature is.
+    pub fn aggregate(&self, partials: &[PartialSignature]) -> Signature {
+        Signature::new(self.r, partials.iter().map(|s| s.0).sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_sign_and_aggregate() {
+        let mut rng = test_rng();
+        let secrets: Vec<_> = (0..4).map(|_| SecretKey::random(&mut rng)).collect();
+        let keys: Vec<_> = secrets.iter().map(SecretKey::public_key).collect();
+        let context = KeyAggContext::new(&keys).unwrap();
+        let msg = b"withdraw 10";
+
+        let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) =
+            secrets.iter().map(|sk| nonce_gen(&mut rng, sk)).unzip();
+        let nonce = AggregateNonce::new(&public_nonces);
+        assert_eq!(AggregateNonce::from_bytes(&nonce.to_bytes()), Some(nonce));
+
+        let session = Session::new(&context, &nonce, msg);
+        let partials: Vec<_> = secret_nonces
+            .into_iter()
+            .zip(&secrets)
+            .map(|(n, sk)| session.sign(n, sk).unwrap())
+            .collect();
+        for ((partial, nonce), key) in partials.iter().zip(&public_nonces).zip(&keys) {
+            assert!(session.verify_partial(partial, nonce, key));
+        }
+        assert!(!session.verify_partial(&partials[0], &public_nonces[1], &keys[0]));
+
+        let signature = session.aggregate(&partials);
+        assert!(signature.verify(context.aggregated_key(), msg));
+        assert!(!signature.verify(&keys[0], msg));
+    }
+
+    #[test]
+    fn test_key_aggregation() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let keys: Vec<_> = (0..3)
+            .map(|_| SecretKey::random(&mut rng).public_key())
+            .collect();
+
+        assert_eq!(KeyAggContext::new(&[]), Err(Musig2Error::NoKeys));
+        assert_eq!(
+            KeyAggContext::new(&[keys[0], Xsk233Affine::zero()]),
+            Err(Musig2Error::InvalidKey)
+        );
+        let context = KeyAggContext::new(&keys).unwrap();
+        let reordered = KeyAggContext::new(&[keys[1], keys[0], keys[2]]).unwrap();
+        assert_ne!(context.aggregated_key(), reordered.aggregated_key());
+        assert_eq!(context.coefficient(&keys[1]), Some(Fr::one()));
+
+        let (secret_nonce, public_nonce) = nonce_gen(&mut rng, &secret);
+        let nonce = AggregateNonce::new(&[public_nonce]);
+        let session = Session::new(&context, &nonce, b"msg");
+        assert_eq!(
+            session.sign(secret_nonce, &secret),
+            Err(Musig2Error::UnknownKey)
+        );
+    }
+}
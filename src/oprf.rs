@@ -0,0 +1,294 @@
+//! Oblivious pseudorandom functions over xsk233, after RFC 9497.
+//!
+//! A server holding a key `k` and a client holding an input jointly
+//! compute `F(k, input)`, without the server learning the input or the
+//! output:
+//!
+//! 1. the client hashes its input to the group and masks it with [`blind`];
+//! 2. the server multiplies the blinded element by its key with
+//!    [`blind_evaluate`];
+//! 3. the client removes the mask and hashes the result with [`finalize`].
+//!
+//! The server can also compute the PRF on its own with [`evaluate`], e.g.
+//! to check a password. In the verifiable mode, the server proves with
+//! [`blind_evaluate_batch`] that it used the key of its public key `k*G`,
+//! and the client checks it with [`finalize_batch`]. The partially
+//! oblivious mode of the RFC is not implemented.
+//!
+//! The protocol follows the RFC with the identifier `xsk233-SHA256`:
+//! `HashToGroup` is [`hash_to_curve`], `HashToScalar` is [`hash_to_scalar`]
+//! and `Hash` is SHA-256, all with the domain separation tags of the RFC.
+//! The proofs of the verifiable mode are the batched [`DleqProof`]s of this
+//! crate, which prove the same statement as the proofs of the RFC.
+
+use crate::affine::Xsk233Affine;
+use crate::dleq::DleqProof;
+use crate::hash::{hash_to_curve, hash_to_scalar};
+use crate::keys::SecretKey;
+use crate::xsk233::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, Zero};
+use ark_std::UniformRand;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use zeroize::Zeroizing;
+
+/// Size of a PRF output.
+pub const OUTPUT_SIZE: usize = 32;
+
+const IDENTIFIER: &[u8] = b"xsk233-SHA256";
+
+/// Protocol variant, which separates the domains of all hashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Oprf = 0x00,
+    Voprf = 0x01,
+}
+
+impl Mode {
+    /// `contextString` of the RFC.
+    fn context_string(self) -> Vec<u8> {
+        [b"OPRFV1-", &[self as u8][..], b"-", IDENTIFIER].concat()
+    }
+
+    fn dst(self, prefix: &[u8]) -> Vec<u8> {
+        [prefix, &self.context_string()].concat()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OprfError {
+    /// The input is too long or hashes to the neutral element.
+    InvalidInput,
+    /// The blind is zero.
+    InvalidBlind,
+    /// No valid key could be derived from the seed.
+    DeriveKeyPair,
+    /// The proof of the server is invalid.
+    Verify,
+}
+
+impl Display for OprfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            OprfError::InvalidInput => write!(f, "invalid input"),
+            OprfError::InvalidBlind => write!(f, "invalid blind"),
+            OprfError::DeriveKeyPair => write!(f, "key derivation failed"),
+            OprfError::Verify => write!(f, "invalid server proof"),
+        }
+    }
+}
+
+impl Error for OprfError {}
+
+/// `DeriveKeyPair`: derives the server key from `seed` and `info`.
+pub fn derive_key_pair(mode: Mode, seed: &[u8; 32], info: &[u8]) -> Result<SecretKey, OprfError> {
+    let info_len = u16::try_from(info.len()).map_err(|_| OprfError::DeriveKeyPair)?;
+    let dst = mode.dst(b"DeriveKeyPair");
+    let mut input = Zeroizing::new([&seed[..], &info_len.to_be_bytes(), info, &[0]].concat());
+    for counter in 0..=u8::MAX {
+        *input.last_mut().unwrap() = counter;
+        if let Some(secret) = SecretKey::new(hash_to_scalar(&dst, &input)) {
+            return Ok(secret);
+        }
+    }
+    Err(OprfError::DeriveKeyPair)
+}
+
+/// `Blind`: hashes `input` to the group and masks it with a random scalar.
+/// Returns the blind, to keep for [`finalize`], and the blinded element
+/// for the server.
+pub fn blind<R: Rng + ?Sized>(
+    rng: &mut R,
+    mode: Mode,
+    input: &[u8],
+) -> Result<(Zeroizing<Fr>, Xsk233Affine), OprfError> {
+    let element = hash_to_group(mode, input)?;
+    let blind = Zeroizing::new(loop {
+        let r = Fr::rand(rng);
+        if !r.is_zero() {
+            break r;
+        }
+    });
+    let blinded = (element * *blind).into_affine();
+    Ok((blind, blinded))
+}
+
+/// `BlindEvaluate` of the base mode.
+pub fn blind_evaluate(secret: &SecretKey, blinded: &Xsk233Affine) -> Xsk233Affine {
+    (*blinded * secret.scalar()).into_affine()
+}
+
+/// `BlindEvaluateBatch` of the verifiable mode: evaluates all blinded
+/// elements and proves that they were multiplied by the key of
+/// `secret.public_key()`.
+pub fn blind_evaluate_batch<R: Rng + ?Sized>(
+    rng: &mut R,
+    secret: &SecretKey,
+    blinded: &[Xsk233Affine],
+) -> (Vec<Xsk233Affine>, DleqProof) {
+    let evaluated = blinded.iter().map(|b| blind_evaluate(secret, b)).collect();
+    let proof = DleqProof::prove_batch(rng, secret.scalar(), &Xsk233Affine::generator(), blinded);
+    (evaluated, proof)
+}
+
+/// `Finalize` of the base mode: unblinds the evaluated element and hashes
+/// it with the input. Fails if the blind is zero or the input is too long
+/// for its length to be encoded on two bytes.
+pub fn finalize(
+    input: &[u8],
+    blind: &Fr,
+    evaluated: &Xsk233Affine,
+) -> Result<[u8; OUTPUT_SIZE], OprfError> {
+    let inverse = blind.inverse().ok_or(OprfError::InvalidBlind)?;
+    output(input, &(*evaluated * inverse).into_affine())
+}
+
+/// `Finalize` of the verifiable mode for a batch: checks the proof of the
+/// server for `public`, then finalizes every input.
+///
+/// # Panics
+///
+/// If `inputs`, `blinds` and `blinded` have different lengths.
+pub fn finalize_batch(
+    public: &Xsk233Affine,
+    inputs: &[&[u8]],
+    blinds: &[Zeroizing<Fr>],
+    blinded: &[Xsk233Affine],
+    evaluated: &[Xsk233Affine],
+    proof: &DleqProof,
+) -> Result<Vec<[u8; OUTPUT_SIZE]>, OprfError> {
+    assert!(inputs.len() == blinds.len() && inputs.len() == blinded.len());
+    if !proof.verify_batch(&Xsk233Affine::generator(), public, blinded, evaluated) {
+        return Err(OprfError::Verify);
+    }
+    inputs
+        .iter()
+        .zip(blinds)
+        .zip(evaluated)
+        .map(|((input, blind), evaluated)| finalize(input, blind, evaluated))
+        .collect()
+}
+
+/// `Evaluate`: computes the PRF directly on the server.
+pub fn evaluate(
+    mode: Mode,
+    secret: &SecretKey,
+    input: &[u8],
+) -> Result<[u8; OUTPUT_SIZE], OprfError> {
+    let element = hash_to_group(mode, input)?;
+    output(input, &(element * secret.scalar()).into_affine())
+}
+
+fn hash_to_group(mode: Mode, input: &[u8]) -> Result<Xsk233Affine, OprfError> {
+    input_len(input)?;
+    let element = hash_to_curve(&mode.dst(b"HashToGroup-"), input);
+    if element.is_zero() {
+        return Err(OprfError::InvalidInput);
+    }
+    Ok(element)
+}
+
+/// `Hash(len(input) || input || len(element) || element || "Finalize")`.
+fn output(input: &[u8], element: &Xsk233Affine) -> Result<[u8; OUTPUT_SIZE], OprfError> {
+    let element = element.to_bytes();
+    Ok(Sha256::new()
+        .chain_update(input_len(input)?.to_be_bytes())
+        .chain_update(input)
+        .chain_update((element.len() as u16).to_be_bytes())
+        .chain_update(element)
+        .chain_update(b"Finalize")
+        .finalize()
+        .into())
+}
+
+/// Length of `input`, which the RFC encodes on two bytes.
+fn input_len(input: &[u8]) -> Result<u16, OprfError> {
+    u16::try_from(input.len()).map_err(|_| OprfError::InvalidInput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_oprf() {
+        let mut rng = test_rng();
+        let secret = derive_key_pair(Mode::Oprf, &[7; 32], b"server").unwrap();
+        assert_eq!(
+            derive_key_pair(Mode::Oprf, &[7; 32], b"server")
+                .unwrap()
+                .scalar(),
+            secret.scalar()
+        );
+        assert_ne!(
+            derive_key_pair(Mode::Voprf, &[7; 32], b"server")
+                .unwrap()
+                .scalar(),
+            secret.scalar()
+        );
+
+        let (blind_1, blinded_1) = blind(&mut rng, Mode::Oprf, b"hunter2").unwrap();
+        let (blind_2, blinded_2) = blind(&mut rng, Mode::Oprf, b"hunter2").unwrap();
+        // the server sees unrelated elements for the same input
+        assert_ne!(blinded_1, blinded_2);
+
+        let evaluated_1 = blind_evaluate(&secret, &blinded_1);
+        let output_1 = finalize(b"hunter2", &blind_1, &evaluated_1).unwrap();
+        let output_2 = finalize(b"hunter2", &blind_2, &blind_evaluate(&secret, &blinded_2));
+        assert_eq!(Ok(output_1), output_2);
+        assert_eq!(evaluate(Mode::Oprf, &secret, b"hunter2"), Ok(output_1));
+        assert_ne!(evaluate(Mode::Oprf, &secret, b"hunter3"), Ok(output_1));
+
+        let other = SecretKey::random(&mut rng);
+        assert_ne!(evaluate(Mode::Oprf, &other, b"hunter2"), Ok(output_1));
+        assert_eq!(
+            blind(&mut rng, Mode::Oprf, &vec![0; 1 << 16]).err(),
+            Some(OprfError::InvalidInput)
+        );
+        assert_eq!(
+            finalize(&vec![0; 1 << 16], &blind_1, &evaluated_1),
+            Err(OprfError::InvalidInput)
+        );
+        assert_eq!(
+            finalize(b"hunter2", &Fr::zero(), &evaluated_1),
+            Err(OprfError::InvalidBlind)
+        );
+    }
+
+    #[test]
+    fn test_voprf_batch() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let public = secret.public_key();
+        let inputs: [&[u8]; 3] = [b"alice", b"bob", b"carol"];
+
+        let (blinds, blinded): (Vec<_>, Vec<_>) = inputs
+            .iter()
+            .map(|input| blind(&mut rng, Mode::Voprf, input).unwrap())
+            .unzip();
+        let (evaluated, proof) = blind_evaluate_batch(&mut rng, &secret, &blinded);
+        let outputs =
+            finalize_batch(&public, &inputs, &blinds, &blinded, &evaluated, &proof).unwrap();
+        for (input, output) in inputs.iter().zip(&outputs) {
+            assert_eq!(evaluate(Mode::Voprf, &secret, input), Ok(*output));
+        }
+
+        // evaluations under another key, or swapped, are rejected
+        let other = SecretKey::random(&mut rng);
+        let (forged, forged_proof) = blind_evaluate_batch(&mut rng, &other, &blinded);
+        assert_eq!(
+            finalize_batch(&public, &inputs, &blinds, &blinded, &forged, &forged_proof),
+            Err(OprfError::Verify)
+        );
+        let mut swapped = evaluated.clone();
+        swapped.swap(0, 1);
+        assert_eq!(
+            finalize_batch(&public, &inputs, &blinds, &blinded, &swapped, &proof),
+            Err(OprfError::Verify)
+        );
+    }
+}
@@ -0,0 +1,774 @@
+//! Point types shared by the xs233 curves.
+//!
+//! xsk233 and xsb233 expose the same C interface over their own opaque point
+//! structures, so their affine and projective types only differ by the
+//! binding functions they call and are generated from the macros below.
+//! Curve-specific functionality lives next to the invocations, and `record`
+//! names the macro counting operations for the `metrics` feature.
+
+/// Expands to the affine point type of an xs233 curve.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! impl_xs233_affine {
+    (
+        $affine:ident,
+        $projective:ident,
+        $config:ident,
+        point: $point:ident,
+        decode: $decode:ident,
+        encode: $encode:ident,
+        equals: $equals:ident,
+        generator: $generator:ident,
+        neutral: $neutral:ident,
+        neg: $neg:ident,
+        mul: $mul:ident,
+        record: $record:ident,
+        $(#[$xy_attr:meta])*
+    ) => {
+        /// Affine coordinates for a point on an elliptic curve in short Weierstrass
+        /// form, over the base field `P::BaseField`.
+        #[derive(educe::Educe)]
+        #[educe(Copy, Clone)]
+        #[must_use]
+        pub struct $affine(xs233_sys::$point);
+
+        const _: () = {
+            use $crate::affine::{C_XSK233_EQUALS_TRUE, COMPRESSED_POINT_SIZE};
+            use ark_ec::{AffineRepr, CurveConfig, CurveGroup, PrimeGroup};
+            use ark_ff::{PrimeField, ToConstraintField};
+            use ark_serialize::{
+                CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid,
+                Validate,
+            };
+            use ark_std::{
+                borrow::Borrow,
+                fmt::{Debug, Display, Formatter, Result as FmtResult},
+                io::{Read, Write},
+                ops::{Add, Mul, Neg, Sub},
+                rand::{
+                    Rng,
+                    distributions::{Distribution, Standard},
+                },
+                vec::*,
+            };
+            use std::hash::{Hash, Hasher};
+            use std::os::raw::c_void;
+            use std::sync::atomic;
+            use std::{fmt, ptr};
+            use xs233_sys::{$decode, $encode, $equals, $generator, $mul, $neg, $neutral, $point};
+            use zeroize::Zeroize;
+
+            impl $affine {
+                pub fn new_unchecked(point: $point) -> Self {
+                    Self(point)
+                }
+
+                pub fn inner(&self) -> &$point {
+                    &self.0
+                }
+
+                pub fn into_inner(self) -> $point {
+                    self.0
+                }
+
+                /// Returns the canonical 30-byte encoding of the point.
+                pub fn to_bytes(&self) -> [u8; COMPRESSED_POINT_SIZE] {
+                    let mut dst = [0u8; COMPRESSED_POINT_SIZE];
+                    $record!(Encode);
+                    unsafe {
+                        $encode(dst.as_mut_ptr() as *mut c_void, &self.0);
+                    }
+                    dst
+                }
+
+                /// Decodes a point from its canonical 30-byte encoding.
+                pub fn from_bytes(bytes: &[u8; COMPRESSED_POINT_SIZE]) -> Option<Self> {
+                    $record!(Decode);
+                    unsafe {
+                        let mut result = $neutral;
+                        let success = $decode(&mut result, bytes.as_ptr() as *const c_void);
+                        (success != 0).then_some(Self(result))
+                    }
+                }
+            }
+
+            impl Eq for $affine {}
+
+            impl PartialEq<Self> for $affine {
+                fn eq(&self, other: &Self) -> bool {
+                    self.into_group() == other.into_group()
+                }
+            }
+
+            impl PartialEq<$projective> for $affine {
+                fn eq(&self, other: &$projective) -> bool {
+                    $record!(Equals);
+                    unsafe { C_XSK233_EQUALS_TRUE == $equals(self.inner(), other.inner()) }
+                }
+            }
+
+            impl Hash for $affine {
+                /// Hashes the canonical encoding, consistently with the
+                /// projective type.
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    Hash::hash(&self.to_bytes(), state);
+                }
+            }
+
+            impl Display for $affine {
+                fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+                    let mut ser = Vec::new();
+                    self.serialize_compressed(&mut ser)
+                        .map_err(|_| fmt::Error)?;
+
+                    write!(f, "{}", hex::encode(ser))
+                }
+            }
+
+            impl Debug for $affine {
+                fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+                    let mut ser = Vec::new();
+                    self.serialize_compressed(&mut ser)
+                        .map_err(|_| fmt::Error)?;
+
+                    write!(f, "{}", hex::encode(ser))
+                }
+            }
+
+            impl Zeroize for $affine {
+                /// Overwrites the point with the neutral element.
+                fn zeroize(&mut self) {
+                    unsafe {
+                        ptr::write_volatile(&mut self.0, $neutral);
+                    }
+                    atomic::compiler_fence(atomic::Ordering::SeqCst);
+                }
+            }
+
+            impl Distribution<$affine> for Standard {
+                /// Generates a uniformly random instance of the curve.
+                #[inline]
+                fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> $affine {
+                    let rand: $projective = rng.r#gen();
+                    rand.into()
+                }
+            }
+
+            impl AffineRepr for $affine {
+                type Config = $config;
+                type BaseField = <$config as CurveConfig>::BaseField;
+                type ScalarField = <$config as CurveConfig>::ScalarField;
+                type Group = $projective;
+
+                /// Always returns `None`: the coordinates live in GF(2^233), which
+                /// `BaseField` only stands in for, so no meaningful value exists.
+                $(#[$xy_attr])*
+                fn xy(&self) -> Option<(Self::BaseField, Self::BaseField)> {
+                    None
+                }
+
+                fn is_zero(&self) -> bool {
+                    $record!(Equals);
+                    unsafe { C_XSK233_EQUALS_TRUE == $equals(&$neutral, &self.0) }
+                }
+
+                #[inline]
+                fn generator() -> Self {
+                    unsafe { Self($generator) }
+                }
+
+                fn zero() -> Self {
+                    unsafe { Self($neutral) }
+                }
+
+                fn from_random_bytes(bytes: &[u8]) -> Option<Self> {
+                    if let Ok(p) = $projective::deserialize_compressed(bytes) {
+                        return Some(p.into_affine());
+                    }
+
+                    None
+                }
+
+                fn mul_bigint(&self, by: impl AsRef<[u64]>) -> Self::Group {
+                    self.into_group().mul_bigint(by)
+                }
+
+                /// Multiplies this element by the cofactor and output the
+                /// resulting projective element.
+                fn mul_by_cofactor_to_group(&self) -> Self::Group {
+                    self.mul(Self::ScalarField::from(
+                        *Self::Config::COFACTOR.first().unwrap(),
+                    ))
+                }
+
+                /// Performs cofactor clearing.
+                /// The default method is simply to multiply by the cofactor.
+                /// Some curves can implement a more efficient algorithm.
+                fn clear_cofactor(&self) -> Self {
+                    self.mul_by_cofactor_to_group().into_affine()
+                }
+            }
+
+            impl Neg for $affine {
+                type Output = Self;
+
+                /// If `self.is_zero()`, returns `self` (`== Self::zero()`).
+                /// Else, returns `(x, -y)`, where `self = (x, y)`.
+                #[inline]
+                fn neg(mut self) -> Self {
+                    $record!(Neg);
+                    unsafe {
+                        $neg(&mut self.0, &self.0);
+                        self
+                    }
+                }
+            }
+
+            impl<T: Borrow<Self>> Add<T> for $affine {
+                type Output = $projective;
+                fn add(self, other: T) -> $projective {
+                    let mut copy = self.into_group();
+                    copy += other.borrow();
+                    copy
+                }
+            }
+
+            impl Add<$projective> for $affine {
+                type Output = $projective;
+                fn add(self, other: $projective) -> $projective {
+                    other + self
+                }
+            }
+
+            impl<'a> Add<&'a $projective> for $affine {
+                type Output = $projective;
+                fn add(self, other: &'a $projective) -> $projective {
+                    *other + self
+                }
+            }
+
+            impl<T: Borrow<Self>> Sub<T> for $affine {
+                type Output = $projective;
+                fn sub(self, other: T) -> $projective {
+                    let mut copy = self.into_group();
+                    copy -= other.borrow();
+                    copy
+                }
+            }
+
+            impl Sub<$projective> for $affine {
+                type Output = $projective;
+                fn sub(self, other: $projective) -> $projective {
+                    self + (-other)
+                }
+            }
+
+            impl<'a> Sub<&'a $projective> for $affine {
+                type Output = $projective;
+                fn sub(self, other: &'a $projective) -> $projective {
+                    self + (-*other)
+                }
+            }
+
+            impl Default for $affine {
+                #[inline]
+                fn default() -> Self {
+                    unsafe { Self($neutral) }
+                }
+            }
+
+            impl<T: Borrow<<$config as CurveConfig>::ScalarField>> Mul<T> for $affine {
+                type Output = $projective;
+
+                #[inline]
+                fn mul(self, other: T) -> Self::Output {
+                    $record!(ScalarMul);
+                    unsafe {
+                        let scalar_bytes = $crate::bigint_to_le_bytes(other.borrow().into_bigint());
+                        let mut result = $neutral;
+                        $mul(
+                            &mut result,
+                            &self.0,
+                            scalar_bytes.as_ptr() as *const _,
+                            scalar_bytes.len(),
+                        );
+
+                        Self::Output::new_unchecked(result)
+                    }
+                }
+            }
+
+            impl From<$projective> for $affine {
+                #[inline]
+                fn from(p: $projective) -> $affine {
+                    Self(p.into_inner())
+                }
+            }
+
+            /// Both [`Compress`] modes write the canonical 30-byte encoding, which is
+            /// already compressed: there is no separate uncompressed form, and
+            /// `Compress::No` reads and writes the same bytes as `Compress::Yes`.
+            impl CanonicalSerialize for $affine {
+                #[inline]
+                fn serialize_with_mode<W: Write>(
+                    &self,
+                    mut writer: W,
+                    _compress: Compress,
+                ) -> Result<(), SerializationError> {
+                    writer.write_all(&self.to_bytes())?;
+                    Ok(())
+                }
+
+                #[inline]
+                fn serialized_size(&self, _compress: Compress) -> usize {
+                    COMPRESSED_POINT_SIZE
+                }
+            }
+
+            impl Valid for $affine {
+                fn check(&self) -> Result<(), SerializationError> {
+                    // it is assumed that all points are created from :
+                    // a. decode function
+                    // b. multipling by a scalar a point like generator point.
+                    //
+                    // Option b is valid by nature.
+                    // Option a has a really intricate mechanism of rejecting invalid points.
+                    Ok(())
+                }
+            }
+
+            impl CanonicalDeserialize for $affine {
+                fn deserialize_with_mode<R: Read>(
+                    reader: R,
+                    compress: Compress,
+                    validate: Validate,
+                ) -> Result<Self, SerializationError> {
+                    $projective::deserialize_with_mode(reader, compress, validate)
+                        .map(|p| p.into_affine())
+                }
+            }
+
+            impl<ConstraintF: PrimeField> ToConstraintField<ConstraintF> for $affine {
+                /// Packs the canonical encoding into field elements.
+                #[inline]
+                fn to_field_elements(&self) -> Option<Vec<ConstraintF>> {
+                    self.to_bytes().to_field_elements()
+                }
+            }
+        };
+    };
+}
+
+/// Expands to the projective point type of an xs233 curve, whose affine
+/// type is generated by [`impl_xs233_affine`].
+#[macro_export]
+#[doc(hidden)]
+macro_rules! impl_xs233_projective {
+    (
+        $projective:ident,
+        $affine:ident,
+        $config:ident,
+        point: $point:ident,
+        decode: $decode:ident,
+        encode: $encode:ident,
+        equals: $equals:ident,
+        neutral: $neutral:ident,
+        neg: $neg:ident,
+        add: $add:ident,
+        sub: $sub:ident,
+        double: $double:ident,
+        mul: $mul:ident,
+        record: $record:ident $(,)?
+    ) => {
+        #[derive(educe::Educe)]
+        #[educe(Copy, Clone)]
+        #[must_use]
+        pub struct $projective(xs233_sys::$point);
+
+        const _: () = {
+            use ark_ec::{AffineRepr, CurveConfig, CurveGroup, PrimeGroup, ScalarMul};
+            use ark_ff::{AdditiveGroup, PrimeField, ToConstraintField};
+            use ark_serialize::{
+                CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid,
+                Validate,
+            };
+            use ark_std::{
+                UniformRand, Zero,
+                borrow::Borrow,
+                fmt::{Debug, Display, Formatter, Result as FmtResult},
+                hash::{Hash, Hasher},
+                io::{Read, Write},
+                ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+                rand::{
+                    Rng,
+                    distributions::{Distribution, Standard},
+                },
+                vec::*,
+            };
+            use std::io;
+            use std::os::raw::c_void;
+            use std::ptr;
+            use std::sync::atomic;
+            use xs233_sys::{
+                $add, $decode, $double, $encode, $equals, $mul, $neg, $neutral, $point, $sub,
+            };
+            use $crate::affine::{C_XSK233_EQUALS_TRUE, COMPRESSED_POINT_SIZE};
+            use zeroize::Zeroize;
+
+            type ScalarField = <$config as CurveConfig>::ScalarField;
+
+            impl $projective {
+                pub fn new_unchecked(point: $point) -> Self {
+                    Self(point)
+                }
+
+                pub fn inner(&self) -> &$point {
+                    &self.0
+                }
+
+                pub fn into_inner(self) -> $point {
+                    self.0
+                }
+
+                /// Returns the canonical 30-byte encoding of the point.
+                pub fn to_bytes(&self) -> [u8; COMPRESSED_POINT_SIZE] {
+                    let mut dst = [0u8; COMPRESSED_POINT_SIZE];
+                    $record!(Encode);
+                    unsafe {
+                        $encode(dst.as_mut_ptr() as *mut c_void, &self.0);
+                    }
+                    dst
+                }
+
+                const fn zero() -> $projective {
+                    unsafe { Self($neutral) }
+                }
+            }
+
+            impl Display for $projective {
+                fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+                    write!(f, "{}", $affine::from(*self))
+                }
+            }
+
+            impl Debug for $projective {
+                fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+                    write!(f, "{}", $affine::from(*self))
+                }
+            }
+
+            impl Eq for $projective {}
+            impl PartialEq for $projective {
+                fn eq(&self, other: &Self) -> bool {
+                    $record!(Equals);
+                    unsafe { $equals(self.inner(), other.inner()) == C_XSK233_EQUALS_TRUE }
+                }
+            }
+
+            impl PartialEq<$affine> for $projective {
+                fn eq(&self, other: &$affine) -> bool {
+                    $record!(Equals);
+                    unsafe { $equals(self.inner(), other.inner()) == C_XSK233_EQUALS_TRUE }
+                }
+            }
+
+            impl Hash for $projective {
+                /// Hashes the canonical encoding, so that equal points hash equally
+                /// regardless of their internal representation or type.
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    Hash::hash(&self.to_bytes(), state);
+                }
+            }
+
+            impl Zeroize for $projective {
+                /// Overwrites the point with the neutral element.
+                fn zeroize(&mut self) {
+                    unsafe {
+                        ptr::write_volatile(&mut self.0, $neutral);
+                    }
+                    atomic::compiler_fence(atomic::Ordering::SeqCst);
+                }
+            }
+
+            impl Distribution<$projective> for Standard {
+                /// Generates a uniformly random instance of the curve.
+                #[inline]
+                fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> $projective {
+                    $projective::generator() * ScalarField::rand(rng)
+                }
+            }
+
+            impl Default for $projective {
+                #[inline]
+                fn default() -> Self {
+                    $projective::zero()
+                }
+            }
+
+            impl Zero for $projective {
+                /// Returns the point at infinity, which always has Z = 0.
+                #[inline]
+                fn zero() -> Self {
+                    unsafe { Self::new_unchecked($neutral) }
+                }
+
+                /// Checks whether `self.z.is_zero()`.
+                #[inline]
+                fn is_zero(&self) -> bool {
+                    self == &Self::zero()
+                }
+            }
+
+            impl AdditiveGroup for $projective {
+                type Scalar = ScalarField;
+
+                const ZERO: Self = $projective::zero();
+
+                fn double_in_place(&mut self) -> &mut Self {
+                    $record!(Double);
+                    unsafe {
+                        $double(&mut self.0, &self.0);
+                        self
+                    }
+                }
+            }
+
+            impl PrimeGroup for $projective {
+                type ScalarField = ScalarField;
+
+                #[inline]
+                fn generator() -> Self {
+                    $affine::generator().into()
+                }
+
+                #[inline]
+                fn mul_bigint(&self, other: impl AsRef<[u64]>) -> Self {
+                    let words = other.as_ref();
+
+                    // Convert to bytes in little-endian order
+                    let mut bytes = Vec::with_capacity(words.len() * 8);
+                    for word in words {
+                        bytes.extend_from_slice(&word.to_le_bytes());
+                    }
+
+                    let scalar = Self::ScalarField::from_le_bytes_mod_order(&bytes);
+                    self.mul(scalar)
+                }
+            }
+
+            impl CurveGroup for $projective {
+                type Config = $config;
+                type BaseField = <$config as CurveConfig>::BaseField;
+                type Affine = $affine;
+                type FullGroup = $affine;
+
+                /// Converts a slice of projective elements to affine ones. Both types
+                /// wrap the same point structure, so no normalization is needed.
+                #[inline]
+                fn normalize_batch(v: &[Self]) -> Vec<Self::Affine> {
+                    v.iter().map(|p| (*p).into()).collect()
+                }
+            }
+
+            impl Neg for $projective {
+                type Output = Self;
+
+                #[inline]
+                fn neg(mut self) -> Self {
+                    $record!(Neg);
+                    unsafe {
+                        $neg(&mut self.0, &self.0);
+                        self
+                    }
+                }
+            }
+
+            impl<T: Borrow<$affine>> AddAssign<T> for $projective {
+                fn add_assign(&mut self, other: T) {
+                    $record!(Add);
+                    unsafe {
+                        $add(&mut self.0, &self.0, other.borrow().inner());
+                    }
+                }
+            }
+
+            impl<T: Borrow<$affine>> Add<T> for $projective {
+                type Output = Self;
+                fn add(mut self, other: T) -> Self {
+                    let other = other.borrow();
+                    self += other;
+                    self
+                }
+            }
+
+            impl<T: Borrow<$affine>> SubAssign<T> for $projective {
+                fn sub_assign(&mut self, other: T) {
+                    $record!(Add);
+                    unsafe {
+                        $sub(&mut self.0, &self.0, other.borrow().inner());
+                    }
+                }
+            }
+
+            impl<T: Borrow<$affine>> Sub<T> for $projective {
+                type Output = Self;
+                fn sub(mut self, other: T) -> Self {
+                    self -= other.borrow();
+                    self
+                }
+            }
+
+            $crate::impl_additive_ops_from_ref!($projective);
+
+            impl<'a> Add<&'a Self> for $projective {
+                type Output = Self;
+
+                #[inline]
+                fn add(mut self, other: &'a Self) -> Self {
+                    self += other;
+                    self
+                }
+            }
+
+            impl<'a> AddAssign<&'a Self> for $projective {
+                fn add_assign(&mut self, other: &'a Self) {
+                    $record!(Add);
+                    unsafe {
+                        $add(&mut self.0, &self.0, other.inner());
+                    }
+                }
+            }
+
+            impl<'a> Sub<&'a Self> for $projective {
+                type Output = Self;
+
+                #[inline]
+                fn sub(mut self, other: &'a Self) -> Self {
+                    self -= other;
+                    self
+                }
+            }
+
+            impl<'a> SubAssign<&'a Self> for $projective {
+                fn sub_assign(&mut self, other: &'a Self) {
+                    *self += &(-(*other));
+                }
+            }
+
+            impl<T: Borrow<ScalarField>> MulAssign<T> for $projective {
+                fn mul_assign(&mut self, other: T) {
+                    $record!(ScalarMul);
+                    unsafe {
+                        let scalar_bytes = $crate::bigint_to_le_bytes(other.borrow().into_bigint());
+                        $mul(
+                            &mut self.0,
+                            &self.0,
+                            scalar_bytes.as_ptr() as *const _,
+                            scalar_bytes.len(),
+                        );
+                    }
+                }
+            }
+
+            impl<T: Borrow<ScalarField>> Mul<T> for $projective {
+                type Output = Self;
+
+                #[inline]
+                fn mul(mut self, other: T) -> Self {
+                    self *= other;
+                    self
+                }
+            }
+
+            impl From<$affine> for $projective {
+                #[inline]
+                fn from(p: $affine) -> $projective {
+                    Self(p.into_inner())
+                }
+            }
+
+            /// Both [`Compress`] modes write the canonical 30-byte encoding, which is
+            /// already compressed: there is no separate uncompressed form, and
+            /// `Compress::No` reads and writes the same bytes as `Compress::Yes`.
+            impl CanonicalSerialize for $projective {
+                #[inline]
+                fn serialize_with_mode<W: Write>(
+                    &self,
+                    mut writer: W,
+                    _compress: Compress,
+                ) -> Result<(), SerializationError> {
+                    writer.write_all(&self.to_bytes())?;
+                    Ok(())
+                }
+
+                #[inline]
+                fn serialized_size(&self, _compress: Compress) -> usize {
+                    COMPRESSED_POINT_SIZE
+                }
+            }
+
+            impl CanonicalDeserialize for $projective {
+                fn deserialize_with_mode<R: Read>(
+                    mut reader: R,
+                    _compress: Compress,
+                    _validate: Validate,
+                ) -> Result<Self, SerializationError> {
+                    let mut bytes = [0; COMPRESSED_POINT_SIZE];
+                    reader.read_exact(&mut bytes)?;
+
+                    $record!(Decode);
+                    unsafe {
+                        let mut result = $neutral;
+                        let success = $decode(&mut result, bytes.as_ptr() as *mut c_void);
+                        if success == 0 {
+                            return Err(SerializationError::IoError(io::Error::other(
+                                "failed to deserialize",
+                            )));
+                        }
+
+                        Ok(Self(result))
+                    }
+                }
+            }
+
+            impl Valid for $projective {
+                fn check(&self) -> Result<(), SerializationError> {
+                    self.into_affine().check()
+                }
+
+                fn batch_check<'a>(
+                    batch: impl Iterator<Item = &'a Self> + Send,
+                ) -> Result<(), SerializationError>
+                where
+                    Self: 'a,
+                {
+                    let batch = batch.copied().collect::<Vec<_>>();
+                    let batch = Self::normalize_batch(&batch);
+                    $affine::batch_check(batch.iter())
+                }
+            }
+
+            impl<ConstraintF: PrimeField> ToConstraintField<ConstraintF> for $projective {
+                #[inline]
+                fn to_field_elements(&self) -> Option<Vec<ConstraintF>> {
+                    $affine::from(*self).to_field_elements()
+                }
+            }
+
+            impl ScalarMul for $projective {
+                type MulBase = $affine;
+                const NEGATION_IS_CHEAP: bool = true;
+
+                fn batch_convert_to_mul_base(bases: &[Self]) -> Vec<Self::MulBase> {
+                    Self::normalize_batch(bases)
+                }
+            }
+
+            impl<T: Borrow<$affine>> core::iter::Sum<T> for $projective {
+                fn sum<I: Iterator<Item = T>>(iter: I) -> Self {
+                    iter.fold($projective::zero(), |sum, x| sum + x.borrow())
+                }
+            }
+        };
+    };
+}
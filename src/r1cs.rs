@@ -0,0 +1,162 @@
+//! R1CS gadgets for xsk233, behind the `r1cs` feature.
+//!
+//! [`Gf2_233Var`] is a GF(2^233) element as 233 [`Boolean`]s over any prime
+//! constraint field, and [`Xsk233Var`] implements [`CurveVar`] for
+//! [`Xsk233Projective`] on top of it, so xsk233 keys can be used in
+//! arkworks SNARKs over e.g. BN254 or BLS12-381.
+//!
+//! Binary field additions are XORs, one constraint per bit, and squarings
+//! are linear. A multiplication computes the polynomial product over the
+//! integers with Karatsuba, one constraint per base product, and extracts
+//! the parity of each reduced coefficient. Points use the extended
+//! `(X, S, Z, T)` coordinates of xs233, whose formulas are complete.
+//!
+//! | gadget                          | constraints |
+//! |---------------------------------|-------------|
+//! | field multiplication            |       8 644 |
+//! | point addition                  |      61 979 |
+//! | point doubling                  |      27 709 |
+//! | allocation (decoding)           |      37 290 |
+//! | encoding                        |       9 501 |
+//! | scalar multiplication, per bit  |      90 620 |
+//!
+//! Allocating a point witnesses its 233-bit encoding and decodes it in the
+//! circuit, which also proves that it is a group element; since the xsk233
+//! group has prime order, no subgroup check is needed on top of that.
+//!
+//! [`Boolean`]: ark_r1cs_std::prelude::Boolean
+//! [`CurveVar`]: ark_r1cs_std::prelude::CurveVar
+//! [`Xsk233Projective`]: crate::group::Xsk233Projective
+
+mod field;
+mod point;
+
+pub use field::Gf2_233Var;
+pub use point::Xsk233Var;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::affine::Xsk233Affine;
+    use crate::gf233::Gf233;
+    use crate::group::Xsk233Projective;
+    use crate::xsk233::Fr;
+    use ark_bn254::Fr as ConstraintF;
+    use ark_ec::{AffineRepr, PrimeGroup};
+    use ark_ff::{ToConstraintField, UniformRand};
+    use ark_r1cs_std::convert::ToConstraintFieldGadget;
+    use ark_r1cs_std::prelude::*;
+    use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef};
+    use ark_std::test_rng;
+
+    fn rand_element<R: ark_std::rand::Rng>(rng: &mut R) -> Gf233 {
+        let mut bits = [false; 233];
+        bits.iter_mut().for_each(|b| *b = rng.r#gen());
+        Gf233::from_bits(&bits)
+    }
+
+    fn witness(
+        cs: &ConstraintSystemRef<ConstraintF>,
+        p: Xsk233Projective,
+    ) -> Xsk233Var<ConstraintF> {
+        Xsk233Var::new_witness(cs.clone(), || Ok(p)).unwrap()
+    }
+
+    #[test]
+    fn test_field_operations() {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        for _ in 0..4 {
+            let (a, b) = (rand_element(&mut rng), rand_element(&mut rng));
+            let a_var = Gf2_233Var::new_witness(cs.clone(), || Ok(a)).unwrap();
+            let b_var = Gf2_233Var::new_witness(cs.clone(), || Ok(b)).unwrap();
+
+            assert_eq!(a_var.add(&b_var).unwrap().value().unwrap(), a + b);
+            assert_eq!(a_var.mul(&b_var).unwrap().value().unwrap(), a * b);
+            assert_eq!(a_var.square().unwrap().value().unwrap(), a.square());
+            assert_eq!(a_var.inverse().unwrap().value().unwrap(), a.inverse());
+            assert_eq!(a_var.trace().unwrap().value().unwrap(), a.trace());
+
+            // products with a constant operand
+            let c = Gf2_233Var::constant(b);
+            assert_eq!(a_var.mul(&c).unwrap().value().unwrap(), a * b);
+        }
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_point_operations() {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let p = Xsk233Projective::rand(&mut rng);
+        let q = Xsk233Projective::rand(&mut rng);
+        let p_var = witness(&cs, p);
+        let q_var = witness(&cs, q);
+
+        assert_eq!((&p_var + &q_var).value().unwrap(), p + q);
+        assert_eq!((&p_var - &q_var).value().unwrap(), p - q);
+        assert_eq!(p_var.double().unwrap().value().unwrap(), p + p);
+        assert_eq!(p_var.negate().unwrap().value().unwrap(), -p);
+        assert_eq!((p_var.clone() + q).value().unwrap(), p + q);
+
+        // equality across representations, including the neutral element
+        (&p_var + &p_var)
+            .enforce_equal(&p_var.double().unwrap())
+            .unwrap();
+        let zero = &p_var - &p_var;
+        assert!(zero.is_zero().unwrap().value().unwrap());
+        assert!(!p_var.is_eq(&q_var).unwrap().value().unwrap());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let g = Xsk233Projective::generator();
+        let g_var = witness(&cs, g);
+
+        let k = 0b1011u8;
+        let bits = Vec::<Boolean<_>>::new_witness(cs.clone(), || {
+            Ok((0..4).map(|i| (k >> i) & 1 == 1).collect::<Vec<_>>())
+        })
+        .unwrap();
+        let res = g_var.scalar_mul_le(bits.iter()).unwrap();
+        assert_eq!(res.value().unwrap(), g * Fr::from(k));
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let p = Xsk233Affine::rand(&mut rng);
+        let p_var = Xsk233Var::new_witness(cs.clone(), || Ok(p)).unwrap();
+
+        let bytes = p_var.to_bytes_le().unwrap();
+        assert_eq!(bytes.value().unwrap(), p.to_bytes());
+        assert_eq!(
+            p_var.to_constraint_field().unwrap().value().unwrap(),
+            p.to_field_elements().unwrap()
+        );
+
+        let decoded = Xsk233Var::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.value().unwrap(), p);
+
+        // the neutral element encodes as zero
+        let zero = Xsk233Var::new_witness(cs.clone(), || Ok(Xsk233Projective::default())).unwrap();
+        assert_eq!(zero.to_bytes_le().unwrap().value().unwrap(), [0; 30]);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_encoding() {
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let mut bytes = Xsk233Affine::generator().to_bytes();
+        while Xsk233Affine::from_bytes(&bytes).is_some() {
+            bytes[0] = bytes[0].wrapping_add(1);
+        }
+        let bytes = UInt8::new_witness_vec(cs.clone(), &bytes).unwrap();
+        Xsk233Var::from_bytes(&bytes).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
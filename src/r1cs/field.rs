@@ -0,0 +1,281 @@
+use crate::gf233::{GF233_BITS, Gf233};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+
+/// Degree of the middle term of z^233 + z^74 + 1.
+const MODULUS_MIDDLE: usize = 74;
+
+/// Element of GF(2^233) as [`GF233_BITS`] booleans, lowest degree first.
+///
+/// The bits are kept reduced: every bit is a [`Boolean`], so the element
+/// can be compared, selected or exported without further constraints.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug)]
+pub struct Gf2_233Var<F: PrimeField> {
+    bits: Vec<Boolean<F>>,
+}
+
+impl<F: PrimeField> Gf2_233Var<F> {
+    /// The element with value `value`. No variables are allocated.
+    pub fn constant(value: Gf233) -> Self {
+        Self {
+            bits: value.to_bits().into_iter().map(Boolean::constant).collect(),
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::constant(Gf233::ZERO)
+    }
+
+    pub fn one() -> Self {
+        Self::constant(Gf233::ONE)
+    }
+
+    /// The element 1 if `bit` is set and 0 otherwise.
+    pub fn from_boolean(bit: &Boolean<F>) -> Self {
+        let mut bits = vec![Boolean::FALSE; GF233_BITS];
+        bits[0] = bit.clone();
+        Self { bits }
+    }
+
+    /// Builds an element from its [`GF233_BITS`] coefficients, lowest
+    /// degree first.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` does not have exactly [`GF233_BITS`] entries.
+    pub fn from_bits_le(bits: &[Boolean<F>]) -> Self {
+        assert_eq!(bits.len(), GF233_BITS, "wrong number of bits");
+        Self {
+            bits: bits.to_vec(),
+        }
+    }
+
+    /// Addition, one constraint per bit where both operands are variables.
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let bits = self
+            .bits
+            .iter()
+            .zip(&other.bits)
+            .map(|(a, b)| a ^ b)
+            .collect();
+        Ok(Self { bits })
+    }
+
+    /// Multiplication.
+    ///
+    /// The polynomial product is computed over the integers with Karatsuba
+    /// multiplication, one constraint per base product, and reduced modulo
+    /// z^233 + z^74 + 1 for free. Each coefficient is then brought back to
+    /// a bit by decomposing it and keeping its parity, which needs the
+    /// constraint field to hold integers up to a thousand exactly.
+    pub fn mul(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let a: Vec<_> = self.bits.iter().cloned().map(FpVar::from).collect();
+        let b: Vec<_> = other.bits.iter().cloned().map(FpVar::from).collect();
+        let product = poly_mul(&a, &b);
+
+        // c_k is the sum of the a_i b_j with i + j = k
+        let bounds = (0..product.len())
+            .map(|k| (k.min(product.len() - 1 - k) + 1) as u64)
+            .collect();
+        let (coeffs, bounds) = reduce(product, bounds);
+        let bits = coeffs
+            .iter()
+            .zip(bounds)
+            .map(|(c, bound)| parity(c, bound))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { bits })
+    }
+
+    /// Squaring, which is linear: the bits are spread to the even degrees
+    /// and reduced with XORs.
+    pub fn square(&self) -> Result<Self, SynthesisError> {
+        let mut spread = vec![Boolean::FALSE; 2 * GF233_BITS - 1];
+        for (i, bit) in self.bits.iter().enumerate() {
+            spread[2 * i] = bit.clone();
+        }
+        for i in (GF233_BITS..spread.len()).rev() {
+            let high = spread[i].clone();
+            spread[i - GF233_BITS] ^= &high;
+            spread[i - GF233_BITS + MODULUS_MIDDLE] ^= &high;
+        }
+        spread.truncate(GF233_BITS);
+        Ok(Self { bits: spread })
+    }
+
+    pub fn square_n(&self, n: usize) -> Result<Self, SynthesisError> {
+        let mut res = self.clone();
+        for _ in 0..n {
+            res = res.square()?;
+        }
+        Ok(res)
+    }
+
+    /// Inverse, as a witness checked with one multiplication. The
+    /// constraint system is unsatisfiable if `self` is zero.
+    pub fn inverse(&self) -> Result<Self, SynthesisError> {
+        if self.is_constant() {
+            return Ok(Self::constant(self.value()?.inverse()));
+        }
+        let inverse = Self::new_witness(self.cs(), || Ok(self.value()?.inverse()))?;
+        self.mul(&inverse)?.enforce_equal(&Self::one())?;
+        Ok(inverse)
+    }
+
+    /// Absolute trace, the coefficient of z^0 plus that of z^159.
+    pub fn trace(&self) -> Result<Boolean<F>, SynthesisError> {
+        Ok(&self.bits[0] ^ &self.bits[159])
+    }
+
+    pub fn is_zero(&self) -> Result<Boolean<F>, SynthesisError> {
+        Ok(!Boolean::kary_or(&self.bits)?)
+    }
+}
+
+/// Product of two polynomials of the same length `n` over the integers, of
+/// length `2n - 1`.
+fn poly_mul<F: PrimeField>(a: &[FpVar<F>], b: &[FpVar<F>]) -> Vec<FpVar<F>> {
+    let n = a.len();
+    debug_assert_eq!(n, b.len());
+
+    if n == 1 {
+        return vec![&a[0] * &b[0]];
+    }
+
+    // a = a0 + z^h a1, with a1 zero-padded to h coefficients for the middle
+    // product
+    let h = n.div_ceil(2);
+    let (a0, a1) = a.split_at(h);
+    let (b0, b1) = b.split_at(h);
+
+    let lo = poly_mul(a0, b0);
+    let hi = poly_mul(a1, b1);
+
+    let sum = |x: &[FpVar<F>], y: &[FpVar<F>]| {
+        (0..h)
+            .map(|i| y.get(i).map_or_else(|| x[i].clone(), |y| &x[i] + y))
+            .collect::<Vec<_>>()
+    };
+    let mut mid = poly_mul(&sum(a0, a1), &sum(b0, b1));
+
+    // mid = (a0 + a1)(b0 + b1) - a0 b0 - a1 b1
+    for (i, c) in lo.iter().enumerate() {
+        mid[i] -= c;
+    }
+    for (i, c) in hi.iter().enumerate() {
+        mid[i] -= c;
+    }
+
+    let mut c = vec![FpVar::zero(); 2 * n - 1];
+    c[..lo.len()].clone_from_slice(&lo);
+    c[2 * h..2 * h + hi.len()].clone_from_slice(&hi);
+    for (i, m) in mid.iter().enumerate() {
+        c[h + i] += m;
+    }
+    c
+}
+
+/// Reduces integer coefficients modulo z^233 + z^74 + 1, keeping track of
+/// an upper bound of each coefficient.
+fn reduce<F: PrimeField>(mut c: Vec<FpVar<F>>, mut bounds: Vec<u64>) -> (Vec<FpVar<F>>, Vec<u64>) {
+    for i in (GF233_BITS..c.len()).rev() {
+        let high = c[i].clone();
+        c[i - GF233_BITS] += &high;
+        c[i - GF233_BITS + MODULUS_MIDDLE] += &high;
+        bounds[i - GF233_BITS] += bounds[i];
+        bounds[i - GF233_BITS + MODULUS_MIDDLE] += bounds[i];
+    }
+    c.truncate(GF233_BITS);
+    bounds.truncate(GF233_BITS);
+    (c, bounds)
+}
+
+/// Parity of `value`, a non-negative integer at most `bound`.
+fn parity<F: PrimeField>(value: &FpVar<F>, bound: u64) -> Result<Boolean<F>, SynthesisError> {
+    if let FpVar::Constant(c) = value {
+        return Ok(Boolean::constant(c.into_bigint().is_odd()));
+    }
+
+    let cs = value.cs();
+    let int = value.value().map(|v| v.into_bigint().as_ref()[0]);
+    let bits = (0..u64::BITS - bound.leading_zeros())
+        .map(|i| Boolean::new_witness(cs.clone(), || int.map(|v| (v >> i) & 1 == 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp(&bits)?.enforce_equal(value)?;
+    Ok(bits[0].clone())
+}
+
+impl<F: PrimeField> R1CSVar<F> for Gf2_233Var<F> {
+    type Value = Gf233;
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.bits.cs()
+    }
+
+    fn value(&self) -> Result<Gf233, SynthesisError> {
+        let bits = self.bits.value()?;
+        Ok(Gf233::from_bits(
+            bits.as_slice().try_into().expect("233 bits"),
+        ))
+    }
+}
+
+impl<F: PrimeField> AllocVar<Gf233, F> for Gf2_233Var<F> {
+    fn new_variable<T: Borrow<Gf233>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let value = f().map(|v| *v.borrow());
+        let bits = (0..GF233_BITS)
+            .map(|i| Boolean::new_variable(cs.clone(), || value.map(|v| v.bit(i)), mode))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { bits })
+    }
+}
+
+impl<F: PrimeField> EqGadget<F> for Gf2_233Var<F> {
+    fn is_eq(&self, other: &Self) -> Result<Boolean<F>, SynthesisError> {
+        self.bits.is_eq(&other.bits)
+    }
+
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<F>,
+    ) -> Result<(), SynthesisError> {
+        self.bits
+            .conditional_enforce_equal(&other.bits, should_enforce)
+    }
+
+    fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        self.bits.enforce_equal(&other.bits)
+    }
+}
+
+impl<F: PrimeField> CondSelectGadget<F> for Gf2_233Var<F> {
+    fn conditionally_select(
+        cond: &Boolean<F>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let bits = true_value
+            .bits
+            .iter()
+            .zip(&false_value.bits)
+            .map(|(t, f)| cond.select(t, f))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { bits })
+    }
+}
+
+impl<F: PrimeField> ToBitsGadget<F> for Gf2_233Var<F> {
+    fn to_bits_le(&self) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        Ok(self.bits.clone())
+    }
+}
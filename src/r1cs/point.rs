@@ -0,0 +1,424 @@
+use super::field::Gf2_233Var;
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::gf233::{GF233_BITS, Gf233};
+use crate::group::Xsk233Projective;
+use crate::inner::{InnerPoint, decode_x};
+use crate::xsk233::Fr;
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_r1cs_std::convert::ToConstraintFieldGadget;
+use ark_r1cs_std::fields::emulated_fp::EmulatedFpVar;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+use ark_std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+
+/// xsk233 group element in the extended `(X, S, Z, T)` coordinates of
+/// xs233, over the prime constraint field `F`.
+///
+/// The addition and doubling formulas are complete, so no operation has
+/// special cases to select between.
+#[derive(Clone, Debug)]
+pub struct Xsk233Var<F: PrimeField> {
+    x: Gf2_233Var<F>,
+    s: Gf2_233Var<F>,
+    z: Gf2_233Var<F>,
+    t: Gf2_233Var<F>,
+}
+
+impl<F: PrimeField> Xsk233Var<F> {
+    fn from_inner(point: InnerPoint) -> Self {
+        let [x, s, z, t] = point.coordinates().map(Gf2_233Var::constant);
+        Self { x, s, z, t }
+    }
+
+    /// Decodes a compressed point, as `xsk233_decode()` does.
+    ///
+    /// The constraint system is unsatisfiable if `bytes` is not the
+    /// canonical encoding of a group element.
+    ///
+    /// # Panics
+    ///
+    /// If `bytes` is not [`COMPRESSED_POINT_SIZE`] bytes long.
+    pub fn from_bytes(bytes: &[UInt8<F>]) -> Result<Self, SynthesisError> {
+        assert_eq!(bytes.len(), COMPRESSED_POINT_SIZE, "wrong encoding size");
+        let bits = bytes.to_bits_le()?;
+        for bit in &bits[GF233_BITS..] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        Self::decode(&Gf2_233Var::from_bits_le(&bits[..GF233_BITS]))
+    }
+
+    /// Decodes `w`. The square root `f` and the half-trace `lambda` that
+    /// `xsk233_decode()` computes are witnessed and checked instead.
+    fn decode(w: &Gf2_233Var<F>) -> Result<Self, SynthesisError> {
+        if w.is_constant() {
+            let point = Xsk233Affine::from_bytes(&w.value()?.to_bytes())
+                .ok_or(SynthesisError::Unsatisfiable)?;
+            return Ok(Self::constant(point.into()));
+        }
+
+        // d = w^2 + w, x = d*f with f^2 + f = 1/d^2, and lambda^2 + lambda = x;
+        // invalid encodings get arbitrary witnesses
+        let cs = w.cs();
+        let hint = w.value().map(|w| {
+            let d = w.square() + w;
+            let x = decode_x(w).unwrap_or(Gf233::ZERO);
+            (x * d.inverse(), x.halftrace())
+        });
+        let f = Gf2_233Var::new_witness(cs.clone(), || hint.map(|(f, _)| f))?;
+        let lambda = Gf2_233Var::new_witness(cs, || hint.map(|(_, lambda)| lambda))?;
+
+        // w = 0 encodes the neutral element, for which d = 0
+        let w_is_zero = w.is_zero()?;
+        let expected = Gf2_233Var::from_boolean(&!&w_is_zero);
+
+        let ww = w.square()?;
+        let d = ww.add(w)?;
+        f.square()?
+            .add(&f)?
+            .mul(&d.square()?)?
+            .enforce_equal(&expected)?;
+        let x = d.mul(&f)?;
+
+        // x has trace zero, and of the two candidates x and x + d, it is
+        // the one for which trace(x*(lambda + w)) = 0
+        lambda.square()?.add(&lambda)?.enforce_equal(&x)?;
+        x.mul(&lambda.add(w)?)?
+            .trace()?
+            .enforce_equal(&Boolean::FALSE)?;
+
+        // S = x*w^2 with Z = 1 and T = x; the neutral element is (0, 1, 1, 0)
+        let s = x.mul(&ww)?.add(&Gf2_233Var::from_boolean(&w_is_zero))?;
+        Ok(Self {
+            x: x.clone(),
+            s,
+            z: Gf2_233Var::one(),
+            t: x,
+        })
+    }
+
+    /// Computes the canonical encoding `w = sqrt(S/T)`, with `w = 0` for
+    /// the neutral element, which is the only one with `T = 0`.
+    fn encode(&self) -> Result<Gf2_233Var<F>, SynthesisError> {
+        if self.is_constant() {
+            let w = Gf233::from_bytes(&self.value()?.to_bytes()).expect("canonical encoding");
+            return Ok(Gf2_233Var::constant(w));
+        }
+
+        let w = Gf2_233Var::new_witness(self.cs(), || {
+            Ok((self.s.value()? * self.t.value()?.inverse()).sqrt())
+        })?;
+        let t_is_zero = self.t.is_zero()?;
+        let s = t_is_zero.select(&Gf2_233Var::zero(), &self.s)?;
+        w.square()?.mul(&self.t)?.enforce_equal(&s)?;
+        w.is_zero()?
+            .conditional_enforce_equal(&Boolean::TRUE, &t_is_zero)?;
+        Ok(w)
+    }
+
+    /// Complete addition (7M + 2S), as in `xsk233_add()`.
+    fn add_points(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let x1x2 = self.x.mul(&other.x)?;
+        let s1s2 = self.s.mul(&other.s)?;
+        let z1z2 = self.z.mul(&other.z)?;
+        let d = self.s.add(&self.t)?.mul(&other.s.add(&other.t)?)?;
+        let f = x1x2.square()?;
+        let g = z1z2.square()?;
+
+        let x = d.add(&s1s2)?;
+        let s = g.mul(&s1s2)?.add(&f.mul(&d)?)?;
+        let z = f.add(&g)?;
+        let t = x.mul(&z)?;
+        Ok(Self { x, s, z, t })
+    }
+}
+
+impl<F: PrimeField> CurveVar<Xsk233Projective, F> for Xsk233Var<F> {
+    fn zero() -> Self {
+        Self::from_inner(InnerPoint::NEUTRAL)
+    }
+
+    fn constant(other: Xsk233Projective) -> Self {
+        Self::from_inner(InnerPoint::from_affine(&other.into_affine()))
+    }
+
+    /// Same as [`AllocVar::new_variable`]: decoding only accepts group
+    /// elements.
+    fn new_variable_omit_prime_order_check(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<Xsk233Projective, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        Self::new_variable(cs, f, mode)
+    }
+
+    /// xsk233 has prime order, so there is nothing to check.
+    fn enforce_prime_order(&self) -> Result<(), SynthesisError> {
+        Ok(())
+    }
+
+    /// Doubling (3M + 4S), as in `xsk233_double()`.
+    fn double_in_place(&mut self) -> Result<(), SynthesisError> {
+        let zz = self.z.square()?;
+        let x = self.t.square()?;
+        let st = self.s.add(&self.t)?;
+        let z = self.s.mul(&st)?;
+        let e = st.add(&zz)?.square()?;
+        let tmp = z.add(&x)?;
+        let s = e.mul(&tmp.add(&e)?)?.add(&tmp.square()?)?;
+        let t = x.mul(&z)?;
+        *self = Self { x, s, z, t };
+        Ok(())
+    }
+
+    fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            s: self.s.add(&self.t)?,
+            ..self.clone()
+        })
+    }
+}
+
+impl<F: PrimeField> R1CSVar<F> for Xsk233Var<F> {
+    type Value = Xsk233Projective;
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.x.cs().or(self.s.cs()).or(self.z.cs()).or(self.t.cs())
+    }
+
+    fn value(&self) -> Result<Xsk233Projective, SynthesisError> {
+        let coordinates = [
+            self.x.value()?,
+            self.s.value()?,
+            self.z.value()?,
+            self.t.value()?,
+        ];
+        InnerPoint::from_coordinates(coordinates)
+            .try_into_affine()
+            .map(Into::into)
+            .ok_or(SynthesisError::Unsatisfiable)
+    }
+}
+
+impl<F: PrimeField> AllocVar<Xsk233Projective, F> for Xsk233Var<F> {
+    /// Allocates the encoding of the point and decodes it in the circuit.
+    fn new_variable<T: Borrow<Xsk233Projective>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        if mode == AllocationMode::Constant {
+            return Ok(Self::constant(*f()?.borrow()));
+        }
+        let w = f().map(|p| Gf233::from_bytes(&p.borrow().to_bytes()).expect("canonical encoding"));
+        Self::decode(&Gf2_233Var::new_variable(ns.cs(), || w, mode)?)
+    }
+}
+
+impl<F: PrimeField> AllocVar<Xsk233Affine, F> for Xsk233Var<F> {
+    fn new_variable<T: Borrow<Xsk233Affine>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let point = f().map(|p| Xsk233Projective::from(*p.borrow()));
+        <Self as AllocVar<Xsk233Projective, F>>::new_variable(cs, || point, mode)
+    }
+}
+
+impl<F: PrimeField> EqGadget<F> for Xsk233Var<F> {
+    /// `S1*T2 == S2*T1`, as in `xsk233_equals()`.
+    fn is_eq(&self, other: &Self) -> Result<Boolean<F>, SynthesisError> {
+        self.s.mul(&other.t)?.is_eq(&other.s.mul(&self.t)?)
+    }
+
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<F>,
+    ) -> Result<(), SynthesisError> {
+        self.s
+            .mul(&other.t)?
+            .conditional_enforce_equal(&other.s.mul(&self.t)?, should_enforce)
+    }
+
+    fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        self.s.mul(&other.t)?.enforce_equal(&other.s.mul(&self.t)?)
+    }
+}
+
+impl<F: PrimeField> CondSelectGadget<F> for Xsk233Var<F> {
+    fn conditionally_select(
+        cond: &Boolean<F>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: cond.select(&true_value.x, &false_value.x)?,
+            s: cond.select(&true_value.s, &false_value.s)?,
+            z: cond.select(&true_value.z, &false_value.z)?,
+            t: cond.select(&true_value.t, &false_value.t)?,
+        })
+    }
+}
+
+/// The bits of the canonical 30-byte encoding, in little-endian order.
+impl<F: PrimeField> ToBitsGadget<F> for Xsk233Var<F> {
+    fn to_bits_le(&self) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        let mut bits = self.encode()?.to_bits_le()?;
+        bits.resize(8 * COMPRESSED_POINT_SIZE, Boolean::FALSE);
+        Ok(bits)
+    }
+}
+
+/// The canonical 30-byte encoding.
+impl<F: PrimeField> ToBytesGadget<F> for Xsk233Var<F> {
+    fn to_bytes_le(&self) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        Ok(self
+            .to_bits_le()?
+            .chunks(8)
+            .map(UInt8::from_bits_le)
+            .collect())
+    }
+}
+
+/// Packs the encoding like the [`ark_ff::ToConstraintField`] implementation
+/// of [`Xsk233Affine`].
+impl<F: PrimeField> ToConstraintFieldGadget<F> for Xsk233Var<F> {
+    fn to_constraint_field(&self) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.to_bytes_le()?.to_constraint_field()
+    }
+}
+
+/// Implements an operator and its assigning form for all combinations of
+/// owned and borrowed variables, and for constant right-hand sides.
+macro_rules! impl_group_op {
+    ($trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:expr) => {
+        impl<'a, F: PrimeField> $trait<&'a Xsk233Var<F>> for &'a Xsk233Var<F> {
+            type Output = Xsk233Var<F>;
+
+            fn $fn(self, other: &'a Xsk233Var<F>) -> Xsk233Var<F> {
+                #[allow(clippy::redundant_closure_call)]
+                ($op)(self, other).expect("constraint synthesis failed")
+            }
+        }
+
+        impl<'a, F: PrimeField> $trait<Xsk233Var<F>> for &'a Xsk233Var<F> {
+            type Output = Xsk233Var<F>;
+
+            fn $fn(self, other: Xsk233Var<F>) -> Xsk233Var<F> {
+                self.$fn(&other)
+            }
+        }
+
+        impl<'a, F: PrimeField> $trait<&'a Xsk233Var<F>> for Xsk233Var<F> {
+            type Output = Xsk233Var<F>;
+
+            fn $fn(self, other: &'a Xsk233Var<F>) -> Xsk233Var<F> {
+                (&self).$fn(other)
+            }
+        }
+
+        impl<F: PrimeField> $trait<Xsk233Var<F>> for Xsk233Var<F> {
+            type Output = Xsk233Var<F>;
+
+            fn $fn(self, other: Xsk233Var<F>) -> Xsk233Var<F> {
+                (&self).$fn(&other)
+            }
+        }
+
+        impl<'a, F: PrimeField> $trait<Xsk233Projective> for &'a Xsk233Var<F> {
+            type Output = Xsk233Var<F>;
+
+            fn $fn(self, other: Xsk233Projective) -> Xsk233Var<F> {
+                self.$fn(&Xsk233Var::constant(other))
+            }
+        }
+
+        impl<F: PrimeField> $trait<Xsk233Projective> for Xsk233Var<F> {
+            type Output = Xsk233Var<F>;
+
+            fn $fn(self, other: Xsk233Projective) -> Xsk233Var<F> {
+                (&self).$fn(other)
+            }
+        }
+
+        impl<'a, F: PrimeField> $assign_trait<&'a Xsk233Var<F>> for Xsk233Var<F> {
+            fn $assign_fn(&mut self, other: &'a Xsk233Var<F>) {
+                *self = (&*self).$fn(other);
+            }
+        }
+
+        impl<F: PrimeField> $assign_trait<Xsk233Var<F>> for Xsk233Var<F> {
+            fn $assign_fn(&mut self, other: Xsk233Var<F>) {
+                *self = (&*self).$fn(&other);
+            }
+        }
+
+        impl<F: PrimeField> $assign_trait<Xsk233Projective> for Xsk233Var<F> {
+            fn $assign_fn(&mut self, other: Xsk233Projective) {
+                *self = (&*self).$fn(other);
+            }
+        }
+    };
+}
+
+impl_group_op!(
+    Add,
+    add,
+    AddAssign,
+    add_assign,
+    |a: &Xsk233Var<F>, b: &Xsk233Var<F>| a.add_points(b)
+);
+impl_group_op!(
+    Sub,
+    sub,
+    SubAssign,
+    sub_assign,
+    |a: &Xsk233Var<F>, b: &Xsk233Var<F>| a.add_points(&b.negate()?)
+);
+
+impl<'a, F: PrimeField> GroupOpsBounds<'a, Xsk233Projective, Xsk233Var<F>> for Xsk233Var<F> {}
+
+impl<'a, F: PrimeField> GroupOpsBounds<'a, Xsk233Projective, Xsk233Var<F>> for &'a Xsk233Var<F> {}
+
+/// Double-and-add over the bits of the scalar.
+impl<'a, F: PrimeField> Mul<&'a EmulatedFpVar<Fr, F>> for &'a Xsk233Var<F> {
+    type Output = Xsk233Var<F>;
+
+    fn mul(self, scalar: &'a EmulatedFpVar<Fr, F>) -> Xsk233Var<F> {
+        if self.is_constant() && scalar.is_constant() {
+            return Xsk233Var::constant(
+                self.value().expect("constant") * scalar.value().expect("constant"),
+            );
+        }
+        let bits = scalar.to_bits_le().expect("constraint synthesis failed");
+        self.scalar_mul_le(bits.iter())
+            .expect("constraint synthesis failed")
+    }
+}
+
+impl<'a, F: PrimeField> Mul<&'a EmulatedFpVar<Fr, F>> for Xsk233Var<F> {
+    type Output = Xsk233Var<F>;
+
+    fn mul(self, scalar: &'a EmulatedFpVar<Fr, F>) -> Xsk233Var<F> {
+        &self * scalar
+    }
+}
+
+impl<F: PrimeField> Mul<EmulatedFpVar<Fr, F>> for Xsk233Var<F> {
+    type Output = Xsk233Var<F>;
+
+    fn mul(self, scalar: EmulatedFpVar<Fr, F>) -> Xsk233Var<F> {
+        &self * &scalar
+    }
+}
+
+impl<F: PrimeField> MulAssign<EmulatedFpVar<Fr, F>> for Xsk233Var<F> {
+    fn mul_assign(&mut self, scalar: EmulatedFpVar<Fr, F>) {
+        *self = &*self * &scalar;
+    }
+}
@@ -0,0 +1,300 @@
+//! Linkable spontaneous anonymous group (LSAG) signatures over xsk233.
+//!
+//! A [`RingSignature`] shows that the holder of one of the keys of a ring
+//! signed the message, without revealing which one. It carries the
+//! [`KeyImage`] `I = x*Hp(P)` of the signer, with `Hp` the [`hash_to_curve`]
+//! of the key: it is the same for every signature with the same key,
+//! whatever the ring and the message, so double signing is detected with
+//! [`RingSignature::is_linked`].
+//!
+//! The construction follows Liu, Wei and Wong as used by Monero: the
+//! signature is `(c_0, s_0, ..., s_{n-1})`, and for every member `i`
+//!
+//! ```text
+//! L_i = s_i*G + c_i*P_i,  R_i = s_i*Hp(P_i) + c_i*I,  c_{i+1} = H(L_i || R_i)
+//! ```
+//!
+//! closes the ring at `c_n = c_0`, where `H` also binds the ring, the key
+//! image and the message. Verification recomputes each step with
+//! two-point multi-scalar multiplications.
+//!
+//! [`hash_to_curve`]: crate::hash::hash_to_curve
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::group::Xsk233Projective;
+use crate::hash::{hash_to_curve, hash_to_curve_vartime, hash_to_scalar};
+use crate::keys::SecretKey;
+use crate::schnorr;
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, scalar_from_bytes, scalar_to_bytes};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::UniformRand;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+
+const HASH_TO_POINT_DST: &[u8] = b"xsk233-lsag-v1-hash-to-point";
+const CHALLENGE_DST: &[u8] = b"xsk233-lsag-v1-chal";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RingError {
+    /// The ring is empty or contains the neutral element.
+    InvalidRing,
+    /// The public key of the signer is not in the ring.
+    SignerNotInRing,
+}
+
+impl Display for RingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            RingError::InvalidRing => write!(f, "invalid ring"),
+            RingError::SignerNotInRing => write!(f, "signer is not in the ring"),
+        }
+    }
+}
+
+impl Error for RingError {}
+
+/// Key image `x*Hp(P)`, unique to a key pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyImage(Xsk233Affine);
+
+impl KeyImage {
+    pub fn new(secret: &SecretKey) -> Self {
+        let hp = hash_to_curve(HASH_TO_POINT_DST, &secret.public_key().to_bytes());
+        Self((hp * secret.scalar()).into_affine())
+    }
+
+    pub fn point(&self) -> &Xsk233Affine {
+        &self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; COMPRESSED_POINT_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes a key image; returns `None` if it is invalid or neutral.
+    pub fn from_bytes(bytes: &[u8; COMPRESSED_POINT_SIZE]) -> Option<Self> {
+        Xsk233Affine::from_bytes(bytes)
+            .filter(|p| !p.is_zero())
+            .map(Self)
+    }
+}
+
+/// LSAG signature `(I, c_0, s_0, ..., s_{n-1})` for a ring of `n` keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RingSignature {
+    key_image: KeyImage,
+    c0: Fr,
+    s: Vec<Fr>,
+}
+
+impl RingSignature {
+    /// Signs `msg` with `secret`, whose public key must be in `ring`.
+    pub fn sign<R: Rng + ?Sized>(
+        rng: &mut R,
+        ring: &[Xsk233Affine],
+        secret: &SecretKey,
+        msg: &[u8],
+    ) -> Result<Self, RingError> {
+        check_ring(ring)?;
+        let public = secret.public_key();
+        let signer = ring
+            .iter()
+            .position(|p| *p == public)
+            .ok_or(RingError::SignerNotInRing)?;
+        let n = ring.len();
+        let key_image = KeyImage::new(secret);
+        let hp: Vec<_> = ring.iter().map(hash_to_point).collect();
+        let prefix = challenge_prefix(ring, &key_image, msg);
+
+        let alpha = schnorr::nonce(rng, secret.scalar());
+        let mut s: Vec<_> = (0..n).map(|_| Fr::rand(rng)).collect();
+        let mut c = vec![Fr::default(); n];
+        let l = Xsk233Affine::generator() * *alpha;
+        let r = hp[signer] * *alpha;
+        c[(signer + 1) % n] = challenge(&prefix, &l, &r);
+        for i in (signer + 1..signer + n).map(|i| i % n) {
+            let (l, r) = step(&s[i], &c[i], &ring[i], &hp[i], &key_image);
+            c[(i + 1) % n] = challenge(&prefix, &l, &r);
+        }
+        s[signer] = *alpha - c[signer] * secret.scalar();
+
+        Ok(Self {
+            key_image,
+            c0: c[0],
+            s,
+        })
+    }
+
+    /// Verifies the signature on `msg` for `ring`.
+    pub fn verify(&self, ring: &[Xsk233Affine], msg: &[u8]) -> bool {
+        if check_ring(ring).is_err() || ring.len() != self.s.len() {
+            return false;
+        }
+        let prefix = challenge_prefix(ring, &self.key_image, msg);
+        let c = ring.iter().zip(&self.s).fold(self.c0, |c, (p, s)| {
+            let (l, r) = step(s, &c, p, &hash_to_point(p), &self.key_image);
+            challenge(&prefix, &l, &r)
+        });
+        c == self.c0
+    }
+
+    pub fn key_image(&self) -> &KeyImage {
+        &self.key_image
+    }
+
+    /// Whether both signatures were made with the same key. This is only
+    /// meaningful for signatures that verify.
+    pub fn is_linked(&self, other: &RingSignature) -> bool {
+        self.key_image == other.key_image
+    }
+
+    /// Number of keys in the ring of the signature.
+    pub fn ring_size(&self) -> usize {
+        self.s.len()
+    }
+
+    /// Encodes the signature as `I || c_0 || s_0 || ... || s_{n-1}`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(encoded_size(self.s.len()));
+        bytes.extend_from_slice(&self.key_image.to_bytes());
+        for scalar in [&self.c0].into_iter().chain(&self.s) {
+            bytes.extend_from_slice(&scalar_to_bytes(scalar));
+        }
+        bytes
+    }
+
+    /// Decodes a signature; returns `None` if the size does not match a
+    /// non-empty ring, or if an element is invalid.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let rest = bytes.get(COMPRESSED_POINT_SIZE..)?;
+        if rest.len() < 2 * SCALAR_SIZE || rest.len() % SCALAR_SIZE != 0 {
+            return None;
+        }
+        let key_image = KeyImage::from_bytes(bytes[..COMPRESSED_POINT_SIZE].try_into().unwrap())?;
+        let mut scalars = rest
+            .chunks_exact(SCALAR_SIZE)
+            .map(|chunk| scalar_from_bytes(chunk.try_into().unwrap()))
+            .collect::<Option<Vec<_>>>()?;
+        let c0 = scalars.remove(0);
+        Some(Self {
+            key_image,
+            c0,
+            s: scalars,
+        })
+    }
+}
+
+/// Size of an encoded signature for a ring of `n` keys.
+pub fn encoded_size(n: usize) -> usize {
+    COMPRESSED_POINT_SIZE + (n + 1) * SCALAR_SIZE
+}
+
+fn check_ring(ring: &[Xsk233Affine]) -> Result<(), RingError> {
+    if ring.is_empty() || ring.iter().any(Xsk233Affine::is_zero) {
+        return Err(RingError::InvalidRing);
+    }
+    Ok(())
+}
+
+/// `Hp(P)`. The keys of the ring are public, so the variable time hash is
+/// enough; it gives the same point as the one of [`KeyImage::new`].
+fn hash_to_point(public: &Xsk233Affine) -> Xsk233Affine {
+    hash_to_curve_vartime(HASH_TO_POINT_DST, &public.to_bytes())
+}
+
+/// `L = s*G + c*P` and `R = s*Hp(P) + c*I`.
+fn step(
+    s: &Fr,
+    c: &Fr,
+    public: &Xsk233Affine,
+    hp: &Xsk233Affine,
+    key_image: &KeyImage,
+) -> (Xsk233Projective, Xsk233Projective) {
+    (
+        Xsk233Projective::mul_add_generator(s, c, public),
+        Xsk233Projective::double_mul(s, hp, c, &key_image.0),
+    )
+}
+
+/// Digest of the ring, the key image and the message, hashed into every
+/// challenge.
+fn challenge_prefix(ring: &[Xsk233Affine], key_image: &KeyImage, msg: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new().chain_update((ring.len() as u64).to_be_bytes());
+    for p in ring {
+        hasher.update(p.to_bytes());
+    }
+    hasher
+        .chain_update(key_image.to_bytes())
+        .chain_update(msg)
+        .finalize()
+        .into()
+}
+
+fn challenge(prefix: &[u8; 32], l: &Xsk233Projective, r: &Xsk233Projective) -> Fr {
+    let input = [&prefix[..], &l.to_bytes(), &r.to_bytes()].concat();
+    hash_to_scalar(CHALLENGE_DST, &input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = test_rng();
+        let secrets: Vec<_> = (0..5).map(|_| SecretKey::random(&mut rng)).collect();
+        let ring: Vec<_> = secrets.iter().map(SecretKey::public_key).collect();
+
+        for secret in [&secrets[0], &secrets[3], &secrets[4]] {
+            let signature = RingSignature::sign(&mut rng, &ring, secret, b"vote").unwrap();
+            assert!(signature.verify(&ring, b"vote"));
+            assert!(!signature.verify(&ring, b"other vote"));
+            assert!(!signature.verify(&ring[1..], b"vote"));
+            let mut reordered = ring.clone();
+            reordered.swap(1, 2);
+            assert!(!signature.verify(&reordered, b"vote"));
+
+            let bytes = signature.to_bytes();
+            assert_eq!(bytes.len(), encoded_size(ring.len()));
+            assert_eq!(RingSignature::from_bytes(&bytes), Some(signature));
+        }
+
+        // a ring of one is a plain signature
+        let single = RingSignature::sign(&mut rng, &ring[2..3], &secrets[2], b"vote").unwrap();
+        assert!(single.verify(&ring[2..3], b"vote"));
+
+        let outsider = SecretKey::random(&mut rng);
+        assert_eq!(
+            RingSignature::sign(&mut rng, &ring, &outsider, b"vote"),
+            Err(RingError::SignerNotInRing)
+        );
+        assert_eq!(
+            RingSignature::sign(&mut rng, &[], &outsider, b"vote"),
+            Err(RingError::InvalidRing)
+        );
+    }
+
+    #[test]
+    fn test_linkability() {
+        let mut rng = test_rng();
+        let secrets: Vec<_> = (0..4).map(|_| SecretKey::random(&mut rng)).collect();
+        let ring: Vec<_> = secrets.iter().map(SecretKey::public_key).collect();
+
+        let first = RingSignature::sign(&mut rng, &ring, &secrets[1], b"vote A").unwrap();
+        let second = RingSignature::sign(&mut rng, &ring[..2], &secrets[1], b"vote B").unwrap();
+        let other = RingSignature::sign(&mut rng, &ring, &secrets[2], b"vote A").unwrap();
+        assert!(first.is_linked(&second));
+        assert!(!first.is_linked(&other));
+
+        // the signer cannot swap in another key image
+        let forged = RingSignature {
+            key_image: *other.key_image(),
+            ..first.clone()
+        };
+        assert!(!forged.verify(&ring, b"vote A"));
+    }
+}
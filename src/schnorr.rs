@@ -0,0 +1,122 @@
+//! Schnorr signatures over xsk233.
+//!
+//! A signature on `msg` under `Y = x*G` is `(R, z)` with `R = k*G` and
+//! `z = k + c*x`, where the challenge `c` hashes `R`, `Y` and `msg` with
+//! [`challenge`]. This is the signature format produced by the threshold
+//! and multi-party protocols of this crate, which all verify with
+//! [`Signature::verify`].
+//!
+//! Nonces are derived from fresh randomness and the secret key, as in RFC
+//! 9591, so that a weak random generator alone does not leak the key.
+
+use crate::affine::{COMPRESSED_POINT_SIZE, Xsk233Affine};
+use crate::group::Xsk233Projective;
+use crate::hash::hash_to_scalar;
+use crate::keys::SecretKey;
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, scalar_from_bytes, scalar_to_bytes};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::rand::Rng;
+use zeroize::Zeroizing;
+
+/// Size of an encoded signature: `R` and `z`.
+pub const SIGNATURE_SIZE: usize = COMPRESSED_POINT_SIZE + SCALAR_SIZE;
+
+const CHALLENGE_DST: &[u8] = b"xsk233-schnorr-v1-chal";
+const NONCE_DST: &[u8] = b"xsk233-schnorr-v1-nonce";
+
+/// Schnorr signature `(R, z)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    r: Xsk233Affine,
+    z: Fr,
+}
+
+impl Signature {
+    /// Assembles a signature, e.g. from the output of a multi-party
+    /// protocol.
+    pub fn new(r: Xsk233Affine, z: Fr) -> Self {
+        Self { r, z }
+    }
+
+    pub fn sign<R: Rng + ?Sized>(rng: &mut R, secret: &SecretKey, msg: &[u8]) -> Self {
+        let k = nonce(rng, secret.scalar());
+        let r = (Xsk233Affine::generator() * *k).into_affine();
+        let c = challenge(&r, &secret.public_key(), msg);
+        Self {
+            r,
+            z: *k + c * secret.scalar(),
+        }
+    }
+
+    /// Verifies the signature on `msg` under `public`, which must not be
+    /// the neutral element.
+    pub fn verify(&self, public: &Xsk233Affine, msg: &[u8]) -> bool {
+        if public.is_zero() {
+            return false;
+        }
+        let c = challenge(&self.r, public, msg);
+        // z*G - c*Y = R
+        Xsk233Projective::mul_add_generator(&self.z, &-c, public) == self.r
+    }
+
+    pub fn r(&self) -> &Xsk233Affine {
+        &self.r
+    }
+
+    pub fn z(&self) -> &Fr {
+        &self.z
+    }
+
+    pub fn to_bytes(&self) -> [u8; SIGNATURE_SIZE] {
+        let mut bytes = [0u8; SIGNATURE_SIZE];
+        bytes[..COMPRESSED_POINT_SIZE].copy_from_slice(&self.r.to_bytes());
+        bytes[COMPRESSED_POINT_SIZE..].copy_from_slice(&scalar_to_bytes(&self.z));
+        bytes
+    }
+
+    /// Decodes a signature; returns `None` if `R` is invalid or `z` is not
+    /// reduced.
+    pub fn from_bytes(bytes: &[u8; SIGNATURE_SIZE]) -> Option<Self> {
+        let (r, z) = bytes.split_at(COMPRESSED_POINT_SIZE);
+        Some(Self {
+            r: Xsk233Affine::from_bytes(r.try_into().unwrap())?,
+            z: scalar_from_bytes(z.try_into().unwrap())?,
+        })
+    }
+}
+
+/// The challenge `c = H(R || Y || msg)`.
+pub fn challenge(r: &Xsk233Affine, public: &Xsk233Affine, msg: &[u8]) -> Fr {
+    let input = [&r.to_bytes()[..], &public.to_bytes(), msg].concat();
+    hash_to_scalar(CHALLENGE_DST, &input)
+}
+
+/// Hedged nonce: 32 random bytes hashed with `secret`.
+pub(crate) fn nonce<R: Rng + ?Sized>(rng: &mut R, secret: &Fr) -> Zeroizing<Fr> {
+    let mut input = Zeroizing::new([0u8; 32 + SCALAR_SIZE]);
+    rng.fill_bytes(&mut input[..32]);
+    input[32..].copy_from_slice(&scalar_to_bytes(secret));
+    Zeroizing::new(hash_to_scalar(NONCE_DST, input.as_slice()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = test_rng();
+        let secret = SecretKey::random(&mut rng);
+        let public = secret.public_key();
+        let signature = Signature::sign(&mut rng, &secret, b"message");
+        assert!(signature.verify(&public, b"message"));
+        assert!(!signature.verify(&public, b"other"));
+        assert!(!signature.verify(&SecretKey::random(&mut rng).public_key(), b"message"));
+        assert!(!signature.verify(&Xsk233Affine::zero(), b"message"));
+
+        let bytes = signature.to_bytes();
+        assert_eq!(Signature::from_bytes(&bytes), Some(signature));
+    }
+}
@@ -0,0 +1,117 @@
+//! Shamir secret sharing over `Fr`, shared by the threshold protocols.
+//!
+//! Shares are evaluations of a polynomial at non-zero `u32` indices, and
+//! Feldman commitments are the coefficients times the generator.
+
+use crate::affine::Xsk233Affine;
+use crate::group::Xsk233Projective;
+use crate::xsk233::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, Zero};
+use ark_std::UniformRand;
+use ark_std::rand::Rng;
+
+/// Random polynomial of degree `threshold - 1` with constant term
+/// `secret`, as its coefficients.
+pub(crate) fn random_polynomial<R: Rng + ?Sized>(
+    rng: &mut R,
+    secret: Fr,
+    threshold: u32,
+) -> Vec<Fr> {
+    let mut coeffs = Vec::with_capacity(threshold as usize);
+    coeffs.push(secret);
+    coeffs.extend((1..threshold).map(|_| Fr::rand(rng)));
+    coeffs
+}
+
+/// Evaluates the polynomial `coeffs` at `index`.
+pub(crate) fn evaluate(coeffs: &[Fr], index: u32) -> Fr {
+    let x = Fr::from(index);
+    coeffs.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c)
+}
+
+/// Feldman commitment to `coeffs`.
+pub(crate) fn commit(coeffs: &[Fr]) -> Vec<Xsk233Affine> {
+    let g = Xsk233Affine::generator();
+    Xsk233Projective::normalize_batch(&coeffs.iter().map(|c| g * c).collect::<Vec<_>>())
+}
+
+/// Evaluates a Feldman commitment at `index`, giving `f(index)*G`.
+pub(crate) fn evaluate_commitment(commitment: &[Xsk233Affine], index: u32) -> Xsk233Projective {
+    let x = Fr::from(index);
+    commitment
+        .iter()
+        .rev()
+        .fold(Xsk233Projective::zero(), |acc, c| acc * x + c)
+}
+
+/// Lagrange coefficient of `index` at zero, `prod(j / (j - i))` over the
+/// other indices.
+///
+/// # Panics
+///
+/// If `indices` has duplicates of `index`.
+pub(crate) fn lagrange_at_zero(index: u32, indices: &[u32]) -> Fr {
+    lagrange_at(0, index, indices)
+}
+
+/// Lagrange coefficient of `index` at `x`, `prod((x - j) / (i - j))` over
+/// the other indices.
+///
+/// # Panics
+///
+/// If `indices` has duplicates of `index`.
+pub(crate) fn lagrange_at(x: u32, index: u32, indices: &[u32]) -> Fr {
+    let (x, i) = (Fr::from(x), Fr::from(index));
+    let (num, den) = indices
+        .iter()
+        .filter(|j| **j != index)
+        .map(|j| Fr::from(*j))
+        .fold((Fr::ONE, Fr::ONE), |(num, den), j| {
+            (num * (x - j), den * (i - j))
+        });
+    num * den.inverse().expect("distinct indices")
+}
+
+/// Evaluates at `x` the polynomial through `shares`, given as
+/// `(index, value)` pairs with distinct indices.
+pub(crate) fn interpolate(shares: &[(u32, Fr)], x: u32) -> Fr {
+    let indices: Vec<_> = shares.iter().map(|(i, _)| *i).collect();
+    shares
+        .iter()
+        .map(|(i, v)| *v * lagrange_at(x, *i, &indices))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_interpolation() {
+        let mut rng = test_rng();
+        let secret = Fr::rand(&mut rng);
+        let coeffs = random_polynomial(&mut rng, secret, 3);
+        let commitment = commit(&coeffs);
+
+        let indices = [2, 5, 7];
+        let recovered: Fr = indices
+            .iter()
+            .map(|i| evaluate(&coeffs, *i) * lagrange_at_zero(*i, &indices))
+            .sum();
+        assert_eq!(recovered, secret);
+        let shares: Vec<_> = indices
+            .iter()
+            .map(|i| (*i, evaluate(&coeffs, *i)))
+            .collect();
+        assert_eq!(interpolate(&shares, 4), evaluate(&coeffs, 4));
+
+        for i in indices {
+            assert_eq!(
+                evaluate_commitment(&commitment, i),
+                Xsk233Affine::generator() * evaluate(&coeffs, i)
+            );
+        }
+    }
+}
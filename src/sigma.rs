@@ -0,0 +1,594 @@
+//! Sigma protocols for linear relations over xsk233, with AND and OR
+//! composition.
+//!
+//! A [`LinearRelation`] is a system of equations `Y_j = sum(x_i * G_ji)`
+//! in secret scalars `x_i`, which covers Schnorr and DLEQ statements,
+//! Pedersen openings and most of their variants. [`Statement`] composes
+//! relations: AND proves all sub-statements under a shared challenge, OR
+//! proves one of them and simulates the others, splitting the challenge
+//! as in Cramer-Damgard-Schoenmakers.
+//!
+//! Proofs are made non-interactive with Fiat-Shamir; the challenge is
+//! drawn from a [`Transcript`] of the whole statement and the commitments,
+//! which callers can seed with their own context. A [`SigmaProof`] stores the
+//! challenge and the responses only, the commitments are recomputed by
+//! the verifier, so its encoding is a list of scalars whose layout is
+//! given by the statement.
+
+use crate::group::Xsk233Projective;
+use crate::transcript::Transcript;
+use crate::xsk233::Fr;
+use crate::{SCALAR_SIZE, scalar_from_bytes, scalar_to_bytes};
+use ark_ec::CurveGroup;
+use ark_ff::Zero;
+use ark_std::UniformRand;
+use ark_std::fmt::{Display, Formatter, Result as FmtResult};
+use ark_std::rand::Rng;
+use std::error::Error;
+
+const TRANSCRIPT_LABEL: &[u8] = b"xsk233-sigma";
+
+/// Equations `Y_j = sum(x_i * G_ji)` over `num_scalars` secret scalars.
+#[derive(Clone, Debug)]
+pub struct LinearRelation {
+    num_scalars: usize,
+    equations: Vec<Equation>,
+}
+
+#[derive(Clone, Debug)]
+struct Equation {
+    image: Xsk233Projective,
+    terms: Vec<(usize, Xsk233Projective)>,
+}
+
+impl LinearRelation {
+    pub fn new(num_scalars: usize) -> Self {
+        Self {
+            num_scalars,
+            equations: Vec::new(),
+        }
+    }
+
+    /// Adds the equation `image = sum(x_i * base)` over the `(i, base)`
+    /// terms.
+    ///
+    /// # Panics
+    ///
+    /// If a scalar index is out of range.
+    pub fn add_equation(
+        &mut self,
+        image: Xsk233Projective,
+        terms: &[(usize, Xsk233Projective)],
+    ) -> &mut Self {
+        assert!(
+            terms.iter().all(|(i, _)| *i < self.num_scalars),
+            "scalar index out of range"
+        );
+        self.equations.push(Equation {
+            image,
+            terms: terms.to_vec(),
+        });
+        self
+    }
+
+    pub fn num_scalars(&self) -> usize {
+        self.num_scalars
+    }
+
+    pub fn is_satisfied(&self, scalars: &[Fr]) -> bool {
+        scalars.len() == self.num_scalars
+            && self
+                .equations
+                .iter()
+                .all(|eq| eq.evaluate(scalars) == eq.image)
+    }
+}
+
+impl Equation {
+    fn evaluate(&self, scalars: &[Fr]) -> Xsk233Projective {
+        self.terms.iter().map(|(i, base)| *base * scalars[*i]).sum()
+    }
+}
+
+/// Statement to prove: a relation, or a composition of statements.
+#[derive(Clone, Debug)]
+pub enum Statement {
+    Relation(LinearRelation),
+    /// All the sub-statements hold.
+    And(Vec<Statement>),
+    /// At least one of the sub-statements holds.
+    Or(Vec<Statement>),
+}
+
+/// Witness for a [`Statement`], with the same shape.
+#[derive(Clone, Debug)]
+pub enum Witness {
+    Relation(Vec<Fr>),
+    And(Vec<Witness>),
+    /// Index of a sub-statement that holds, and its witness.
+    Or(usize, Box<Witness>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigmaError {
+    /// The witness does not have the shape of the statement.
+    WitnessMismatch,
+    /// The witness does not satisfy the statement.
+    Unsatisfied,
+}
+
+impl Display for SigmaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            SigmaError::WitnessMismatch => write!(f, "witness does not match the statement"),
+            SigmaError::Unsatisfied => write!(f, "witness does not satisfy the statement"),
+        }
+    }
+}
+
+impl Error for SigmaError {}
+
+/// Non-interactive proof for a [`Statement`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigmaProof {
+    challenge: Fr,
+    responses: Responses,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Responses {
+    Relation(Vec<Fr>),
+    And(Vec<Responses>),
+    /// The challenge of every branch with its responses; the challenges
+    /// add up to that of the OR node.
+    Or(Vec<(Fr, Responses)>),
+}
+
+/// Prover state between the commitments and the responses.
+enum Prover<'a> {
+    Relation(Vec<Fr>, &'a [Fr]),
+    And(Vec<Prover<'a>>),
+    Or(usize, Vec<Prover<'a>>),
+    Simulated(Fr, Responses),
+}
+
+impl Statement {
+    /// Proves the statement with `witness`.
+    pub fn prove<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        witness: &Witness,
+    ) -> Result<SigmaProof, SigmaError> {
+        self.prove_with_transcript(&mut Transcript::new(TRANSCRIPT_LABEL), rng, witness)
+    }
+
+    /// Proves the statement with `witness`, drawing the challenge from
+    /// `transcript` after appending the statement and the commitments.
+    pub fn prove_with_transcript<R: Rng + ?Sized>(
+        &self,
+        transcript: &mut Transcript,
+        rng: &mut R,
+        witness: &Witness,
+    ) -> Result<SigmaProof, SigmaError> {
+        let mut commitments = Vec::new();
+        let prover = self.commit(rng, witness, &mut commitments)?;
+        let challenge = self.challenge(transcript, &commitments);
+        Ok(SigmaProof {
+            challenge,
+            responses: prover.respond(challenge),
+        })
+    }
+
+    /// Verifies `proof` for this statement.
+    pub fn verify(&self, proof: &SigmaProof) -> bool {
+        self.verify_with_transcript(&mut Transcript::new(TRANSCRIPT_LABEL), proof)
+    }
+
+    /// Verifies a proof made with [`Statement::prove_with_transcript`];
+    /// `transcript` must be in the same state as the prover's.
+    pub fn verify_with_transcript(&self, transcript: &mut Transcript, proof: &SigmaProof) -> bool {
+        let mut commitments = Vec::new();
+        self.commitments(&proof.responses, proof.challenge, &mut commitments)
+            && self.challenge(transcript, &commitments) == proof.challenge
+    }
+
+    fn commit<'a, R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        witness: &'a Witness,
+        out: &mut Vec<Xsk233Projective>,
+    ) -> Result<Prover<'a>, SigmaError> {
+        match (self, witness) {
+            (Statement::Relation(relation), Witness::Relation(scalars)) => {
+                if scalars.len() != relation.num_scalars {
+                    return Err(SigmaError::WitnessMismatch);
+                }
+                if !relation.is_satisfied(scalars) {
+                    return Err(SigmaError::Unsatisfied);
+                }
+                let nonces: Vec<_> = (0..scalars.len()).map(|_| Fr::rand(rng)).collect();
+                out.extend(relation.equations.iter().map(|eq| eq.evaluate(&nonces)));
+                Ok(Prover::Relation(nonces, scalars))
+            }
+            (Statement::And(statements), Witness::And(witnesses))
+                if statements.len() == witnesses.len() =>
+            {
+                let children = statements
+                    .iter()
+                    .zip(witnesses)
+                    .map(|(s, w)| s.commit(rng, w, out))
+                    .collect::<Result<_, _>>()?;
+                Ok(Prover::And(children))
+            }
+            (Statement::Or(statements), Witness::Or(real, witness)) if *real < statements.len() => {
+                let mut children = Vec::with_capacity(statements.len());
+                for (i, statement) in statements.iter().enumerate() {
+                    if i == *real {
+                        children.push(statement.commit(rng, witness, out)?);
+                    } else {
+                        let challenge = Fr::rand(rng);
+                        let responses = statement.simulate(rng, challenge);
+                        statement.commitments(&responses, challenge, out);
+                        children.push(Prover::Simulated(challenge, responses));
+                    }
+                }
+                Ok(Prover::Or(*real, children))
+            }
+            _ => Err(SigmaError::WitnessMismatch),
+        }
+    }
+
+    /// Random responses for `challenge`, whose commitments are then
+    /// recomputed like the verifier does.
+    fn simulate<R: Rng + ?Sized>(&self, rng: &mut R, challenge: Fr) -> Responses {
+        match self {
+            Statement::Relation(relation) => {
+                Responses::Relation((0..relation.num_scalars).map(|_| Fr::rand(rng)).collect())
+            }
+            Statement::And(statements) => Responses::And(
+                statements
+                    .iter()
+                    .map(|s| s.simulate(rng, challenge))
+                    .collect(),
+            ),
+            Statement::Or(statements) => {
+                let mut rest = challenge;
+                let branches = statements
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| {
+                        let c = if i + 1 == statements.len() {
+                            rest
+                        } else {
+                            Fr::rand(rng)
+                        };
+                        rest -= c;
+                        (c, s.simulate(rng, c))
+                    })
+                    .collect();
+                Responses::Or(branches)
+            }
+        }
+    }
+
+    /// Recomputes the commitments `sum(s_i * G_ji) - c * Y_j`. Returns
+    /// `false` if the responses do not have the shape of the statement.
+    fn commitments(
+        &self,
+        responses: &Responses,
+        challenge: Fr,
+        out: &mut Vec<Xsk233Projective>,
+    ) -> bool {
+        match (self, responses) {
+            (Statement::Relation(relation), Responses::Relation(s)) => {
+                if s.len() != relation.num_scalars {
+                    return false;
+                }
+                out.extend(
+                    relation
+                        .equations
+                        .iter()
+                        .map(|eq| eq.evaluate(s) - eq.image * challenge),
+                );
+                true
+            }
+            (Statement::And(statements), Responses::And(children)) => {
+                statements.len() == children.len()
+                    && statements
+                        .iter()
+                        .zip(children)
+                        .all(|(s, r)| s.commitments(r, challenge, out))
+            }
+            (Statement::Or(statements), Responses::Or(branches)) => {
+                !statements.is_empty()
+                    && statements.len() == branches.len()
+                    && branches.iter().map(|(c, _)| c).sum::<Fr>() == challenge
+                    && statements
+                        .iter()
+                        .zip(branches)
+                        .all(|(s, (c, r))| s.commitments(r, *c, out))
+            }
+            _ => false,
+        }
+    }
+
+    fn challenge(&self, transcript: &mut Transcript, commitments: &[Xsk233Projective]) -> Fr {
+        let mut statement = Vec::new();
+        self.encode(&mut statement);
+        transcript.append_message(b"statement", &statement);
+        for commitment in Xsk233Projective::normalize_batch(commitments) {
+            transcript.append_point(b"commitment", &commitment);
+        }
+        transcript.challenge_scalar(b"challenge")
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        let len = |n: usize| (n as u64).to_le_bytes();
+        match self {
+            Statement::Relation(relation) => {
+                out.push(0);
+                out.extend(len(relation.num_scalars));
+                out.extend(len(relation.equations.len()));
+                for eq in &relation.equations {
+                    out.extend(eq.image.to_bytes());
+                    out.extend(len(eq.terms.len()));
+                    for (i, base) in &eq.terms {
+                        out.extend(len(*i));
+                        out.extend(base.to_bytes());
+                    }
+                }
+            }
+            Statement::And(statements) | Statement::Or(statements) => {
+                out.push(if matches!(self, Statement::And(_)) {
+                    1
+                } else {
+                    2
+                });
+                out.extend(len(statements.len()));
+                for s in statements {
+                    s.encode(out);
+                }
+            }
+        }
+    }
+}
+
+impl Prover<'_> {
+    fn respond(self, challenge: Fr) -> Responses {
+        match self {
+            Prover::Relation(nonces, scalars) => Responses::Relation(
+                nonces
+                    .into_iter()
+                    .zip(scalars)
+                    .map(|(k, x)| k + challenge * x)
+                    .collect(),
+            ),
+            Prover::And(children) => {
+                Responses::And(children.into_iter().map(|p| p.respond(challenge)).collect())
+            }
+            Prover::Or(real, children) => {
+                let simulated: Fr = children
+                    .iter()
+                    .map(|p| match p {
+                        Prover::Simulated(c, _) => *c,
+                        _ => Fr::zero(),
+                    })
+                    .sum();
+                let branches = children
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, p)| match p {
+                        Prover::Simulated(c, responses) => (c, responses),
+                        p => {
+                            debug_assert_eq!(i, real);
+                            let c = challenge - simulated;
+                            (c, p.respond(c))
+                        }
+                    })
+                    .collect();
+                Responses::Or(branches)
+            }
+            Prover::Simulated(..) => unreachable!("only OR branches are simulated"),
+        }
+    }
+}
+
+impl SigmaProof {
+    /// Encodes the challenge and the responses; the challenge of the last
+    /// branch of every OR is left out since it is implied.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = scalar_to_bytes(&self.challenge).to_vec();
+        self.responses.encode(&mut out);
+        out
+    }
+
+    /// Decodes a proof for `statement`.
+    pub fn from_bytes(statement: &Statement, bytes: &[u8]) -> Option<Self> {
+        let mut scalars = bytes.chunks(SCALAR_SIZE).map(|chunk| {
+            let chunk: &[u8; SCALAR_SIZE] = chunk.try_into().ok()?;
+            scalar_from_bytes(chunk)
+        });
+        let mut next = || scalars.next().flatten();
+        let challenge = next()?;
+        let responses = Responses::decode(statement, challenge, &mut next)?;
+        scalars.next().is_none().then_some(Self {
+            challenge,
+            responses,
+        })
+    }
+}
+
+impl Responses {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Responses::Relation(s) => s.iter().for_each(|s| out.extend(scalar_to_bytes(s))),
+            Responses::And(children) => children.iter().for_each(|r| r.encode(out)),
+            Responses::Or(branches) => {
+                for (i, (c, r)) in branches.iter().enumerate() {
+                    if i + 1 < branches.len() {
+                        out.extend(scalar_to_bytes(c));
+                    }
+                    r.encode(out);
+                }
+            }
+        }
+    }
+
+    fn decode(
+        statement: &Statement,
+        challenge: Fr,
+        next: &mut impl FnMut() -> Option<Fr>,
+    ) -> Option<Self> {
+        match statement {
+            Statement::Relation(relation) => Some(Responses::Relation(
+                (0..relation.num_scalars)
+                    .map(|_| next())
+                    .collect::<Option<_>>()?,
+            )),
+            Statement::And(statements) => Some(Responses::And(
+                statements
+                    .iter()
+                    .map(|s| Self::decode(s, challenge, next))
+                    .collect::<Option<_>>()?,
+            )),
+            Statement::Or(statements) => {
+                let mut rest = challenge;
+                let mut branches = Vec::with_capacity(statements.len());
+                for (i, s) in statements.iter().enumerate() {
+                    let c = if i + 1 == statements.len() {
+                        rest
+                    } else {
+                        next()?
+                    };
+                    rest -= c;
+                    branches.push((c, Self::decode(s, c, next)?));
+                }
+                Some(Responses::Or(branches))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::PrimeGroup;
+    use ark_std::test_rng;
+
+    /// `Y = x*G` and `Z = x*H`.
+    fn dleq(x: &Fr, h: Xsk233Projective) -> LinearRelation {
+        let g = Xsk233Projective::generator();
+        let mut relation = LinearRelation::new(1);
+        relation
+            .add_equation(g * x, &[(0, g)])
+            .add_equation(h * x, &[(0, h)]);
+        relation
+    }
+
+    /// `C = v*G + r*H`.
+    fn pedersen(v: &Fr, r: &Fr, h: Xsk233Projective) -> LinearRelation {
+        let g = Xsk233Projective::generator();
+        let mut relation = LinearRelation::new(2);
+        relation.add_equation(g * v + h * r, &[(0, g), (1, h)]);
+        relation
+    }
+
+    #[test]
+    fn test_relation() {
+        let mut rng = test_rng();
+        let (x, h) = (Fr::rand(&mut rng), Xsk233Projective::rand(&mut rng));
+        let statement = Statement::Relation(dleq(&x, h));
+        let proof = statement
+            .prove(&mut rng, &Witness::Relation(vec![x]))
+            .unwrap();
+        assert!(statement.verify(&proof));
+
+        let other = Statement::Relation(dleq(&(x + Fr::from(1u64)), h));
+        assert!(!other.verify(&proof));
+        assert_eq!(
+            other.prove(&mut rng, &Witness::Relation(vec![x])),
+            Err(SigmaError::Unsatisfied)
+        );
+        assert_eq!(
+            statement.prove(&mut rng, &Witness::Relation(vec![x, x])),
+            Err(SigmaError::WitnessMismatch)
+        );
+
+        // proofs are bound to the caller's transcript
+        let mut transcript = Transcript::new(b"test");
+        transcript.append_message(b"context", b"a");
+        let proof = statement
+            .prove_with_transcript(
+                &mut transcript.clone(),
+                &mut rng,
+                &Witness::Relation(vec![x]),
+            )
+            .unwrap();
+        assert!(statement.verify_with_transcript(&mut transcript.clone(), &proof));
+        assert!(!statement.verify(&proof));
+        transcript.append_message(b"context", b"b");
+        assert!(!statement.verify_with_transcript(&mut transcript, &proof));
+    }
+
+    #[test]
+    fn test_composition() {
+        let mut rng = test_rng();
+        let h = Xsk233Projective::rand(&mut rng);
+        let (x, v, r) = (Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let unknown = Fr::rand(&mut rng);
+
+        // (DLEQ(x) AND Pedersen(v, r)) OR DLEQ(unknown), knowing the left side
+        let statement = Statement::Or(vec![
+            Statement::And(vec![
+                Statement::Relation(dleq(&x, h)),
+                Statement::Relation(pedersen(&v, &r, h)),
+            ]),
+            Statement::Relation(dleq(&unknown, h)),
+        ]);
+        let left = Witness::And(vec![
+            Witness::Relation(vec![x]),
+            Witness::Relation(vec![v, r]),
+        ]);
+        let proof = statement
+            .prove(&mut rng, &Witness::Or(0, Box::new(left)))
+            .unwrap();
+        assert!(statement.verify(&proof));
+
+        // and knowing the right side
+        let right = Witness::Relation(vec![unknown]);
+        let proof = statement
+            .prove(&mut rng, &Witness::Or(1, Box::new(right)))
+            .unwrap();
+        assert!(statement.verify(&proof));
+
+        assert_eq!(
+            statement.prove(&mut rng, &Witness::Or(2, Box::new(Witness::And(vec![])))),
+            Err(SigmaError::WitnessMismatch)
+        );
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = test_rng();
+        let h = Xsk233Projective::rand(&mut rng);
+        let x = Fr::rand(&mut rng);
+        let statement = Statement::Or(vec![
+            Statement::Relation(dleq(&Fr::rand(&mut rng), h)),
+            Statement::Relation(dleq(&x, h)),
+            Statement::Relation(pedersen(&x, &x, h)),
+        ]);
+        let witness = Witness::Or(1, Box::new(Witness::Relation(vec![x])));
+        let proof = statement.prove(&mut rng, &witness).unwrap();
+
+        // challenge, two of the three branch challenges, four responses
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 7 * SCALAR_SIZE);
+        assert_eq!(SigmaProof::from_bytes(&statement, &bytes), Some(proof));
+
+        assert_eq!(SigmaProof::from_bytes(&statement, &bytes[1..]), None);
+        let mut tampered = bytes.clone();
+        tampered[SCALAR_SIZE] ^= 1;
+        let tampered = SigmaProof::from_bytes(&statement, &tampered).unwrap();
+        assert!(!statement.verify(&tampered));
+    }
+}
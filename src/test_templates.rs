@@ -0,0 +1,354 @@
+//! Group conformance tests ported from `ark-algebra-test-templates`.
+//!
+//! The upstream macros are written against `short_weierstrass::Projective`
+//! and the template crate cannot be used as is with curves whose points are
+//! opaque xs233 structures, so the generic part of its `test_group!` suite
+//! is reproduced here for any `CurveGroup`.
+
+pub(crate) const ITERATIONS: usize = 10;
+
+/// Expands to the arkworks group test suite for `$group`.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! test_group {
+    ($group:ty) => {
+        use ark_ec::scalar_mul::variable_base::{ChunkedPippenger, HashMapPippenger};
+        use ark_ec::scalar_mul::wnaf::WnafContext;
+        use ark_ec::{AffineRepr, CurveConfig, CurveGroup, PrimeGroup, ScalarMul, VariableBaseMSM};
+        use ark_ff::{AdditiveGroup, Field, PrimeField, UniformRand, Zero};
+        use ark_serialize::{
+            CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+        };
+        use $crate::test_templates::ITERATIONS;
+        use zeroize::Zeroize;
+
+        type ScalarField = <$group as PrimeGroup>::ScalarField;
+        type Affine = <$group as CurveGroup>::Affine;
+
+        fn naive_var_base_msm(bases: &[Affine], scalars: &[ScalarField]) -> $group {
+            bases
+                .iter()
+                .zip(scalars)
+                .fold(<$group>::zero(), |acc, (base, scalar)| acc + *base * scalar)
+        }
+
+        #[test]
+        fn test_add_properties() {
+            let mut rng = ark_std::test_rng();
+            let zero = <$group>::zero();
+            for _ in 0..ITERATIONS {
+                let a = <$group>::rand(&mut rng);
+                let b = <$group>::rand(&mut rng);
+                let c = <$group>::rand(&mut rng);
+
+                // associativity and commutativity
+                assert_eq!((a + b) + c, a + (b + c));
+                assert_eq!(a + b, b + a);
+                assert_eq!((a + b) + c, a + (c + b));
+                assert_eq!((a + c) + b, (b + c) + a);
+
+                // identity and inverses
+                assert_eq!(zero + a, a);
+                assert_eq!(a + zero, a);
+                assert_eq!(-a + a, zero);
+                assert_eq!(zero, -zero);
+
+                // doubling
+                assert_eq!(a + a, a.double());
+                assert_eq!(zero.double(), zero);
+                let mut a_doubled = a;
+                a_doubled.double_in_place();
+                assert_eq!(a_doubled, a.double());
+
+                // mixed addition
+                let b_affine = b.into_affine();
+                let mut ab = a;
+                ab += &b_affine;
+                assert_eq!(ab, a + b);
+                assert_eq!(a + b_affine, a + b);
+                assert_eq!(b_affine + a, a + b);
+                assert_eq!(zero + b_affine, b);
+            }
+        }
+
+        #[test]
+        fn test_sub_properties() {
+            let mut rng = ark_std::test_rng();
+            let zero = <$group>::zero();
+            for _ in 0..ITERATIONS {
+                let a = <$group>::rand(&mut rng);
+                let b = <$group>::rand(&mut rng);
+
+                assert_eq!(a - b, -(b - a));
+                assert_eq!(a - zero, a);
+                assert_eq!(zero - a, -a);
+                assert_eq!(a - a, zero);
+                assert_eq!((a - b) + b, a);
+
+                let b_affine = b.into_affine();
+                let mut ab = a;
+                ab -= &b_affine;
+                assert_eq!(ab, a - b);
+                assert_eq!(a - b_affine, a - b);
+            }
+        }
+
+        #[test]
+        fn test_mul_properties() {
+            let mut rng = ark_std::test_rng();
+            let zero = <$group>::zero();
+            for _ in 0..ITERATIONS {
+                let a = <$group>::rand(&mut rng);
+                let s1 = ScalarField::rand(&mut rng);
+                let s2 = ScalarField::rand(&mut rng);
+
+                assert_eq!(zero * s1, zero);
+                assert_eq!(a * ScalarField::ZERO, zero);
+                assert_eq!(a * ScalarField::ONE, a);
+                assert_eq!(a * ScalarField::from(2u64), a.double());
+                assert_eq!(a * -ScalarField::ONE, -a);
+
+                // (a * s1) * s2 == a * (s1 * s2) == (a * s2) * s1
+                assert_eq!((a * s1) * s2, a * (s1 * s2));
+                assert_eq!((a * s2) * s1, a * (s1 * s2));
+
+                // distributivity
+                assert_eq!(a * (s1 + s2), a * s1 + a * s2);
+                assert_eq!(a * s1 + a * s1, a * s1.double());
+
+                // affine scalar multiplication agrees
+                assert_eq!(a.into_affine() * s1, a * s1);
+            }
+        }
+
+        #[test]
+        fn test_mul_bigint() {
+            let mut rng = ark_std::test_rng();
+            for _ in 0..ITERATIONS {
+                let a = <$group>::rand(&mut rng);
+                let s = ScalarField::rand(&mut rng);
+
+                assert_eq!(a.mul_bigint(s.into_bigint()), a * s);
+                assert_eq!(a.into_affine().mul_bigint(s.into_bigint()), a * s);
+
+                // the group order annihilates every element
+                assert!(a.mul_bigint(ScalarField::MODULUS).is_zero());
+            }
+        }
+
+        #[test]
+        fn test_wnaf() {
+            let mut rng = ark_std::test_rng();
+            for w in 2..=5 {
+                let context = WnafContext::new(w);
+                for _ in 0..ITERATIONS {
+                    let a = <$group>::rand(&mut rng);
+                    let s = ScalarField::rand(&mut rng);
+
+                    assert_eq!(context.mul(a, &s), a * s);
+
+                    let table = context.table(a);
+                    assert_eq!(context.mul_with_table(&table, &s), Some(a * s));
+                }
+            }
+        }
+
+        #[test]
+        fn test_var_base_msm() {
+            let mut rng = ark_std::test_rng();
+            for size in [1, 2, 5, 17, 64] {
+                let bases = (0..size)
+                    .map(|_| <$group>::rand(&mut rng))
+                    .collect::<Vec<_>>();
+                let bases = <$group>::normalize_batch(&bases);
+                let scalars = (0..size)
+                    .map(|_| ScalarField::rand(&mut rng))
+                    .collect::<Vec<_>>();
+
+                let naive = naive_var_base_msm(&bases, &scalars);
+                assert_eq!(<$group>::msm(&bases, &scalars).unwrap(), naive);
+                assert_eq!(<$group>::msm_unchecked(&bases, &scalars), naive);
+            }
+
+            // mismatched lengths are rejected
+            let bases = vec![Affine::generator(); 3];
+            let scalars = vec![ScalarField::ONE; 2];
+            assert!(<$group>::msm(&bases, &scalars).is_err());
+        }
+
+        #[test]
+        fn test_chunked_pippenger() {
+            let mut rng = ark_std::test_rng();
+            let size = 100;
+            let bases = (0..size)
+                .map(|_| <$group>::rand(&mut rng).into_affine())
+                .collect::<Vec<_>>();
+            let scalars = (0..size)
+                .map(|_| ScalarField::rand(&mut rng))
+                .collect::<Vec<_>>();
+            let naive = naive_var_base_msm(&bases, &scalars);
+
+            let mut pippenger = ChunkedPippenger::<$group>::new(8);
+            for (base, scalar) in bases.iter().zip(&scalars) {
+                pippenger.add(base, scalar.into_bigint());
+            }
+            assert_eq!(pippenger.finalize(), naive);
+
+            let chunks = <$group>::msm_chunks(&bases.as_slice(), &scalars.as_slice());
+            assert_eq!(chunks, naive);
+        }
+
+        #[test]
+        fn test_hashmap_pippenger() {
+            let mut rng = ark_std::test_rng();
+            let size = 100;
+            let bases = (0..size)
+                .map(|_| <$group>::rand(&mut rng).into_affine())
+                .collect::<Vec<_>>();
+            let scalars = (0..size)
+                .map(|_| ScalarField::rand(&mut rng))
+                .collect::<Vec<_>>();
+
+            // repeated bases are merged by the hash map
+            let mut pippenger = HashMapPippenger::<$group>::new(8);
+            for (base, scalar) in bases.iter().zip(&scalars) {
+                pippenger.add(base, scalar);
+                pippenger.add(base, scalar);
+            }
+            assert_eq!(
+                pippenger.finalize(),
+                naive_var_base_msm(&bases, &scalars).double()
+            );
+        }
+
+        #[test]
+        fn test_batch_mul_and_normalization() {
+            let mut rng = ark_std::test_rng();
+            let points = (0..ITERATIONS)
+                .map(|_| <$group>::rand(&mut rng))
+                .collect::<Vec<_>>();
+
+            let normalized = <$group>::normalize_batch(&points);
+            for (p, q) in points.iter().zip(&normalized) {
+                assert_eq!(p.into_affine(), *q);
+            }
+
+            let g = <$group>::generator();
+            let scalars = (0..ITERATIONS)
+                .map(|_| ScalarField::rand(&mut rng))
+                .collect::<Vec<_>>();
+            let batch = g.batch_mul(&scalars);
+            for (p, s) in batch.iter().zip(&scalars) {
+                assert_eq!(*p, (g * s).into_affine());
+            }
+        }
+
+        #[test]
+        fn test_cofactor() {
+            let mut rng = ark_std::test_rng();
+            let cofactor =
+                ScalarField::from(<<$group as CurveGroup>::Config as CurveConfig>::COFACTOR[0]);
+            assert_eq!(
+                cofactor * <<$group as CurveGroup>::Config as CurveConfig>::COFACTOR_INV,
+                ScalarField::ONE
+            );
+
+            for _ in 0..ITERATIONS {
+                let a = <$group>::rand(&mut rng).into_affine();
+
+                assert_eq!(a.mul_by_cofactor_to_group(), a * cofactor);
+                assert_eq!(a.mul_by_cofactor(), (a * cofactor).into_affine());
+                assert_eq!(a.mul_by_cofactor().mul_by_cofactor_inv(), a.into_group());
+                assert_eq!(a.clear_cofactor(), a.mul_by_cofactor());
+            }
+        }
+
+        #[test]
+        fn test_serialization() {
+            let mut rng = ark_std::test_rng();
+            let zero = <$group>::zero();
+            for _ in 0..ITERATIONS {
+                let a = <$group>::rand(&mut rng);
+                for compress in [Compress::Yes, Compress::No] {
+                    for validate in [Validate::Yes, Validate::No] {
+                        for p in [a, zero] {
+                            let mut bytes = Vec::new();
+                            p.serialize_with_mode(&mut bytes, compress).unwrap();
+                            assert_eq!(bytes.len(), p.serialized_size(compress));
+                            let q = <$group>::deserialize_with_mode(&bytes[..], compress, validate)
+                                .unwrap();
+                            assert_eq!(p, q);
+
+                            let mut affine_bytes = Vec::new();
+                            p.into_affine()
+                                .serialize_with_mode(&mut affine_bytes, compress)
+                                .unwrap();
+                            assert_eq!(affine_bytes, bytes);
+                            let q = Affine::deserialize_with_mode(&bytes[..], compress, validate)
+                                .unwrap();
+                            assert_eq!(p.into_affine(), q);
+
+                            // truncated input is rejected
+                            let truncated = &bytes[..bytes.len() - 1];
+                            assert!(matches!(
+                                <$group>::deserialize_with_mode(truncated, compress, validate),
+                                Err(SerializationError::IoError(_))
+                            ));
+                        }
+                    }
+                }
+            }
+
+            // both modes share the canonical compressed encoding
+            let a = <$group>::rand(&mut rng).into_affine();
+            let (mut compressed, mut uncompressed) = (Vec::new(), Vec::new());
+            a.serialize_compressed(&mut compressed).unwrap();
+            a.serialize_uncompressed(&mut uncompressed).unwrap();
+            assert_eq!(compressed, uncompressed);
+            assert_eq!(a.compressed_size(), a.uncompressed_size());
+            assert_eq!(
+                Affine::deserialize_uncompressed(&compressed[..]).unwrap(),
+                a
+            );
+
+            let batch = (0..ITERATIONS)
+                .map(|_| <$group>::rand(&mut rng))
+                .collect::<Vec<_>>();
+            assert!(<$group>::batch_check(batch.iter()).is_ok());
+        }
+
+        #[test]
+        fn test_coordinates() {
+            let mut rng = ark_std::test_rng();
+            for p in [<$group>::rand(&mut rng).into_affine(), Affine::zero()] {
+                let xy = p.xy();
+                assert_eq!(p.x(), xy.map(|(x, _)| x));
+                assert_eq!(p.y(), xy.map(|(_, y)| y));
+            }
+            assert_eq!(Affine::zero().xy(), None);
+        }
+
+        #[test]
+        fn test_zeroize() {
+            let mut rng = ark_std::test_rng();
+            let mut a = <$group>::rand(&mut rng);
+            a.zeroize();
+            assert!(a.is_zero());
+
+            let mut b = <$group>::rand(&mut rng).into_affine();
+            b.zeroize();
+            assert!(b.is_zero());
+        }
+
+        #[test]
+        fn test_from_random_bytes() {
+            let mut rng = ark_std::test_rng();
+            for _ in 0..ITERATIONS {
+                let a = <$group>::rand(&mut rng).into_affine();
+                let mut bytes = Vec::new();
+                a.serialize_compressed(&mut bytes).unwrap();
+                assert_eq!(Affine::from_random_bytes(&bytes), Some(a));
+            }
+        }
+    };
+}
@@ -0,0 +1,322 @@
+//! Variable-time multi-scalar multiplication with width-w tau-adic NAFs.
+//!
+//! On a Koblitz curve with `a = 0` the Frobenius map `tau` acts on the prime
+//! order subgroup as a root of `tau^2 + tau + 2 = 0`, so a scalar can be
+//! rewritten as `sum k_i tau^i` with sparse odd digits and evaluated with a
+//! Frobenius (four squarings) in place of each doubling. Scalars are first
+//! reduced modulo `delta = (tau^233 - 1)/(tau - 1)`, which acts as zero on
+//! the subgroup and has norm `r`, so the expansions stay about 233 digits
+//! long. See Solinas, "Efficient Arithmetic on Koblitz Curves", and
+//! Algorithms 3.62-3.70 of the Guide to Elliptic Curve Cryptography.
+
+use crate::affine::Xsk233Affine;
+use crate::inner::InnerPoint;
+use crate::xsk233::Fr;
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::{BigInt, Sign};
+use std::sync::OnceLock;
+
+/// `mu = (-1)^(1 - a)` for `a = 0`; `tau^2 = mu*tau - 2`.
+const MU: i128 = -1;
+
+/// `delta = DELTA_0 + DELTA_1*tau` with `N(delta) = r`.
+const DELTA_0: i128 = 16332424505647875174294325944499003;
+const DELTA_1: i128 = 44192136247082304936052160908934886;
+
+/// Window width for arbitrary bases: 8 precomputed points.
+const WINDOW: u32 = 5;
+
+/// Window width for the fixed generator table: 32 precomputed points.
+const GENERATOR_WINDOW: u32 = 7;
+
+/// Element `a0 + a1*tau` of `Z[tau]`.
+type ZTau = (i128, i128);
+
+/// Signed odd digits of a width-w tau-adic NAF, least significant first.
+/// Digit `u` stands for `sign(u) * alpha_|u|`.
+type Tnaf = Vec<i8>;
+
+/// Rounds `(l0 + l1*tau) / d` to a nearby element of `Z[tau]` so that the
+/// remainder has small norm (Algorithm 3.62 with the divisions kept exact).
+fn round_div(l0: &BigInt, l1: &BigInt, d: &BigInt) -> (BigInt, BigInt) {
+    let round = |n: &BigInt| -> (BigInt, BigInt) {
+        // nearest integer to n/d, and the scaled error n - f*d
+        let twice: BigInt = n * 2 + d;
+        let den: BigInt = d * 2;
+        let mut f = &twice / &den;
+        if twice.sign() == Sign::Minus && &f * &den != twice {
+            f -= 1;
+        }
+        let e = n - &f * d;
+        (f, e)
+    };
+    let (f0, e0) = round(l0);
+    let (f1, e1) = round(l1);
+
+    // all comparisons of the errors are scaled by d
+    let mu = BigInt::from(MU);
+    let eta: BigInt = &e0 * 2 + &mu * &e1;
+    let three: BigInt = &e0 - &mu * &e1 * 3;
+    let four: BigInt = &e0 + &mu * &e1 * 4;
+    let d2: BigInt = d * 2;
+    let neg_d = -d;
+    let neg_d2 = -&d2;
+
+    let (mut h0, mut h1) = (0i128, 0i128);
+    if eta >= *d {
+        if three < neg_d {
+            h1 = MU;
+        } else {
+            h0 = 1;
+        }
+    } else if four >= d2 {
+        h1 = MU;
+    }
+    if eta < neg_d {
+        if three >= *d {
+            h1 = -MU;
+        } else {
+            h0 = -1;
+        }
+    } else if four < neg_d2 {
+        h1 = -MU;
+    }
+
+    (f0 + h0, f1 + h1)
+}
+
+/// Returns `rho = k mod delta` with `rho` of minimal norm.
+fn reduce(k: &Fr) -> ZTau {
+    let k = BigInt::from_bytes_le(Sign::Plus, &k.into_bigint().to_bytes_le());
+    let r = BigInt::from_bytes_le(Sign::Plus, &Fr::MODULUS.to_bytes_le());
+    let (d0, d1) = (BigInt::from(DELTA_0), BigInt::from(DELTA_1));
+
+    // k / delta = k * conj(delta) / r, conj(d0 + d1*tau) = (d0 + mu*d1) - d1*tau
+    let l0 = &k * (&d0 + MU * &d1);
+    let l1 = -(&k * &d1);
+    let (q0, q1) = round_div(&l0, &l1, &r);
+
+    // rho = k - q*delta
+    let qd0 = &q0 * &d0 - 2 * &q1 * &d1;
+    let qd1 = &q0 * &d1 + &q1 * &d0 + MU * &q1 * &d1;
+    let rho0 = k - qd0;
+    let rho1 = -qd1;
+
+    let to_i128 = |v: BigInt| i128::try_from(v).expect("reduced scalars have about 117 bits");
+    (to_i128(rho0), to_i128(rho1))
+}
+
+/// Lucas sequence `U_0 = 0, U_1 = 1, U_{k+1} = mu*U_k - 2*U_{k-1}`, so that
+/// `tau^k = U_k*tau - 2*U_{k-1}`.
+fn lucas(k: u32) -> (i128, i128) {
+    let (mut prev, mut cur) = (0i128, 1i128);
+    for _ in 1..k {
+        (prev, cur) = (cur, MU * cur - 2 * prev);
+    }
+    (prev, cur)
+}
+
+/// Digit representatives of a width-w TNAF.
+struct Window {
+    width: u32,
+    /// `t_w` with `tau = t_w (mod tau^w)` viewed modulo `2^w`.
+    t_w: i128,
+    /// `alpha_u = u mod tau^w` for `u = 1, 3, ..., 2^(w-1) - 1`.
+    alphas: Vec<ZTau>,
+}
+
+impl Window {
+    fn new(width: u32) -> Self {
+        let modulus = 1i128 << width;
+        let (u_prev, u_w) = lucas(width);
+
+        // t_w = 2*U_{w-1} / U_w mod 2^w; U_w is odd
+        let inv = (1..modulus)
+            .step_by(2)
+            .find(|v| (u_w * v).rem_euclid(modulus) == 1)
+            .unwrap();
+        let t_w = (2 * u_prev * inv).rem_euclid(modulus);
+
+        let (tw0, tw1) = (-2 * u_prev, u_w);
+        let norm = BigInt::from(modulus);
+        let alphas = (1..modulus / 2)
+            .step_by(2)
+            .map(|u| {
+                // u / tau^w = u * conj(tau^w) / 2^w
+                let l0 = BigInt::from(u * (tw0 + MU * tw1));
+                let l1 = BigInt::from(-u * tw1);
+                let (q0, q1) = round_div(&l0, &l1, &norm);
+                let (q0, q1) = (i128::try_from(q0).unwrap(), i128::try_from(q1).unwrap());
+                (
+                    u - (q0 * tw0 - 2 * q1 * tw1),
+                    -(q0 * tw1 + q1 * tw0 + MU * q1 * tw1),
+                )
+            })
+            .collect();
+
+        Self { width, t_w, alphas }
+    }
+
+    /// Width-w TNAF of `k` (Algorithm 3.69), computed from `k mod delta`.
+    fn recode(&self, k: &Fr) -> Tnaf {
+        let modulus = 1i128 << self.width;
+        let (mut r0, mut r1) = reduce(k);
+        let mut digits = Vec::with_capacity(240);
+
+        while r0 != 0 || r1 != 0 {
+            let mut digit = 0;
+            if r0 & 1 == 1 {
+                let mut u = (r0 + r1 * self.t_w).rem_euclid(modulus);
+                if u >= modulus / 2 {
+                    u -= modulus;
+                }
+                let (b, c) = self.alphas[(u.unsigned_abs() / 2) as usize];
+                if u > 0 {
+                    r0 -= b;
+                    r1 -= c;
+                } else {
+                    r0 += b;
+                    r1 += c;
+                }
+                digit = u as i8;
+            }
+            digits.push(digit);
+
+            // divide by tau
+            (r0, r1) = (r1 + MU * (r0 >> 1), -(r0 >> 1));
+        }
+        digits
+    }
+
+    /// Returns `alpha_u * P` for every digit representative.
+    fn precompute(&self, point: &InnerPoint) -> Vec<InnerPoint> {
+        let tau_p = point.frobenius();
+        self.alphas
+            .iter()
+            .map(|(b, c)| mul_small(point, *b).add(&mul_small(&tau_p, *c)))
+            .collect()
+    }
+}
+
+/// Multiplies by a small signed integer with plain double-and-add.
+fn mul_small(point: &InnerPoint, k: i128) -> InnerPoint {
+    let mut res = InnerPoint::NEUTRAL;
+    let n = k.unsigned_abs();
+    for i in (0..128 - n.leading_zeros()).rev() {
+        res = res.double();
+        if (n >> i) & 1 == 1 {
+            res = res.add(point);
+        }
+    }
+    if k < 0 { res.neg() } else { res }
+}
+
+fn window() -> &'static Window {
+    static WINDOW_TABLE: OnceLock<Window> = OnceLock::new();
+    WINDOW_TABLE.get_or_init(|| Window::new(WINDOW))
+}
+
+fn generator_table() -> &'static (Window, Vec<InnerPoint>) {
+    static GENERATOR_TABLE: OnceLock<(Window, Vec<InnerPoint>)> = OnceLock::new();
+    GENERATOR_TABLE.get_or_init(|| {
+        let window = Window::new(GENERATOR_WINDOW);
+        let table = window.precompute(&InnerPoint::from_affine(&Xsk233Affine::generator()));
+        (window, table)
+    })
+}
+
+/// Evaluates `sum k_j * P_j` by interleaving the TNAFs: one Frobenius per
+/// digit position, one addition per non-zero digit.
+fn interleave(terms: &[(Tnaf, &[InnerPoint])]) -> InnerPoint {
+    let len = terms.iter().map(|(d, _)| d.len()).max().unwrap_or(0);
+    let mut acc = InnerPoint::NEUTRAL;
+    for i in (0..len).rev() {
+        acc = acc.frobenius();
+        for (digits, table) in terms {
+            match digits.get(i) {
+                Some(&u) if u > 0 => acc = acc.add(&table[u as usize / 2]),
+                Some(&u) if u < 0 => acc = acc.add(&table[u.unsigned_abs() as usize / 2].neg()),
+                _ => {}
+            }
+        }
+    }
+    acc
+}
+
+/// Computes `a*P + b*Q` in variable time.
+pub(crate) fn double_mul(a: &Fr, p: &Xsk233Affine, b: &Fr, q: &Xsk233Affine) -> Xsk233Affine {
+    let window = window();
+    let p_table = window.precompute(&InnerPoint::from_affine(p));
+    let q_table = window.precompute(&InnerPoint::from_affine(q));
+    interleave(&[(window.recode(a), &p_table), (window.recode(b), &q_table)]).into_affine()
+}
+
+/// Computes `a*G + b*P` in variable time, using a cached wider table for `G`.
+pub(crate) fn mul_add_generator(a: &Fr, b: &Fr, p: &Xsk233Affine) -> Xsk233Affine {
+    let (g_window, g_table) = generator_table();
+    let window = window();
+    let p_table = window.precompute(&InnerPoint::from_affine(p));
+    interleave(&[(g_window.recode(a), g_table), (window.recode(b), &p_table)]).into_affine()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{Field, Zero};
+    use ark_std::UniformRand;
+    use rand::thread_rng;
+
+    /// Evaluates a TNAF as an element of `Z[tau]`.
+    fn evaluate(window: &Window, digits: &Tnaf) -> ZTau {
+        let (mut a0, mut a1) = (0i128, 0i128);
+        for &u in digits.iter().rev() {
+            // multiply by tau: (a0 + a1*tau)*tau = -2*a1 + (a0 + mu*a1)*tau
+            (a0, a1) = (-2 * a1, a0 + MU * a1);
+            if u != 0 {
+                let (b, c) = window.alphas[u.unsigned_abs() as usize / 2];
+                let s = u.signum() as i128;
+                a0 += s * b;
+                a1 += s * c;
+            }
+        }
+        (a0, a1)
+    }
+
+    #[test]
+    fn test_recoding() {
+        let mut rng = thread_rng();
+        for width in [WINDOW, GENERATOR_WINDOW] {
+            let window = Window::new(width);
+            for _ in 0..10 {
+                let k = Fr::rand(&mut rng);
+                let digits = window.recode(&k);
+                assert_eq!(evaluate(&window, &digits), reduce(&k));
+                assert!(digits.len() <= 233 + width as usize);
+
+                // at most one non-zero digit in any w consecutive positions
+                let nonzero: Vec<_> = (0..digits.len()).filter(|i| digits[*i] != 0).collect();
+                assert!(nonzero.windows(2).all(|p| p[1] - p[0] >= width as usize));
+            }
+        }
+    }
+
+    #[test]
+    fn test_alpha_representatives() {
+        // alpha_u = u mod tau^w, i.e. alpha_u - u is divisible by tau^w
+        let window = Window::new(WINDOW);
+        for (i, (b, c)) in window.alphas.iter().enumerate() {
+            let (mut r0, mut r1) = (b - (2 * i as i128 + 1), *c);
+            for _ in 0..WINDOW {
+                assert_eq!(r0 & 1, 0);
+                (r0, r1) = (r1 + MU * (r0 >> 1), -(r0 >> 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduction() {
+        assert!(reduce(&Fr::zero()) == (0, 0));
+        assert!(reduce(&Fr::ONE) == (1, 0));
+    }
+}
@@ -0,0 +1,183 @@
+use ark_ec::CurveConfig;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ff::{Fp256, MontBackend, MontConfig, MontFp};
+
+pub use crate::xsk233::Fq;
+
+pub mod affine;
+pub mod group;
+
+pub use affine::Xsb233Affine;
+pub use group::Xsb233Projective;
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Xsb233CurveConfig;
+
+#[derive(MontConfig)]
+#[modulus = "6901746346790563787434755862277025555839812737345013555379383634485463"]
+#[generator = "5"]
+pub struct FrConfig;
+pub type Fr = Fp256<MontBackend<FrConfig, 4>>;
+
+impl CurveConfig for Xsb233CurveConfig {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+
+    /// COFACTOR = 2
+    const COFACTOR: &'static [u64] = &[0x2];
+
+    /// COFACTOR_INV = COFACTOR^{-1} mod r
+    #[rustfmt::skip]
+    const COFACTOR_INV: Fr = MontFp!("3450873173395281893717377931138512777919906368672506777689691817242732");
+}
+
+impl SWCurveConfig for Xsb233CurveConfig {
+    const COEFF_A: Fq = MontFp!("1");
+
+    const COEFF_B: Fq =
+        MontFp!("2760497980029204187078845502377898520307707256259003964398570147123373");
+
+    const GENERATOR: Affine<Self> = Affine::new_unchecked(G_GENERATOR_X, G_GENERATOR_Y);
+}
+
+pub const G_GENERATOR_X: Fq =
+    MontFp!("6761246501583409083997096882159824046681246465812468867444643442021771");
+
+pub const G_GENERATOR_Y: Fq =
+    MontFp!("6912913004411390932094889411904587007871508723951293564567204383952978");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint_to_le_bytes;
+    use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+    use ark_ff::{AdditiveGroup, Field, PrimeField, Zero};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::UniformRand;
+    use rand::thread_rng;
+    use std::io::Cursor;
+    use xs233_sys::{
+        xsb233_add, xsb233_double, xsb233_equals, xsb233_generator, xsb233_mul, xsb233_neg,
+        xsb233_neutral, xsb233_point,
+    };
+
+    fn rand_xsb233_sys_point(scalar: Fr) -> xsb233_point {
+        let scalar_bytes = bigint_to_le_bytes(scalar.into_bigint());
+
+        unsafe {
+            let g = xsb233_generator;
+
+            let mut res = xsb233_neutral;
+            xsb233_mul(
+                &mut res,
+                &g,
+                scalar_bytes.as_ptr() as *const _,
+                scalar_bytes.len(),
+            );
+
+            res
+        }
+    }
+
+    fn rand_xsb233_ark_point(scalar: Fr) -> Xsb233Projective {
+        Xsb233Affine::generator() * scalar
+    }
+
+    #[test]
+    fn test_scalar_mul_correspondence() {
+        unsafe {
+            let mut rng = thread_rng();
+            let scalar = Fr::rand(&mut rng);
+
+            let res_xsb = rand_xsb233_sys_point(scalar);
+            let res_ark = rand_xsb233_ark_point(scalar);
+
+            let equals = xsb233_equals(res_ark.inner(), &res_xsb);
+            assert!(equals != 0);
+
+            // (r - 1)*G + G is the neutral element
+            let minus_g = Xsb233Affine::generator() * -Fr::ONE;
+            assert!((minus_g + Xsb233Affine::generator()).is_zero());
+        }
+    }
+
+    #[test]
+    fn test_addition_correspondence() {
+        unsafe {
+            let mut rng = thread_rng();
+            let scalar1 = Fr::rand(&mut rng);
+            let scalar2 = Fr::rand(&mut rng);
+
+            let p1_xsb = rand_xsb233_sys_point(scalar1);
+            let p2_xsb = rand_xsb233_sys_point(scalar2);
+
+            let mut p12_xsb = xsb233_neutral;
+            xsb233_add(&mut p12_xsb, &p1_xsb, &p2_xsb);
+
+            let p12_ark = rand_xsb233_ark_point(scalar1) + rand_xsb233_ark_point(scalar2);
+            assert!(xsb233_equals(p12_ark.inner(), &p12_xsb) != 0);
+
+            // scalar and point arithmetic agree
+            assert_eq!(p12_ark, rand_xsb233_ark_point(scalar1 + scalar2));
+        }
+    }
+
+    #[test]
+    fn test_double_and_negation_correspondence() {
+        unsafe {
+            let mut rng = thread_rng();
+            let scalar = Fr::rand(&mut rng);
+
+            let p_xsb = rand_xsb233_sys_point(scalar);
+            let p_ark = rand_xsb233_ark_point(scalar);
+
+            let mut double_xsb = xsb233_neutral;
+            xsb233_double(&mut double_xsb, &p_xsb);
+            assert!(xsb233_equals(p_ark.double().inner(), &double_xsb) != 0);
+
+            let mut neg_xsb = xsb233_neutral;
+            xsb233_neg(&mut neg_xsb, &p_xsb);
+            assert!(xsb233_equals((-p_ark.into_affine()).inner(), &neg_xsb) != 0);
+        }
+    }
+
+    #[test]
+    fn test_msm() {
+        let mut rng = thread_rng();
+        let scalar1 = Fr::rand(&mut rng);
+        let scalar2 = Fr::rand(&mut rng);
+
+        let g = Xsb233Affine::generator() * scalar1;
+        let h = Xsb233Affine::generator() * scalar2;
+
+        let msm = Xsb233Projective::msm(
+            &[Xsb233Affine::generator(), Xsb233Affine::generator()],
+            &[scalar1, scalar2],
+        )
+        .unwrap();
+
+        assert_eq!(g + h, msm);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let g = Xsb233Affine::generator() * Fr::from(100);
+
+        let mut res = Vec::new();
+        g.serialize_compressed(&mut res).unwrap();
+
+        let g_deserialized = Xsb233Affine::deserialize_compressed(Cursor::new(res)).unwrap();
+        assert_eq!(g, g_deserialized);
+        assert_eq!(
+            Xsb233Affine::from_bytes(&g.into_affine().to_bytes()),
+            Some(g.into_affine())
+        );
+    }
+}
+
+#[cfg(test)]
+mod conformance {
+    use super::*;
+
+    crate::test_group!(Xsb233Projective);
+}
@@ -0,0 +1,16 @@
+use crate::xsb233::{Xsb233CurveConfig, Xsb233Projective};
+
+crate::impl_xs233_affine!(
+    Xsb233Affine,
+    Xsb233Projective,
+    Xsb233CurveConfig,
+    point: xsb233_point,
+    decode: xsb233_decode,
+    encode: xsb233_encode,
+    equals: xsb233_equals,
+    generator: xsb233_generator,
+    neutral: xsb233_neutral,
+    neg: xsb233_neg,
+    mul: xsb233_mul,
+    record: ignore_op,
+);
@@ -0,0 +1,21 @@
+use crate::xsb233::{Xsb233Affine, Xsb233CurveConfig};
+use ark_ec::VariableBaseMSM;
+
+crate::impl_xs233_projective!(
+    Xsb233Projective,
+    Xsb233Affine,
+    Xsb233CurveConfig,
+    point: xsb233_point,
+    decode: xsb233_decode,
+    encode: xsb233_encode,
+    equals: xsb233_equals,
+    neutral: xsb233_neutral,
+    neg: xsb233_neg,
+    add: xsb233_add,
+    sub: xsb233_sub,
+    double: xsb233_double,
+    mul: xsb233_mul,
+    record: ignore_op,
+);
+
+impl VariableBaseMSM for Xsb233Projective {}
@@ -1,6 +1,6 @@
 use ark_ec::CurveConfig;
 use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
-use ark_ff::{Field, Fp256, MontBackend, MontConfig, MontFp};
+use ark_ff::{Fp256, MontBackend, MontConfig, MontFp};
 
 #[derive(Clone, Default, PartialEq, Eq)]
 pub struct Xsk233CurveConfig;
@@ -21,12 +21,15 @@ impl CurveConfig for Xsk233CurveConfig {
     type BaseField = Fq;
     type ScalarField = Fr;
 
-    /// COFACTOR = 1
+    /// COFACTOR = 4
+    ///
+    /// xsk233 elements always lie in the prime order subgroup, so
+    /// multiplying by the cofactor is a bijection on the group.
     const COFACTOR: &'static [u64] = &[0x4];
 
-    /// COFACTOR_INV = COFACTOR^{-1} mod r = 1
+    /// COFACTOR_INV = COFACTOR^{-1} mod r
     #[rustfmt::skip]
-    const COFACTOR_INV: Fr = Fr::ONE;
+    const COFACTOR_INV: Fr = MontFp!("862718293348820473429344482784628190142735247215563031582021756185336");
 }
 impl SWCurveConfig for Xsk233CurveConfig {
     const COEFF_A: Fq = MontFp!("0");
@@ -54,9 +57,10 @@ mod tests {
     use crate::bigint_to_le_bytes;
     use crate::group::Xsk233Projective;
     use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
-    use ark_ff::{AdditiveGroup, PrimeField};
+    use ark_ff::{AdditiveGroup, Field, PrimeField, ToConstraintField, Zero};
     use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use ark_std::UniformRand;
+    use proptest::prelude::*;
     use rand::thread_rng;
     use std::hash::{DefaultHasher, Hash, Hasher};
     use std::io::Cursor;
@@ -214,6 +218,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_double_mul() {
+        let mut rng = thread_rng();
+        let g = Xsk233Affine::generator();
+        for _ in 0..5 {
+            let a = Fr::rand(&mut rng);
+            let b = Fr::rand(&mut rng);
+            let p = Xsk233Projective::rand(&mut rng).into_affine();
+            let q = Xsk233Projective::rand(&mut rng).into_affine();
+
+            assert_eq!(Xsk233Projective::double_mul(&a, &p, &b, &q), p * a + q * b);
+            assert_eq!(
+                Xsk233Projective::mul_add_generator(&a, &b, &p),
+                g * a + p * b
+            );
+        }
+
+        // edge cases: zero scalars, the neutral element, cancellation
+        let a = Fr::rand(&mut rng);
+        let zero = Xsk233Affine::zero();
+        assert!(Xsk233Projective::double_mul(&Fr::ZERO, &g, &Fr::ZERO, &g).is_zero());
+        assert_eq!(Xsk233Projective::double_mul(&a, &g, &a, &zero), g * a);
+        assert!(Xsk233Projective::double_mul(&a, &g, &-a, &g).is_zero());
+        assert!(Xsk233Projective::mul_add_generator(&a, &-a, &g).is_zero());
+        assert_eq!(
+            Xsk233Projective::mul_add_generator(&-Fr::ONE, &Fr::ZERO, &g),
+            -g
+        );
+    }
+
     #[test]
     fn test_equality() {
         let mut rng = thread_rng();
@@ -239,6 +273,65 @@ mod tests {
         assert_eq!(hasher.finish(), 15456673610726659490);
     }
 
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn scalar_strategy() -> impl Strategy<Value = Fr> {
+        any::<[u8; 32]>().prop_map(|bytes| Fr::from_le_bytes_mod_order(&bytes))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_hash_consistent_across_types(a in scalar_strategy(), b in scalar_strategy()) {
+            let g = Xsk233Affine::generator();
+
+            // the same point reached via different scalar paths and types
+            let p1 = g * (a + b);
+            let p2 = g * a + g * b;
+            let p3 = (g * a).into_affine() + (g * b).into_affine();
+            let p4 = Xsk233Projective::double_mul(&a, &g, &b, &g);
+
+            prop_assert_eq!(p1, p2);
+            prop_assert_eq!(hash_of(&p1), hash_of(&p2));
+            prop_assert_eq!(hash_of(&p1), hash_of(&p3));
+            prop_assert_eq!(hash_of(&p1), hash_of(&p4));
+            prop_assert_eq!(hash_of(&p1), hash_of(&p1.into_affine()));
+        }
+
+        #[test]
+        fn prop_hash_neutral_and_negation(a in scalar_strategy()) {
+            let p = Xsk233Affine::generator() * a;
+            let zero = Xsk233Projective::zero();
+
+            prop_assert_eq!(hash_of(&(p - p)), hash_of(&zero));
+            prop_assert_eq!(hash_of(&(p + (-p))), hash_of(&Xsk233Affine::zero()));
+            prop_assert_eq!(hash_of(&-(-p)), hash_of(&p));
+            prop_assert_eq!(hash_of(&-p), hash_of(&(-p.into_affine())));
+            if !p.is_zero() {
+                prop_assert_ne!(hash_of(&p), hash_of(&-p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_coordinates_and_constraint_field() {
+        let p = (Xsk233Affine::generator() * Fr::from(100)).into_affine();
+        assert!(p.to_k233_coordinates().is_some());
+        assert_eq!(p.xy(), None);
+
+        let elems: Vec<Fr> = p.to_field_elements().unwrap();
+        assert_eq!(
+            elems,
+            Xsk233Projective::from(p).to_field_elements().unwrap()
+        );
+        assert_ne!(elems, (-p).to_field_elements().unwrap());
+    }
+
     #[test]
     fn test_serialization() {
         let scalar1 = Fr::from(100);
@@ -252,3 +345,8 @@ mod tests {
         assert_eq!(g, g_deserialized);
     }
 }
+
+#[cfg(test)]
+mod conformance {
+    crate::test_group!(crate::group::Xsk233Projective);
+}